@@ -0,0 +1,17 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+    id serial PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    -- Only the sha-256 hash of a refresh token is stored
+    token_hash TEXT NOT NULL,
+    family_id TEXT NOT NULL,
+    expires_at BIGINT NOT NULL,
+    is_used BOOLEAN NOT NULL,
+    is_revoked BOOLEAN NOT NULL,
+
+    created_on TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_on TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS refresh_tokens_token_hash_idx ON refresh_tokens (token_hash);
+CREATE INDEX IF NOT EXISTS refresh_tokens_family_id_idx ON refresh_tokens (family_id);
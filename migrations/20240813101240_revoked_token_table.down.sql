@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS user_token_versions;
+DROP TABLE IF EXISTS revoked_tokens;
\ No newline at end of file
@@ -0,0 +1,18 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS revoked_tokens (
+    id serial PRIMARY KEY,
+    jti TEXT NOT NULL UNIQUE,
+    user_id INTEGER NOT NULL,
+    expires_at BIGINT NOT NULL,
+
+    created_on TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_on TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS user_token_versions (
+    user_id INTEGER PRIMARY KEY,
+    token_version INTEGER NOT NULL,
+
+    created_on TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_on TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS role_permissions;
+ALTER TABLE roles DROP COLUMN IF EXISTS inherits_role_id;
\ No newline at end of file
@@ -0,0 +1,33 @@
+-- Add up migration script here
+ALTER TABLE roles ADD COLUMN IF NOT EXISTS inherits_role_id INTEGER;
+
+CREATE TABLE IF NOT EXISTS role_permissions (
+    role_id INTEGER NOT NULL,
+    permission TEXT NOT NULL,
+
+    created_on TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_on TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (role_id, permission)
+);
+
+-- Default roles, admin inherits hr and hr inherits user
+INSERT INTO roles (id, role, is_delete)
+values
+    (1, 'admin', false),
+    (2, 'user', false),
+    (3, 'hr', false)
+ON CONFLICT (id) DO NOTHING;
+SELECT setval(pg_get_serial_sequence('roles', 'id'), GREATEST((SELECT MAX(id) FROM roles), 1));
+
+UPDATE roles SET inherits_role_id = 3 WHERE id = 1;
+UPDATE roles SET inherits_role_id = 2 WHERE id = 3;
+
+INSERT INTO role_permissions (role_id, permission)
+values
+    (1, 'company:manage'),
+    (1, 'role:assign'),
+    (2, 'account:manage'),
+    (2, 'resume:manage'),
+    (2, 'job:apply'),
+    (3, 'job:manage')
+ON CONFLICT (role_id, permission) DO NOTHING;
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS jobs_created_on_idx;
+DROP INDEX IF EXISTS jobs_salary_idx;
+DROP INDEX IF EXISTS jobs_search_vector_idx;
+ALTER TABLE jobs DROP COLUMN IF EXISTS search_vector;
\ No newline at end of file
@@ -0,0 +1,7 @@
+-- Add up migration script here
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS search_vector tsvector
+    GENERATED ALWAYS AS (to_tsvector('english', job_name || ' ' || description)) STORED;
+
+CREATE INDEX IF NOT EXISTS jobs_search_vector_idx ON jobs USING GIN (search_vector);
+CREATE INDEX IF NOT EXISTS jobs_salary_idx ON jobs (salary);
+CREATE INDEX IF NOT EXISTS jobs_created_on_idx ON jobs (created_on);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS application_status_history;
+ALTER TABLE map_resume_job DROP COLUMN IF EXISTS status;
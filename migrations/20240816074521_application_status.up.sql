@@ -0,0 +1,24 @@
+-- Add up migration script here
+ALTER TABLE map_resume_job ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'applied';
+
+CREATE TABLE IF NOT EXISTS application_status_history (
+    id serial PRIMARY KEY,
+    application_id INTEGER NOT NULL,
+    from_status TEXT,
+    to_status TEXT NOT NULL,
+    changed_by INTEGER NOT NULL,
+    note TEXT,
+    changed_at BIGINT NOT NULL,
+
+    created_on TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_on TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS application_status_history_application_idx
+    ON application_status_history (application_id, changed_at, id);
+
+-- Existing applications start their timeline with the apply of the resume owner
+INSERT INTO application_status_history (application_id, from_status, to_status, changed_by, changed_at)
+SELECT m.id, NULL, 'applied', r.user_id, EXTRACT(EPOCH FROM m.created_on)::BIGINT
+FROM map_resume_job m
+JOIN resumes r ON r.id = m.resume_id;
@@ -0,0 +1,24 @@
+-- Add down migration script here
+ALTER TABLE user_token_versions DROP CONSTRAINT IF EXISTS user_token_versions_user_id_fkey;
+ALTER TABLE revoked_tokens DROP CONSTRAINT IF EXISTS revoked_tokens_user_id_fkey;
+ALTER TABLE refresh_tokens DROP CONSTRAINT IF EXISTS refresh_tokens_user_id_fkey;
+ALTER TABLE application_status_history
+    DROP CONSTRAINT IF EXISTS application_status_history_changed_by_fkey,
+    DROP CONSTRAINT IF EXISTS application_status_history_application_id_fkey;
+ALTER TABLE map_resume_job
+    DROP CONSTRAINT IF EXISTS map_resume_job_job_id_fkey,
+    DROP CONSTRAINT IF EXISTS map_resume_job_resume_id_fkey;
+ALTER TABLE resumes DROP CONSTRAINT IF EXISTS resumes_user_id_fkey;
+ALTER TABLE jobs DROP CONSTRAINT IF EXISTS jobs_company_id_fkey;
+ALTER TABLE role_permissions DROP CONSTRAINT IF EXISTS role_permissions_role_id_fkey;
+ALTER TABLE roles DROP CONSTRAINT IF EXISTS roles_inherits_role_id_fkey;
+ALTER TABLE users
+    DROP CONSTRAINT IF EXISTS users_role_id_fkey,
+    DROP CONSTRAINT IF EXISTS users_company_id_fkey;
+
+DROP INDEX IF EXISTS map_resume_job_resume_job_idx;
+DROP INDEX IF EXISTS companies_email_idx;
+DROP INDEX IF EXISTS users_email_idx;
+
+UPDATE users SET company_id = 0 WHERE company_id IS NULL;
+ALTER TABLE users ALTER COLUMN company_id SET NOT NULL;
@@ -0,0 +1,76 @@
+-- Add up migration script here
+-- Users without company keep NULL instead of 0, so the company can be referenced
+ALTER TABLE users ALTER COLUMN company_id DROP NOT NULL;
+UPDATE users SET company_id = NULL
+WHERE company_id IS NOT NULL AND company_id NOT IN (SELECT id FROM companies);
+UPDATE users SET role_id = 2 WHERE role_id NOT IN (SELECT id FROM roles);
+UPDATE roles SET inherits_role_id = NULL
+WHERE inherits_role_id IS NOT NULL AND inherits_role_id NOT IN (SELECT id FROM roles);
+DELETE FROM role_permissions WHERE role_id NOT IN (SELECT id FROM roles);
+
+-- Remove orphan rows, children first
+DELETE FROM application_status_history
+WHERE application_id NOT IN (
+    SELECT m.id FROM map_resume_job m
+    JOIN resumes r ON r.id = m.resume_id AND r.user_id IN (SELECT id FROM users)
+    JOIN jobs j ON j.id = m.job_id AND j.company_id IN (SELECT id FROM companies)
+) OR changed_by NOT IN (SELECT id FROM users);
+DELETE FROM map_resume_job
+WHERE resume_id NOT IN (SELECT id FROM resumes WHERE user_id IN (SELECT id FROM users))
+   OR job_id NOT IN (SELECT id FROM jobs WHERE company_id IN (SELECT id FROM companies));
+DELETE FROM resumes WHERE user_id NOT IN (SELECT id FROM users);
+DELETE FROM jobs WHERE company_id NOT IN (SELECT id FROM companies);
+DELETE FROM refresh_tokens WHERE user_id NOT IN (SELECT id FROM users);
+DELETE FROM revoked_tokens WHERE user_id NOT IN (SELECT id FROM users);
+DELETE FROM user_token_versions WHERE user_id NOT IN (SELECT id FROM users);
+
+-- Keep the first application of a resume to a job
+DELETE FROM application_status_history
+WHERE application_id IN (
+    SELECT id FROM map_resume_job m
+    WHERE EXISTS (
+        SELECT 1 FROM map_resume_job o
+        WHERE o.resume_id = m.resume_id AND o.job_id = m.job_id AND o.id < m.id
+    )
+);
+DELETE FROM map_resume_job m
+WHERE EXISTS (
+    SELECT 1 FROM map_resume_job o
+    WHERE o.resume_id = m.resume_id AND o.job_id = m.job_id AND o.id < m.id
+);
+
+-- Duplicated emails are still referenced, so they are renamed instead of removed
+UPDATE users u SET email = u.email || '#duplicate-' || u.id
+WHERE EXISTS (SELECT 1 FROM users o WHERE o.email = u.email AND o.id < u.id);
+UPDATE companies c SET email = c.email || '#duplicate-' || c.id
+WHERE EXISTS (SELECT 1 FROM companies o WHERE o.email = c.email AND o.id < c.id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS users_email_idx ON users (email);
+CREATE UNIQUE INDEX IF NOT EXISTS companies_email_idx ON companies (email);
+CREATE UNIQUE INDEX IF NOT EXISTS map_resume_job_resume_job_idx ON map_resume_job (resume_id, job_id);
+
+ALTER TABLE users
+    ADD CONSTRAINT users_company_id_fkey FOREIGN KEY (company_id) REFERENCES companies (id),
+    ADD CONSTRAINT users_role_id_fkey FOREIGN KEY (role_id) REFERENCES roles (id);
+ALTER TABLE roles
+    ADD CONSTRAINT roles_inherits_role_id_fkey FOREIGN KEY (inherits_role_id) REFERENCES roles (id);
+ALTER TABLE role_permissions
+    ADD CONSTRAINT role_permissions_role_id_fkey FOREIGN KEY (role_id) REFERENCES roles (id) ON DELETE CASCADE;
+ALTER TABLE jobs
+    ADD CONSTRAINT jobs_company_id_fkey FOREIGN KEY (company_id) REFERENCES companies (id);
+ALTER TABLE resumes
+    ADD CONSTRAINT resumes_user_id_fkey FOREIGN KEY (user_id) REFERENCES users (id);
+ALTER TABLE map_resume_job
+    ADD CONSTRAINT map_resume_job_resume_id_fkey FOREIGN KEY (resume_id) REFERENCES resumes (id),
+    ADD CONSTRAINT map_resume_job_job_id_fkey FOREIGN KEY (job_id) REFERENCES jobs (id);
+ALTER TABLE application_status_history
+    ADD CONSTRAINT application_status_history_application_id_fkey
+        FOREIGN KEY (application_id) REFERENCES map_resume_job (id) ON DELETE CASCADE,
+    ADD CONSTRAINT application_status_history_changed_by_fkey
+        FOREIGN KEY (changed_by) REFERENCES users (id);
+ALTER TABLE refresh_tokens
+    ADD CONSTRAINT refresh_tokens_user_id_fkey FOREIGN KEY (user_id) REFERENCES users (id);
+ALTER TABLE revoked_tokens
+    ADD CONSTRAINT revoked_tokens_user_id_fkey FOREIGN KEY (user_id) REFERENCES users (id);
+ALTER TABLE user_token_versions
+    ADD CONSTRAINT user_token_versions_user_id_fkey FOREIGN KEY (user_id) REFERENCES users (id);
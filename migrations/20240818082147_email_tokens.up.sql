@@ -0,0 +1,21 @@
+-- Add up migration script here
+ALTER TABLE users ADD COLUMN IF NOT EXISTS email_verified BOOLEAN NOT NULL DEFAULT FALSE;
+-- Accounts created before email verification keep signing in
+UPDATE users SET email_verified = TRUE;
+
+CREATE TABLE IF NOT EXISTS email_tokens (
+    id serial PRIMARY KEY,
+    -- Id of the signed token sent by email, the token itself is not stored
+    jti TEXT NOT NULL,
+    user_id INTEGER NOT NULL,
+    purpose TEXT NOT NULL,
+    expires_at BIGINT NOT NULL,
+    used_at BIGINT,
+
+    created_on TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_on TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT email_tokens_user_id_fkey FOREIGN KEY (user_id) REFERENCES users (id)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS email_tokens_jti_idx ON email_tokens (jti);
+CREATE INDEX IF NOT EXISTS email_tokens_user_id_purpose_idx ON email_tokens (user_id, purpose);
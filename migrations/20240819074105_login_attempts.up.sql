@@ -0,0 +1,16 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS login_attempts (
+    id serial PRIMARY KEY,
+    -- account:<email> or ip:<address>
+    subject TEXT NOT NULL,
+    failed_count INTEGER NOT NULL,
+    first_failed_at BIGINT NOT NULL,
+    -- Login of the subject is refused before this time
+    next_attempt_at BIGINT NOT NULL,
+    locked_until BIGINT,
+
+    created_on TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_on TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS login_attempts_subject_idx ON login_attempts (subject);
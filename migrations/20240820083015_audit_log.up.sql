@@ -0,0 +1,21 @@
+-- Add up migration script here
+-- No foreign key on actor_id, entries outlive the users and entities they mention
+CREATE TABLE IF NOT EXISTS audit_log (
+    id serial PRIMARY KEY,
+    -- Empty for operations made by the service itself
+    actor_id INTEGER,
+    action TEXT NOT NULL,
+    entity_type TEXT NOT NULL,
+    entity_id TEXT NOT NULL,
+    -- Snapshots of the entity, empty before a create and after a delete
+    before JSONB,
+    after JSONB,
+    created_at BIGINT NOT NULL,
+
+    created_on TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_on TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS audit_log_entity_idx ON audit_log (entity_type, entity_id);
+CREATE INDEX IF NOT EXISTS audit_log_actor_id_idx ON audit_log (actor_id);
+CREATE INDEX IF NOT EXISTS audit_log_created_at_idx ON audit_log (created_at);
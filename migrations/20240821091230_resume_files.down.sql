@@ -0,0 +1,6 @@
+-- Add down migration script here
+ALTER TABLE resumes
+    DROP COLUMN IF EXISTS file_key,
+    DROP COLUMN IF EXISTS file_name,
+    DROP COLUMN IF EXISTS file_content_type,
+    DROP COLUMN IF EXISTS file_size;
@@ -0,0 +1,7 @@
+-- Add up migration script here
+-- File uploaded with the resume, the content is kept by the blob store under file_key
+ALTER TABLE resumes
+    ADD COLUMN IF NOT EXISTS file_key TEXT,
+    ADD COLUMN IF NOT EXISTS file_name TEXT,
+    ADD COLUMN IF NOT EXISTS file_content_type TEXT,
+    ADD COLUMN IF NOT EXISTS file_size BIGINT;
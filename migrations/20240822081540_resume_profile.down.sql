@@ -0,0 +1,13 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS resumes_skills_idx;
+DROP INDEX IF EXISTS resumes_search_vector_idx;
+
+ALTER TABLE resumes
+    DROP COLUMN IF EXISTS search_vector,
+    DROP COLUMN IF EXISTS file_text,
+    DROP COLUMN IF EXISTS years_of_experience,
+    DROP COLUMN IF EXISTS languages,
+    DROP COLUMN IF EXISTS educations,
+    DROP COLUMN IF EXISTS experiences,
+    DROP COLUMN IF EXISTS skills,
+    DROP COLUMN IF EXISTS headline;
@@ -0,0 +1,17 @@
+-- Add up migration script here
+-- Structured profile of the resume, lists of entries are kept as jsonb
+ALTER TABLE resumes
+    ADD COLUMN IF NOT EXISTS headline TEXT NOT NULL DEFAULT '',
+    ADD COLUMN IF NOT EXISTS skills TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN IF NOT EXISTS experiences JSONB NOT NULL DEFAULT '[]',
+    ADD COLUMN IF NOT EXISTS educations JSONB NOT NULL DEFAULT '[]',
+    ADD COLUMN IF NOT EXISTS languages TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN IF NOT EXISTS years_of_experience INTEGER NOT NULL DEFAULT 0,
+    -- Text extracted from the uploaded pdf
+    ADD COLUMN IF NOT EXISTS file_text TEXT NOT NULL DEFAULT '';
+
+ALTER TABLE resumes ADD COLUMN IF NOT EXISTS search_vector tsvector
+    GENERATED ALWAYS AS (to_tsvector('english', headline || ' ' || file_text)) STORED;
+
+CREATE INDEX IF NOT EXISTS resumes_search_vector_idx ON resumes USING GIN (search_vector);
+CREATE INDEX IF NOT EXISTS resumes_skills_idx ON resumes USING GIN (skills);
@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS resumes_searchable_idx;
+
+ALTER TABLE resumes
+    DROP COLUMN IF EXISTS location,
+    DROP COLUMN IF EXISTS is_searchable;
@@ -0,0 +1,8 @@
+-- Add up migration script here
+-- Resumes are hidden from the candidate search until the candidate opts in
+ALTER TABLE resumes
+    ADD COLUMN IF NOT EXISTS is_searchable BOOLEAN NOT NULL DEFAULT false,
+    ADD COLUMN IF NOT EXISTS location TEXT NOT NULL DEFAULT '';
+
+CREATE INDEX IF NOT EXISTS resumes_searchable_idx ON resumes (years_of_experience)
+    WHERE is_searchable = true AND is_delete = false;
@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE resumes DROP COLUMN IF EXISTS expected_salary;
@@ -0,0 +1,3 @@
+-- Add up migration script here
+-- Salary the candidate expects, used to recommend jobs
+ALTER TABLE resumes ADD COLUMN IF NOT EXISTS expected_salary INTEGER;
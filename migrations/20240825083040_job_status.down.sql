@@ -0,0 +1,7 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS jobs_expires_at_idx;
+
+ALTER TABLE jobs
+    DROP COLUMN IF EXISTS expires_at,
+    DROP COLUMN IF EXISTS published_at,
+    DROP COLUMN IF EXISTS status;
@@ -0,0 +1,12 @@
+-- Add up migration script here
+-- Jobs posted before the statuses stay open, published when they were created
+ALTER TABLE jobs
+    ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'published',
+    ADD COLUMN IF NOT EXISTS published_at BIGINT,
+    ADD COLUMN IF NOT EXISTS expires_at BIGINT;
+
+UPDATE jobs SET published_at = EXTRACT(EPOCH FROM created_on)::BIGINT WHERE published_at IS NULL;
+
+-- Jobs checked by the expiry task
+CREATE INDEX IF NOT EXISTS jobs_expires_at_idx ON jobs (expires_at)
+    WHERE status IN ('published', 'paused');
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS map_resume_job_applied_by_job_idx;
+
+ALTER TABLE map_resume_job DROP CONSTRAINT IF EXISTS map_resume_job_applied_by_fkey;
+ALTER TABLE map_resume_job DROP COLUMN IF EXISTS applied_by;
@@ -0,0 +1,20 @@
+-- Add up migration script here
+-- The applicant is kept on the application so a user applies once to a job, whichever resume
+ALTER TABLE map_resume_job ADD COLUMN IF NOT EXISTS applied_by INTEGER;
+
+UPDATE map_resume_job m SET applied_by = r.user_id
+FROM resumes r
+WHERE r.id = m.resume_id AND m.applied_by IS NULL;
+
+-- Keep the first application of a user to a job, the timeline is removed by the cascade
+DELETE FROM map_resume_job m
+WHERE EXISTS (
+    SELECT 1 FROM map_resume_job o
+    WHERE o.applied_by = m.applied_by AND o.job_id = m.job_id AND o.id < m.id
+);
+
+ALTER TABLE map_resume_job ALTER COLUMN applied_by SET NOT NULL;
+ALTER TABLE map_resume_job
+    ADD CONSTRAINT map_resume_job_applied_by_fkey FOREIGN KEY (applied_by) REFERENCES users (id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS map_resume_job_applied_by_job_idx ON map_resume_job (applied_by, job_id);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS resumes_skill_words_idx;
+DROP FUNCTION IF EXISTS skill_words(TEXT[]);
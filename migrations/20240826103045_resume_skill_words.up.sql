@@ -0,0 +1,15 @@
+-- Add up migration script here
+-- Lowercase words of the skills, the same as tokenize of the service, so the
+-- recommendation finds resumes sharing a word with a job through the index
+CREATE OR REPLACE FUNCTION skill_words(skills TEXT[]) RETURNS TEXT[]
+    LANGUAGE SQL IMMUTABLE PARALLEL SAFE
+    AS $$
+        SELECT ARRAY(
+            SELECT DISTINCT word
+            FROM unnest(skills) skill, regexp_split_to_table(LOWER(skill), '[^[:alnum:]]+') word
+            WHERE word <> ''
+        )
+    $$;
+
+CREATE INDEX IF NOT EXISTS resumes_skill_words_idx ON resumes USING GIN (skill_words(skills))
+    WHERE is_searchable = true AND is_delete = false;
@@ -1,9 +1,46 @@
+use std::collections::HashMap;
+use std::fs;
+use std::net::{AddrParseError, SocketAddr};
+
 use clap::Parser;
-use config::{Config as ConfigLoader, File, FileFormat};
+use config::{Config as ConfigLoader, ConfigError, Environment, File, FileFormat, Map};
+use jsonwebtoken::Algorithm;
 use serde::Deserialize;
 
-#[derive(Deserialize, Debug, Clone)]
+// Environment variables overriding the config, `APP__POSTGRES__URL` sets `postgres.url`
+const ENV_PREFIX: &str = "APP";
+const ENV_SEPARATOR: &str = "__";
+// Suffix of the environment variables naming a file which holds the value
+const ENV_FILE_SUFFIX: &str = "_FILE";
+const DEFAULT_CONFIG_PATH: &str = "config-default.toml";
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "String")]
+pub enum DatabaseKind {
+    Postgres,
+    // Data is lost when the service stops, used for development
+    InMemory,
+}
+
+impl TryFrom<String> for DatabaseKind {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "postgres" => Ok(DatabaseKind::Postgres),
+            "in-memory" => Ok(DatabaseKind::InMemory),
+            _ => Err(format!(
+                "database must be postgres or in-memory, got {}",
+                value
+            )),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
 pub struct PostgresConfig {
+    // Required when the database is postgres
     pub url: String,
 }
 
@@ -15,6 +52,240 @@ pub struct Server {
     pub port: u16,
     // Which PORT the server jaeger collection is listening to
     pub jaeger_port: u16,
+    // Seconds the requests in flight get to finish after a shutdown signal
+    pub shutdown_timeout_seconds: u64,
+}
+
+impl Server {
+    // Address the server binds, the host must be an ip address
+    pub fn address(&self) -> Result<SocketAddr, AddrParseError> {
+        format!("{}:{}", self.host, self.port).parse()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct JwtKeyConfig {
+    // Key id written into the header of issued tokens
+    pub kid: String,
+    // One of HS256, HS512, RS256, EdDSA
+    pub algorithm: Algorithm,
+    // Secret of HMAC algorithms, or a file holding it so the secret isn't committed
+    pub secret: Option<String>,
+    pub secret_file: Option<String>,
+    // PEM files of asymmetric algorithms, a key without private key only verifies tokens
+    pub private_key_path: Option<String>,
+    pub public_key_path: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct JwtConfig {
+    // Which key signs new tokens, other keys only verify tokens during a key rotation
+    pub active_kid: String,
+    pub access_token_minutes: i64,
+    pub refresh_token_days: i64,
+    pub keys: Vec<JwtKeyConfig>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MailerKind {
+    // Send through the smtp server of `email.smtp`
+    Smtp,
+    // Write every email as a file into `email.file_dir`
+    File,
+    // Keep emails in memory, used by tests
+    Memory,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    // Upgrade the connection with STARTTLS, otherwise the connection is TLS from the start
+    #[serde(default)]
+    pub starttls: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct EmailConfig {
+    // Login is refused until the user verified the email
+    pub require_verification: bool,
+    pub verification_token_minutes: i64,
+    pub reset_token_minutes: i64,
+    // Base url of the links sent by email
+    pub app_url: String,
+    pub from: String,
+    pub mailer: MailerKind,
+    pub file_dir: String,
+    pub smtp: Option<SmtpConfig>,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        EmailConfig {
+            require_verification: false,
+            verification_token_minutes: 24 * 60,
+            reset_token_minutes: 30,
+            app_url: "http://localhost:3030".to_string(),
+            from: "Simple Web <no-reply@localhost>".to_string(),
+            mailer: MailerKind::File,
+            file_dir: "mails".to_string(),
+            smtp: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct LoginLimit {
+    // Failed logins answered right away, every later attempt waits for a delay
+    pub free_attempts: i32,
+    // Failed logins locking the login until `lockout_seconds` passed
+    pub max_failures: i32,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct LoginConfig {
+    // Failed logins are forgotten once the first of them is older than the window
+    pub window_seconds: i64,
+    // Delay after the free attempts, doubled with every failed login up to the max
+    pub base_delay_seconds: i64,
+    pub max_delay_seconds: i64,
+    pub lockout_seconds: i64,
+    pub account: LoginLimit,
+    pub ip: LoginLimit,
+}
+
+impl Default for LoginConfig {
+    fn default() -> Self {
+        LoginConfig {
+            window_seconds: 15 * 60,
+            base_delay_seconds: 1,
+            max_delay_seconds: 60,
+            lockout_seconds: 15 * 60,
+            account: LoginLimit {
+                free_attempts: 3,
+                max_failures: 10,
+            },
+            ip: LoginLimit {
+                free_attempts: 10,
+                max_failures: 50,
+            },
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitQuota {
+    // Requests allowed in a period, the bucket refills evenly over the period
+    pub requests: u32,
+    pub period_seconds: u64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RateLimitGroup {
+    pub name: String,
+    // Requests whose path starts with one of the prefixes belong to the group
+    pub path_prefixes: Vec<String>,
+    pub requests: u32,
+    pub period_seconds: u64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    // Quota of the requests matching no group
+    pub default: RateLimitQuota,
+    pub groups: Vec<RateLimitGroup>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            enabled: true,
+            default: RateLimitQuota {
+                requests: 300,
+                period_seconds: 60,
+            },
+            groups: Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageKind {
+    // Write the files into `storage.local_dir`
+    Local,
+    // Put the files into the bucket of `storage.s3`, any S3-compatible service works
+    S3,
+    // Keep the files in memory, used by tests
+    Memory,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct S3Config {
+    // Url of the service, e.g. https://s3.eu-west-1.amazonaws.com or http://minio:9000
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    // Address the bucket in the path instead of the host name, needed by most self-hosted services
+    #[serde(default)]
+    pub path_style: bool,
+}
+
+impl std::fmt::Debug for S3Config {
+    // The secret key is never written into logs
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3Config")
+            .field("endpoint", &self.endpoint)
+            .field("bucket", &self.bucket)
+            .field("region", &self.region)
+            .field("path_style", &self.path_style)
+            .finish()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct StorageConfig {
+    pub backend: StorageKind,
+    // Largest resume file accepted by the upload
+    pub max_file_bytes: u64,
+    pub local_dir: String,
+    pub s3: Option<S3Config>,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        StorageConfig {
+            backend: StorageKind::Local,
+            max_file_bytes: 5 * 1024 * 1024,
+            local_dir: "uploads".to_string(),
+            s3: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct JobConfig {
+    // Seconds between two runs of the task expiring jobs past their expiry date
+    pub expiry_interval_seconds: u64,
+}
+
+impl Default for JobConfig {
+    fn default() -> Self {
+        JobConfig {
+            expiry_interval_seconds: 60,
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -22,26 +293,287 @@ pub struct Config {
     pub log_level: String,
     pub service_name: String,
     pub server: Server,
-    pub database: Option<String>,
+    pub database: DatabaseKind,
+    #[serde(default)]
     pub postgres: PostgresConfig,
+    pub jwt: JwtConfig,
+    #[serde(default)]
+    pub email: EmailConfig,
+    #[serde(default)]
+    pub login: LoginConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub job: JobConfig,
     pub sample_data_url: String,
 }
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Default)]
 pub struct Args {
-    // Config file
-    #[clap(short, long, default_value = "config-default.toml")]
-    pub config_path: String,
+    // Config file, config-default.toml is read when it exists and no file is given
+    #[clap(short, long)]
+    pub config_path: Option<String>,
+    #[clap(long)]
+    pub host: Option<String>,
+    #[clap(long)]
+    pub port: Option<u16>,
+    #[clap(long)]
+    pub log_level: Option<String>,
+    // postgres or in-memory
+    #[clap(long)]
+    pub database: Option<String>,
 }
 
 impl Config {
-    pub fn new() -> Result<Config, config::ConfigError> {
-        let args = Args::parse();
+    // Layers from the lowest priority: defaults, config file, environment variables, cli flags
+    pub fn new() -> Result<Config, ConfigError> {
+        Config::load(&Args::parse(), std::env::vars().collect())
+    }
+
+    pub fn load(args: &Args, env: HashMap<String, String>) -> Result<Config, ConfigError> {
+        let config_path = args.config_path.as_deref().unwrap_or(DEFAULT_CONFIG_PATH);
         let content = ConfigLoader::builder()
-            .add_source(File::new(&args.config_path, FileFormat::Toml))
+            .set_default("log_level", "info")?
+            .set_default("service_name", "rust-api-service")?
+            .set_default("database", "in-memory")?
+            .set_default("sample_data_url", "migrations/postgres/sample.sql")?
+            .set_default("server.host", "0.0.0.0")?
+            .set_default("server.port", 3030)?
+            .set_default("server.jaeger_port", 4317)?
+            .set_default("server.shutdown_timeout_seconds", 30)?
+            // A missing file is an error only when it was asked for
+            .add_source(
+                File::new(config_path, FileFormat::Toml).required(args.config_path.is_some()),
+            )
+            .add_source(
+                Environment::with_prefix(ENV_PREFIX)
+                    .prefix_separator(ENV_SEPARATOR)
+                    .separator(ENV_SEPARATOR)
+                    .source(Some(env_overrides(env)?)),
+            )
+            .set_override_option("server.host", args.host.clone())?
+            .set_override_option("server.port", args.port)?
+            .set_override_option("log_level", args.log_level.clone())?
+            .set_override_option("database", args.database.clone())?
             .build()?;
         let config: Config = content.try_deserialize::<Config>()?;
+        config.validate()?;
 
         Ok(config)
     }
+
+    // Every invalid value is reported at once, so the service fails at startup
+    // instead of at the first request using the value
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+        if !["trace", "debug", "info", "warn", "error"]
+            .contains(&self.log_level.to_lowercase().as_str())
+        {
+            problems.push(format!(
+                "log_level must be one of trace, debug, info, warn, error, got {}",
+                self.log_level
+            ));
+        }
+        if self.server.port == 0 {
+            problems.push("server.port must not be 0".to_string());
+        }
+        if self.server.address().is_err() {
+            problems.push(format!(
+                "server.host must be an ip address, got {}",
+                self.server.host
+            ));
+        }
+        if self.database == DatabaseKind::Postgres
+            && !self.postgres.url.starts_with("postgres://")
+            && !self.postgres.url.starts_with("postgresql://")
+        {
+            problems.push(
+                "postgres.url must be a postgres:// url when the database is postgres".to_string(),
+            );
+        }
+        if self.jwt.access_token_minutes <= 0 || self.jwt.refresh_token_days <= 0 {
+            problems.push("jwt token lifetimes must be positive".to_string());
+        }
+        if !self
+            .jwt
+            .keys
+            .iter()
+            .any(|key| key.kid == self.jwt.active_kid)
+        {
+            problems.push(format!(
+                "jwt.active_kid {} is not one of jwt.keys",
+                self.jwt.active_kid
+            ));
+        }
+        for key in &self.jwt.keys {
+            if key.secret.is_some() && key.secret_file.is_some() {
+                problems.push(format!(
+                    "jwt key {} has both secret and secret_file",
+                    key.kid
+                ));
+            }
+        }
+        if self.email.mailer == MailerKind::Smtp && self.email.smtp.is_none() {
+            problems.push("email.smtp is required by the smtp mailer".to_string());
+        }
+        if self.storage.backend == StorageKind::S3 && self.storage.s3.is_none() {
+            problems.push("storage.s3 is required by the s3 backend".to_string());
+        }
+        if self.storage.max_file_bytes == 0 {
+            problems.push("storage.max_file_bytes must be positive".to_string());
+        }
+        if self.job.expiry_interval_seconds == 0 {
+            problems.push("job.expiry_interval_seconds must be positive".to_string());
+        }
+        let quotas = std::iter::once((
+            "default".to_string(),
+            self.rate_limit.default.requests,
+            self.rate_limit.default.period_seconds,
+        ))
+        .chain(
+            self.rate_limit
+                .groups
+                .iter()
+                .map(|group| (group.name.clone(), group.requests, group.period_seconds)),
+        );
+        for (name, requests, period_seconds) in quotas {
+            if requests == 0 || period_seconds == 0 {
+                problems.push(format!(
+                    "rate limit group {} must allow requests in a non empty period",
+                    name
+                ));
+            }
+        }
+        match problems.is_empty() {
+            true => Ok(()),
+            false => Err(ConfigError::Message(format!(
+                "Invalid config: {}",
+                problems.join("; ")
+            ))),
+        }
+    }
+}
+
+// Environment variables of the service, `APP__X_FILE` is replaced by `APP__X`
+// holding the content of the file, so secrets can be mounted as files
+fn env_overrides(env: HashMap<String, String>) -> Result<Map<String, String>, ConfigError> {
+    let prefix = format!("{}{}", ENV_PREFIX, ENV_SEPARATOR);
+    let mut overrides = Map::new();
+    let mut files = Vec::new();
+    for (key, value) in env {
+        if !key.to_uppercase().starts_with(&prefix) {
+            continue;
+        }
+        match key.to_uppercase().strip_suffix(ENV_FILE_SUFFIX) {
+            Some(name) => files.push((key[..name.len()].to_string(), value)),
+            None => {
+                overrides.insert(key, value);
+            }
+        }
+    }
+    for (key, path) in files {
+        if overrides.contains_key(&key) {
+            return Err(ConfigError::Message(format!(
+                "{} and {}{} are both set",
+                key, key, ENV_FILE_SUFFIX
+            )));
+        }
+        let value = fs::read_to_string(&path).map_err(|e| {
+            ConfigError::Message(format!(
+                "Can't read {}{} file {}: {}",
+                key, ENV_FILE_SUFFIX, path, e
+            ))
+        })?;
+        overrides.insert(key, value.trim_end_matches(['\r', '\n']).to_string());
+    }
+    Ok(overrides)
+}
+
+#[cfg(test)]
+mod config_tests {
+    use std::collections::HashMap;
+    use std::fs;
+
+    use super::{Args, Config, DatabaseKind};
+
+    fn args() -> Args {
+        Args {
+            config_path: Some("config-default.toml".to_string()),
+            ..Args::default()
+        }
+    }
+
+    #[test]
+    fn later_layers_override_earlier_ones() {
+        let secret = std::env::temp_dir().join(format!("postgres-url-{}", rand::random::<u32>()));
+        fs::write(&secret, "postgres://app:secret@db:5432/app\n").unwrap();
+        let env = HashMap::from([
+            ("APP__SERVER__HOST".to_string(), "127.0.0.1".to_string()),
+            ("APP__SERVER__PORT".to_string(), "8080".to_string()),
+            (
+                "APP__POSTGRES__URL_FILE".to_string(),
+                secret.to_string_lossy().to_string(),
+            ),
+            ("PORT".to_string(), "1".to_string()),
+        ]);
+        let args = Args {
+            port: Some(9090),
+            log_level: Some("debug".to_string()),
+            ..args()
+        };
+        let config = Config::load(&args, env).unwrap();
+        fs::remove_file(secret).unwrap();
+
+        assert_eq!(config.server.host, "127.0.0.1");
+        assert_eq!(config.server.port, 9090);
+        assert_eq!(config.server.shutdown_timeout_seconds, 30);
+        assert_eq!(config.log_level, "debug");
+        assert_eq!(config.database, DatabaseKind::Postgres);
+        assert_eq!(config.postgres.url, "postgres://app:secret@db:5432/app");
+    }
+
+    #[test]
+    fn value_and_file_of_same_key_are_refused() {
+        let env = HashMap::from([
+            ("APP__POSTGRES__URL".to_string(), "postgres://a".to_string()),
+            (
+                "APP__POSTGRES__URL_FILE".to_string(),
+                "/run/secrets/url".to_string(),
+            ),
+        ]);
+        let err = Config::load(&args(), env).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "APP__POSTGRES__URL and APP__POSTGRES__URL_FILE are both set"
+        );
+    }
+
+    #[test]
+    fn invalid_values_are_reported_together() {
+        let env = HashMap::from([
+            ("APP__POSTGRES__URL".to_string(), "".to_string()),
+            ("APP__JWT__ACTIVE_KID".to_string(), "missing".to_string()),
+            (
+                "APP__JOB__EXPIRY_INTERVAL_SECONDS".to_string(),
+                "0".to_string(),
+            ),
+        ]);
+        let err = Config::load(&args(), env).unwrap_err().to_string();
+        assert!(err.contains("postgres.url must be a postgres:// url"));
+        assert!(err.contains("jwt.active_kid missing is not one of jwt.keys"));
+        assert!(err.contains("job.expiry_interval_seconds must be positive"));
+
+        let env = HashMap::from([("APP__SERVER__HOST".to_string(), "localhost".to_string())]);
+        let err = Config::load(&args(), env).unwrap_err().to_string();
+        assert!(err.contains("server.host must be an ip address, got localhost"));
+
+        let args = Args {
+            database: Some("mysql".to_string()),
+            ..args()
+        };
+        let err = Config::load(&args, HashMap::new()).unwrap_err().to_string();
+        assert!(err.contains("database must be postgres or in-memory, got mysql"));
+    }
 }
@@ -0,0 +1,236 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use tracing::{event, instrument, Level};
+use warp::http::StatusCode;
+
+use crate::errors::Error;
+use crate::models::application::{
+    ApplicationStatus, NewApplicationStatusChange, UpdateApplicationStatus, WithdrawApplication,
+};
+use crate::models::audit::{AuditAction, AuditEntity};
+use crate::models::job::{JobId, JobStatus};
+use crate::models::map_resume_job::{MapResumeJob, MapResumeJobId};
+use crate::models::permission::Permission;
+use crate::models::store_trait::StoreMethods;
+use crate::services::audit::{record_audit, snapshot};
+use crate::services::jwt::Claims;
+use crate::utils::convert_to_json::{Data, PayloadWithData};
+
+// Check the user is hr of the company posting the job of the application
+async fn is_company_hr(
+    store: &Arc<dyn StoreMethods + Send + Sync>,
+    claims: &Claims,
+    application: &MapResumeJob,
+) -> Result<bool, warp::Rejection> {
+    let permissions = store.get_role_permissions(claims.role_id.clone()).await?;
+    if !permissions.contains(&Permission::ManageJob) {
+        return Ok(false);
+    }
+    let user = store.get_user_by_id(claims.id.clone()).await?;
+    let job = store.get_job_by_id(application.job_id.clone()).await?;
+    Ok(user.company_id == job.company_id)
+}
+
+// Check the user is the candidate owning the resume of the application
+async fn is_candidate(
+    store: &Arc<dyn StoreMethods + Send + Sync>,
+    claims: &Claims,
+    application: &MapResumeJob,
+) -> Result<bool, warp::Rejection> {
+    let resume = store
+        .get_resume_by_id(application.resume_id.clone())
+        .await?;
+    Ok(resume.user_id == claims.id)
+}
+
+// Validate the transition and store it with the user making it
+async fn change_status(
+    store: &Arc<dyn StoreMethods + Send + Sync>,
+    claims: &Claims,
+    application: MapResumeJob,
+    status: ApplicationStatus,
+    note: Option<String>,
+) -> Result<MapResumeJob, warp::Rejection> {
+    if !application.status.can_transition_to(status) {
+        return Err(warp::reject::custom(Error::InvalidStatusTransition(
+            application.status.to_string(),
+            status.to_string(),
+        )));
+    }
+    let application_id = application.id.clone().unwrap();
+    let res = store
+        .update_application_status(NewApplicationStatusChange {
+            application_id: application_id.clone(),
+            from_status: Some(application.status),
+            to_status: status,
+            changed_by: claims.id.clone(),
+            note,
+        })
+        .await?;
+    record_audit(
+        store,
+        Some(claims.id.clone()),
+        AuditAction::Update,
+        AuditEntity::Application,
+        application_id.0,
+        snapshot(&application),
+        snapshot(&res),
+    )
+    .await;
+    // A failure leaves the job open instead of failing the hire
+    if res.status == ApplicationStatus::Hired {
+        if let Err(e) = close_filled_job(store, claims, res.job_id.clone()).await {
+            event!(Level::ERROR, "Close filled job has error: {:?}", e);
+        }
+    }
+    Ok(res)
+}
+
+// Close the job once as many candidates as its quantity are hired
+async fn close_filled_job(
+    store: &Arc<dyn StoreMethods + Send + Sync>,
+    claims: &Claims,
+    job_id: JobId,
+) -> Result<(), Error> {
+    let job = store.get_job_by_id(job_id.clone()).await?;
+    let hired = store.count_hired_applications(job_id.clone()).await?;
+    if job.status.is_final() || hired < job.quantity as i64 {
+        return Ok(());
+    }
+    let res = store
+        .update_job_status(
+            job_id.clone(),
+            job.status,
+            JobStatus::Closed,
+            Utc::now().timestamp(),
+        )
+        .await?;
+    record_audit(
+        store,
+        Some(claims.id.clone()),
+        AuditAction::Update,
+        AuditEntity::Job,
+        job_id.0,
+        snapshot(&job),
+        snapshot(&res),
+    )
+    .await;
+    Ok(())
+}
+
+// Handler for moving application through the pipeline by hr
+#[utoipa::path(
+    put,
+    path = "/api/v1/application/update-status",
+    tag = "application",
+    request_body = UpdateApplicationStatus,
+    responses((status = 200, description = "Application with the new status", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store))]
+pub async fn update_application_status(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+    update: UpdateApplicationStatus,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // Only the candidate can withdraw the application
+    if update.status == ApplicationStatus::Withdrawn {
+        return Err(warp::reject::custom(Error::WithdrawNotAllowed));
+    }
+    let application = store
+        .get_map_resume_job_by_id(update.application_id)
+        .await?;
+    if !is_company_hr(&store, &claims, &application).await? {
+        return Err(warp::reject::custom(Error::Unauthorized));
+    }
+    let res = change_status(&store, &claims, application, update.status, update.note).await?;
+    let payload = PayloadWithData {
+        message: "Success".to_string(),
+        data: Data::MapJobResume(res),
+        page: None,
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
+
+// Handler for withdrawing application by the candidate
+#[utoipa::path(
+    put,
+    path = "/api/v1/application/withdraw",
+    tag = "application",
+    request_body = WithdrawApplication,
+    responses((status = 200, description = "Withdrawn application", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store))]
+pub async fn withdraw_application(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+    withdraw: WithdrawApplication,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let application = store
+        .get_map_resume_job_by_id(withdraw.application_id)
+        .await?;
+    if !is_candidate(&store, &claims, &application).await? {
+        return Err(warp::reject::custom(Error::Unauthorized));
+    }
+    let res = change_status(
+        &store,
+        &claims,
+        application,
+        ApplicationStatus::Withdrawn,
+        withdraw.note,
+    )
+    .await?;
+    let payload = PayloadWithData {
+        message: "Success".to_string(),
+        data: Data::MapJobResume(res),
+        page: None,
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
+
+// Handle for retrieving status timeline of application, for the candidate and hr of the job
+#[utoipa::path(
+    get,
+    path = "/api/v1/application/timeline/{id}",
+    tag = "application",
+    params(
+        ("id" = i32, Path, description = "Id of the item"),
+    ),
+    responses((status = 200, description = "Status changes of the application, oldest first", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store))]
+pub async fn get_application_timeline(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+    application_id: i32,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let application = store
+        .get_map_resume_job_by_id(MapResumeJobId(application_id))
+        .await?;
+    if !is_candidate(&store, &claims, &application).await?
+        && !is_company_hr(&store, &claims, &application).await?
+    {
+        return Err(warp::reject::custom(Error::Unauthorized));
+    }
+    let res = store
+        .get_application_timeline(MapResumeJobId(application_id))
+        .await?;
+    let payload = PayloadWithData {
+        message: "Success".to_string(),
+        data: Data::ApplicationTimeline(res),
+        page: None,
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
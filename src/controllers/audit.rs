@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tracing::instrument;
+use warp::http::StatusCode;
+
+use crate::models::audit::AuditFilter;
+use crate::models::store_trait::StoreMethods;
+use crate::services::jwt::Claims;
+use crate::utils::convert_to_json::{Data, PageInfo, PayloadWithData};
+
+// Handle for retrieving audit entries based on query parameters, newest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/audit",
+    tag = "admin",
+    params(
+        ("actorId" = Option<i32>, Query, description = "Id of the user making the operations"),
+        ("action" = Option<String>, Query, description = "create, update, delete, login_locked or login_unlocked"),
+        ("entityType" = Option<String>, Query, description = "user, company, job, resume, role, application or login"),
+        ("entityId" = Option<String>, Query, description = "Id of the entity, subject of the login attempts for login"),
+        ("from" = Option<i64>, Query, description = "Oldest time of the entries, unix timestamp in seconds, inclusive"),
+        ("to" = Option<i64>, Query, description = "Newest time of the entries, unix timestamp in seconds, inclusive"),
+        ("limit" = Option<i32>, Query, description = "Number of items of the page, at most 100"),
+        ("offset" = Option<i32>, Query, description = "Number of items skipped, replaced by the cursor"),
+        ("cursor" = Option<String>, Query, description = "next_cursor of the previous page"),
+    ),
+    responses((status = 200, description = "Page of audit entries matching the filters", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store))]
+pub async fn get_list_audit(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+    params: HashMap<String, String>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let filter = AuditFilter::extract_audit_filter(params)?;
+    let res = store.get_list_audit_entries(filter).await?;
+    let payload = PayloadWithData {
+        message: "Success".to_string(),
+        page: Some(PageInfo::new(&res, None)),
+        data: Data::ListAudit(res.items),
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
@@ -5,13 +5,23 @@ use tracing::instrument;
 use warp::http::StatusCode;
 
 use crate::errors::Error;
+use crate::models::audit::{AuditAction, AuditEntity};
 use crate::models::company::{Company, CompanyId, NewCompany};
 use crate::models::pagination::Pagination;
 use crate::models::store_trait::StoreMethods;
+use crate::services::audit::{record_audit, snapshot};
 use crate::services::jwt::Claims;
-use crate::utils::convert_to_json::{Data, PayloadNoData, PayloadWithData};
+use crate::utils::convert_to_json::{Data, PageInfo, PayloadWithData};
 
 // Handle for creating company
+#[utoipa::path(
+    post,
+    path = "/api/v1/company/create-company",
+    tag = "company",
+    request_body = NewCompany,
+    responses((status = 201, description = "Created company", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn create_company(
     store: Arc<dyn StoreMethods + Send + Sync>,
@@ -20,21 +30,28 @@ pub async fn create_company(
 ) -> Result<impl warp::Reply, warp::Rejection> {
     //Check valid company
     if let Ok(_res) = store.get_company_by_email(&new_company.email).await {
-        let payload = PayloadNoData {
-            message: "Email company already exists".to_string(),
-        };
-        return Ok(warp::reply::with_status(
-            warp::reply::json(&payload),
-            StatusCode::BAD_REQUEST,
-        ));
+        return Err(warp::reject::custom(Error::AlreadyExists(
+            "Company".to_string(),
+        )));
     }
     let res = store
         .create_company(new_company)
         .await
         .map_err(Error::from)?;
+    record_audit(
+        &store,
+        Some(claims.id.clone()),
+        AuditAction::Create,
+        AuditEntity::Company,
+        res.id.clone().unwrap().0,
+        None,
+        snapshot(&res),
+    )
+    .await;
     let payload = PayloadWithData {
         message: "Success".to_string(),
         data: Data::Company(res),
+        page: None,
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -43,6 +60,15 @@ pub async fn create_company(
 }
 
 // Handle for retrieving company by ID
+#[utoipa::path(
+    get,
+    path = "/api/v1/company/get-company/{id}",
+    tag = "company",
+    params(
+        ("id" = i32, Path, description = "Id of the item"),
+    ),
+    responses((status = 200, description = "Company of the id", body = PayloadWithData))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn get_company(
     store: Arc<dyn StoreMethods + Send + Sync>,
@@ -55,6 +81,7 @@ pub async fn get_company(
     let payload = PayloadWithData {
         message: "Success".to_string(),
         data: Data::Company(res),
+        page: None,
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -62,6 +89,17 @@ pub async fn get_company(
     ))
 }
 // Handle for retrieving list companies based on query parameters
+#[utoipa::path(
+    get,
+    path = "/api/v1/company/list-company",
+    tag = "company",
+    params(
+        ("limit" = Option<i32>, Query, description = "Number of items of the page, at most 100"),
+        ("offset" = Option<i32>, Query, description = "Number of items skipped, replaced by the cursor"),
+        ("cursor" = Option<String>, Query, description = "next_cursor of the previous page"),
+    ),
+    responses((status = 200, description = "Page of companies", body = PayloadWithData))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn get_list_company(
     store: Arc<dyn StoreMethods + Send + Sync>,
@@ -74,13 +112,11 @@ pub async fn get_list_company(
         pagination = Pagination::extract_pagination(params)?;
     }
     // Get list companies with pagination filters
-    let res = store
-        .get_list_company(pagination.limit, pagination.offset)
-        .await
-        .map_err(Error::from)?;
+    let res = store.get_list_company(pagination).await?;
     let payload = PayloadWithData {
         message: "Success".to_string(),
-        data: Data::ListCompany(res),
+        page: Some(PageInfo::new(&res, None)),
+        data: Data::ListCompany(res.items),
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -89,6 +125,14 @@ pub async fn get_list_company(
 }
 
 // Handler for updating company.
+#[utoipa::path(
+    put,
+    path = "/api/v1/company/update-company",
+    tag = "company",
+    request_body = Company,
+    responses((status = 200, description = "Updated company", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn update_company(
     store: Arc<dyn StoreMethods + Send + Sync>,
@@ -97,18 +141,30 @@ pub async fn update_company(
 ) -> Result<impl warp::Reply, warp::Rejection> {
     // Check valid company
     if let Ok(_res) = store.get_company_by_email(&company.email).await {
-        let payload = PayloadNoData {
-            message: "Email company already exists".to_string(),
-        };
-        return Ok(warp::reply::with_status(
-            warp::reply::json(&payload),
-            StatusCode::BAD_REQUEST,
-        ));
+        return Err(warp::reject::custom(Error::AlreadyExists(
+            "Company".to_string(),
+        )));
     }
+    let company_id = company
+        .id
+        .clone()
+        .ok_or_else(|| Error::MissingField("id".to_string()))?;
+    let before = store.get_company_by_id(company_id.clone()).await?;
     let res = store.update_company(company).await.map_err(Error::from)?;
+    record_audit(
+        &store,
+        Some(claims.id.clone()),
+        AuditAction::Update,
+        AuditEntity::Company,
+        company_id.0,
+        snapshot(&before),
+        snapshot(&res),
+    )
+    .await;
     let payload = PayloadWithData {
         message: "Success".to_string(),
         data: Data::Company(res),
+        page: None,
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -117,16 +173,38 @@ pub async fn update_company(
 }
 
 // Handler for deleting company by ID.
+#[utoipa::path(
+    put,
+    path = "/api/v1/company/delete-company",
+    tag = "company",
+    request_body = Company,
+    responses((status = 200, description = "Company is deleted", body = String, content_type = "text/plain")),
+    security(("bearer_auth" = []))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn delete_company(
     store: Arc<dyn StoreMethods + Send + Sync>,
     claims: Claims,
     company: Company,
 ) -> Result<impl warp::Reply, warp::Rejection> {
+    let company_id = company
+        .id
+        .ok_or_else(|| Error::MissingField("id".to_string()))?;
+    let before = store.get_company_by_id(company_id.clone()).await?;
     let _ = store
-        .delete_company(company.id.unwrap())
+        .delete_company(company_id.clone())
         .await
         .map_err(Error::from)?;
+    record_audit(
+        &store,
+        Some(claims.id.clone()),
+        AuditAction::Delete,
+        AuditEntity::Company,
+        company_id.0,
+        snapshot(&before),
+        None,
+    )
+    .await;
     Ok(warp::reply::with_status(
         "Success".to_string(),
         StatusCode::OK,
@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use warp::http::StatusCode;
+
+use crate::models::health::{Health, HealthCheck};
+use crate::models::store_trait::StoreMethods;
+use crate::services::metrics::METRICS;
+
+// Handle for the liveness probe, answered as long as the server runs
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "health",
+    responses((status = 200, description = "The server is running", body = Health))
+)]
+#[instrument(level = "debug")]
+pub async fn get_liveness() -> Result<impl warp::Reply, warp::Rejection> {
+    let health = Health {
+        status: "ok".to_string(),
+        checks: Vec::new(),
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&health),
+        StatusCode::OK,
+    ))
+}
+
+// Handle for the readiness probe, the store has to answer and be migrated
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "health",
+    responses(
+        (status = 200, description = "The server can handle requests", body = Health),
+        (status = 503, description = "A check failed", body = Health),
+    )
+)]
+#[instrument(level = "debug", skip(store))]
+pub async fn get_readiness(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut checks = vec![HealthCheck {
+        name: "store".to_string(),
+        ok: true,
+        detail: None,
+    }];
+    if let Err(e) = store.ping().await {
+        checks[0].ok = false;
+        checks[0].detail = Some(e.to_string());
+    }
+    // Migrations can't be read from a store which doesn't answer
+    let migrations = match checks[0].ok {
+        true => match store.pending_migrations().await {
+            Ok(pending) if pending.is_empty() => None,
+            Ok(pending) => Some(format!("Pending migrations: {:?}", pending)),
+            Err(e) => Some(e.to_string()),
+        },
+        false => Some("Store is unreachable".to_string()),
+    };
+    checks.push(HealthCheck {
+        name: "migrations".to_string(),
+        ok: migrations.is_none(),
+        detail: migrations,
+    });
+
+    let mut health = Health {
+        status: "ready".to_string(),
+        checks,
+    };
+    let status = match health.is_ready() {
+        true => StatusCode::OK,
+        false => {
+            health.status = "not_ready".to_string();
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&health), status))
+}
+
+// Handle for scraping metrics in the Prometheus text format
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "health",
+    responses((status = 200, description = "Request counts and latencies per route, pool connections and password hashing time", body = String, content_type = "text/plain"))
+)]
+#[instrument(level = "debug", skip(store))]
+pub async fn get_metrics(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    METRICS.set_pool_status(store.pool_status());
+    Ok(warp::reply::with_header(
+        warp::reply::with_status(METRICS.encode(), StatusCode::OK),
+        "content-type",
+        "text/plain; version=0.0.4",
+    ))
+}
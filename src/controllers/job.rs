@@ -1,18 +1,35 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
+use chrono::Utc;
 use reqwest::StatusCode;
 use tracing::instrument;
 
 use crate::errors::Error;
-use crate::models::job::{Job, JobId, NewJob};
+use crate::models::application::check_application;
+use crate::models::audit::{AuditAction, AuditEntity};
+use crate::models::job::{Job, JobId, JobStatus, NewJob, UpdateJobStatus};
+use crate::models::job_search::{tokenize, JobSearch};
 use crate::models::map_resume_job::NewMapResumeJob;
 use crate::models::pagination::Pagination;
+use crate::models::permission::Permission;
+use crate::models::recommendation::{extract_limit, skill_words, MAX_RECOMMENDATION_CANDIDATES};
 use crate::models::store_trait::StoreMethods;
+use crate::models::validation::Validator;
+use crate::services::audit::{record_audit, snapshot};
 use crate::services::jwt::Claims;
-use crate::utils::convert_to_json::{Data, PayloadNoData, PayloadWithData};
+use crate::services::recommendation::{recommend_jobs, suggest_candidates};
+use crate::utils::convert_to_json::{Data, PageInfo, PayloadWithData, Problem};
 
 // Handle for create job
+#[utoipa::path(
+    post,
+    path = "/api/v1/job/create-job",
+    tag = "job",
+    request_body = NewJob,
+    responses((status = 201, description = "Created job", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn create_job(
     store: Arc<dyn StoreMethods + Send + Sync>,
@@ -20,20 +37,25 @@ pub async fn create_job(
     new_job: NewJob,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     // Check authorization create job of the user
-    let user = store.get_user_by_id(claims.id).await?;
+    let user = store.get_user_by_id(claims.id.clone()).await?;
     if user.company_id != new_job.company_id.clone() {
-        let payload = PayloadNoData {
-            message: "Un authorization create job".to_string(),
-        };
-        return Ok(warp::reply::with_status(
-            warp::reply::json(&payload),
-            StatusCode::BAD_REQUEST,
-        ));
+        return Err(warp::reject::custom(Error::JobCompanyMismatch));
     }
     let res = store.create_job(new_job).await.map_err(Error::from)?;
+    record_audit(
+        &store,
+        Some(claims.id.clone()),
+        AuditAction::Create,
+        AuditEntity::Job,
+        res.id.clone().unwrap().0,
+        None,
+        snapshot(&res),
+    )
+    .await;
     let payload = PayloadWithData {
         message: "Success".to_string(),
         data: Data::Job(res),
+        page: None,
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -42,18 +64,50 @@ pub async fn create_job(
 }
 
 // Handle for retrieving job by ID
+#[utoipa::path(
+    get,
+    path = "/api/v1/job/get-job/{id}",
+    tag = "job",
+    params(
+        ("id" = i32, Path, description = "Id of the item"),
+    ),
+    responses(
+        (status = 200, description = "Job of the id, hr of the posting company also gets jobs which are not open", body = PayloadWithData),
+        (status = 404, description = "Job is not found or not open", body = Problem, content_type = "application/problem+json"),
+    ),
+    security((), ("bearer_auth" = []))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn get_job(
     store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Option<Claims>,
     job_id: i32,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let res = store
         .get_job_by_id(JobId(job_id))
         .await
         .map_err(Error::from)?;
+    // Drafts, paused, closed, expired and deleted jobs are hidden from everyone
+    // but hr of the company posting the job
+    if !res.is_open(Utc::now().timestamp()) {
+        let is_posting_hr = match &claims {
+            Some(claims) => {
+                store
+                    .get_role_permissions(claims.role_id.clone())
+                    .await?
+                    .contains(&Permission::ManageJob)
+                    && store.get_user_by_id(claims.id.clone()).await?.company_id == res.company_id
+            }
+            None => false,
+        };
+        if !is_posting_hr {
+            return Err(warp::reject::custom(Error::NotFound));
+        }
+    }
     let payload = PayloadWithData {
         message: "Success".to_string(),
         data: Data::Job(res),
+        page: None,
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -61,26 +115,38 @@ pub async fn get_job(
     ))
 }
 
-// Handle for retrieving list jobs based on query parameters
+// Handle for searching jobs based on query parameters
+#[utoipa::path(
+    get,
+    path = "/api/v1/job/list-job",
+    tag = "job",
+    params(
+        ("q" = Option<String>, Query, description = "Text matched against job name and description"),
+        ("location" = Option<String>, Query, description = "Part of the job location"),
+        ("jobLevel" = Option<String>, Query, description = "Job level"),
+        ("companyId" = Option<i32>, Query, description = "Id of the company posting the job"),
+        ("minSalary" = Option<i32>, Query, description = "Lowest salary, inclusive"),
+        ("maxSalary" = Option<i32>, Query, description = "Highest salary, inclusive"),
+        ("sort" = Option<String>, Query, description = "salary or created_on, created_on by default"),
+        ("order" = Option<String>, Query, description = "asc or desc"),
+        ("limit" = Option<i32>, Query, description = "Number of items of the page, at most 100"),
+        ("offset" = Option<i32>, Query, description = "Number of items skipped, replaced by the cursor"),
+        ("cursor" = Option<String>, Query, description = "next_cursor of the previous page"),
+    ),
+    responses((status = 200, description = "Page of published jobs not past their expiry date matching the search, with the total count", body = PayloadWithData))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn get_list_job(
     store: Arc<dyn StoreMethods + Send + Sync>,
     params: HashMap<String, String>,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    // Get pagination from query parameters
-    let mut pagination = Pagination::default();
-
-    if !params.is_empty() {
-        pagination = Pagination::extract_pagination(params)?;
-    }
-    // Get list jobs with pagination filters
-    let res = store
-        .get_list_job(pagination.limit, pagination.offset)
-        .await
-        .map_err(Error::from)?;
+    // Get search filters, sorting and pagination from query parameters
+    let search = JobSearch::extract_job_search(params)?;
+    let res = store.search_jobs(search).await?;
     let payload = PayloadWithData {
         message: "Success".to_string(),
-        data: Data::ListJob(res),
+        page: Some(PageInfo::new(&res.jobs, Some(res.total))),
+        data: Data::ListJob(res.jobs.items),
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -89,28 +155,105 @@ pub async fn get_list_job(
 }
 
 // Handler for updating job.
+#[utoipa::path(
+    put,
+    path = "/api/v1/job/update-job",
+    tag = "job",
+    request_body = Job,
+    responses((status = 200, description = "Updated job", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn update_job(
     store: Arc<dyn StoreMethods + Send + Sync>,
     claims: Claims,
     job: Job,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    // Check authorization update job of the user
-    if let Ok(res) = store.get_user_by_id(claims.id).await {
-        if res.company_id != job.company_id.clone() {
-            let payload = PayloadNoData {
-                message: "Un authorization update job".to_string(),
-            };
-            return Ok(warp::reply::with_status(
-                warp::reply::json(&payload),
-                StatusCode::BAD_REQUEST,
-            ));
-        }
+    let job_id = job
+        .id
+        .clone()
+        .ok_or_else(|| Error::MissingField("id".to_string()))?;
+    // Check authorization update job of the user against the stored job
+    let before = store.get_job_by_id(job_id.clone()).await?;
+    let user = store.get_user_by_id(claims.id.clone()).await?;
+    if user.company_id != before.company_id {
+        return Err(warp::reject::custom(Error::JobCompanyMismatch));
     }
     let res = store.update_job(job).await.map_err(Error::from)?;
+    record_audit(
+        &store,
+        Some(claims.id.clone()),
+        AuditAction::Update,
+        AuditEntity::Job,
+        job_id.0,
+        snapshot(&before),
+        snapshot(&res),
+    )
+    .await;
     let payload = PayloadWithData {
         message: "Success".to_string(),
         data: Data::Job(res),
+        page: None,
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
+
+// Handler for moving job through its lifecycle by hr
+#[utoipa::path(
+    put,
+    path = "/api/v1/job/update-job-status",
+    tag = "job",
+    request_body = UpdateJobStatus,
+    responses((status = 200, description = "Job with the new status", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store))]
+pub async fn update_job_status(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+    update: UpdateJobStatus,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let job = store.get_job_by_id(update.job_id.clone()).await?;
+    let user = store.get_user_by_id(claims.id.clone()).await?;
+    if user.company_id != job.company_id {
+        return Err(warp::reject::custom(Error::JobCompanyMismatch));
+    }
+    if job.is_delete {
+        return Err(warp::reject::custom(Error::JobClosed));
+    }
+    if !job.status.can_transition_to(update.status) {
+        return Err(warp::reject::custom(Error::InvalidStatusTransition(
+            job.status.to_string(),
+            update.status.to_string(),
+        )));
+    }
+    let now = Utc::now().timestamp();
+    // A job past its expiry date needs a later one before it is published again
+    if let (JobStatus::Published, Some(expires_at)) = (update.status, job.expires_at) {
+        Validator::new()
+            .future("expires_at", expires_at, now)
+            .finish()?;
+    }
+    let res = store
+        .update_job_status(update.job_id.clone(), job.status, update.status, now)
+        .await?;
+    record_audit(
+        &store,
+        Some(claims.id.clone()),
+        AuditAction::Update,
+        AuditEntity::Job,
+        update.job_id.0,
+        snapshot(&job),
+        snapshot(&res),
+    )
+    .await;
+    let payload = PayloadWithData {
+        message: "Success".to_string(),
+        data: Data::Job(res),
+        page: None,
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -119,31 +262,63 @@ pub async fn update_job(
 }
 
 // Handler for apply job.
+#[utoipa::path(
+    post,
+    path = "/api/v1/job/apply-job",
+    tag = "job",
+    request_body = NewMapResumeJob,
+    responses((status = 200, description = "Created application", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn apply_job(
     store: Arc<dyn StoreMethods + Send + Sync>,
     claims: Claims,
     new_map_resume_job: NewMapResumeJob,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    // Check job status
-    if let Ok(res) = store.get_job_by_id(new_map_resume_job.job_id.clone()).await {
-        if res.is_delete {
-            let payload = PayloadNoData {
-                message: "Job was deleted, can't apply job".to_string(),
-            };
-            return Ok(warp::reply::with_status(
-                warp::reply::json(&payload),
-                StatusCode::BAD_REQUEST,
-            ));
+    // Check the resume, the job and the applications of the job before applying
+    let resume = store
+        .get_resume_by_id(new_map_resume_job.resume_id.clone())
+        .await?;
+    let job = match store.get_job_by_id(new_map_resume_job.job_id.clone()).await {
+        Ok(job) => job,
+        Err(Error::NotFound) | Err(Error::DatabaseQuery(sqlx::Error::RowNotFound)) => {
+            return Err(warp::reject::custom(Error::JobNotFound))
         }
-    }
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    let already_applied = store
+        .has_applied_job(claims.id.clone(), new_map_resume_job.job_id.clone())
+        .await?;
+    let hired_applications = store
+        .count_hired_applications(new_map_resume_job.job_id.clone())
+        .await?;
+    check_application(
+        &claims.id,
+        &resume,
+        &job,
+        already_applied,
+        hired_applications,
+        Utc::now().timestamp(),
+    )?;
+
     let res = store
-        .create_map_job_resume(new_map_resume_job)
-        .await
-        .map_err(Error::from)?;
+        .create_map_job_resume(new_map_resume_job, claims.id.clone())
+        .await?;
+    record_audit(
+        &store,
+        Some(claims.id),
+        AuditAction::Create,
+        AuditEntity::Application,
+        res.id.clone().unwrap().0,
+        None,
+        snapshot(&res),
+    )
+    .await;
     let payload = PayloadWithData {
         message: "Success".to_string(),
         data: Data::MapJobResume(res),
+        page: None,
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -152,26 +327,125 @@ pub async fn apply_job(
 }
 
 // Handler for deleting company by ID.
+#[utoipa::path(
+    put,
+    path = "/api/v1/job/delete-job",
+    tag = "job",
+    request_body = Job,
+    responses((status = 200, description = "Job is deleted", body = String, content_type = "text/plain")),
+    security(("bearer_auth" = []))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn delete_job(
     store: Arc<dyn StoreMethods + Send + Sync>,
     claims: Claims,
     job: Job,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    if let Ok(res) = store.get_user_by_id(claims.id).await {
-        if res.company_id != job.company_id.clone() {
-            return Ok(warp::reply::with_status(
-                "Un authorization delete job".to_string(),
-                StatusCode::BAD_REQUEST,
-            ));
-        }
+    let job_id = job
+        .id
+        .ok_or_else(|| Error::MissingField("id".to_string()))?;
+    let before = store.get_job_by_id(job_id.clone()).await?;
+    let user = store.get_user_by_id(claims.id.clone()).await?;
+    if user.company_id != before.company_id {
+        return Err(warp::reject::custom(Error::JobCompanyMismatch));
     }
     let _ = store
-        .delete_job(job.id.unwrap())
+        .delete_job(job_id.clone())
         .await
         .map_err(Error::from)?;
+    record_audit(
+        &store,
+        Some(claims.id.clone()),
+        AuditAction::Delete,
+        AuditEntity::Job,
+        job_id.0,
+        snapshot(&before),
+        None,
+    )
+    .await;
     Ok(warp::reply::with_status(
         "Success".to_string(),
         StatusCode::OK,
     ))
 }
+
+// Handle for the jobs recommended to the candidate from the profiles of the own resumes
+#[utoipa::path(
+    get,
+    path = "/api/v1/job/recommended",
+    tag = "job",
+    params(
+        ("limit" = Option<i32>, Query, description = "Number of jobs, 10 by default and at most 50"),
+    ),
+    responses((status = 200, description = "Jobs not applied yet with the best score first, and the score breakdown", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store))]
+pub async fn get_recommended_jobs(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+    params: HashMap<String, String>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let limit = extract_limit(&params)?;
+    let resumes = store
+        .get_list_resume_by_user_id(Pagination::default(), claims.id.clone())
+        .await?
+        .items;
+    // Only the open jobs sharing a skill word with the resumes are scored, the
+    // store leaves out the jobs already applied to
+    let words = skill_words(resumes.iter().flat_map(|resume| &resume.profile.skills));
+    let jobs = store
+        .get_recommendation_jobs(claims.id.clone(), words, MAX_RECOMMENDATION_CANDIDATES)
+        .await?;
+    let payload = PayloadWithData {
+        message: "Success".to_string(),
+        data: Data::ListJobRecommendation(recommend_jobs(&jobs, &resumes, &HashSet::new(), limit)),
+        page: None,
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
+
+// Handle for the searchable resumes suggested to hr of the company posting the job
+#[utoipa::path(
+    get,
+    path = "/api/v1/job/{id}/suggested-candidates",
+    tag = "job",
+    params(
+        ("id" = i32, Path, description = "Id of the job"),
+        ("limit" = Option<i32>, Query, description = "Number of candidates, 10 by default and at most 50"),
+    ),
+    responses((status = 200, description = "Resumes with the best score first, and the score breakdown", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store))]
+pub async fn get_suggested_candidates(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+    job_id: i32,
+    params: HashMap<String, String>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let limit = extract_limit(&params)?;
+    let job = store.get_job_by_id(JobId(job_id)).await?;
+    let user = store.get_user_by_id(claims.id.clone()).await?;
+    if user.company_id != job.company_id {
+        return Err(warp::reject::custom(Error::JobCompanyMismatch));
+    }
+    // Only candidates who opted in to the search and have a skill word found
+    // in the job are scored
+    let words = tokenize(&format!("{} {}", job.job_name, job.description));
+    let resumes = store
+        .get_recommendation_resumes(words, MAX_RECOMMENDATION_CANDIDATES)
+        .await?;
+    let payload = PayloadWithData {
+        message: "Success".to_string(),
+        data: Data::ListCandidateSuggestion(suggest_candidates(&job, &resumes, limit)),
+        page: None,
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
@@ -0,0 +1,21 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use warp::http::StatusCode;
+
+use crate::services::jwt::{Jwt, JwtActions};
+
+// Handle for publishing public keys which verify access tokens, following RFC 7517
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    tag = "jwks",
+    responses((status = 200, description = "JSON Web Key Set of the keys verifying access tokens", body = Object))
+)]
+#[instrument(level = "info", skip(jwt))]
+pub async fn get_jwks(jwt: Arc<Jwt>) -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::with_status(
+        warp::reply::json(&jwt.jwks()),
+        StatusCode::OK,
+    ))
+}
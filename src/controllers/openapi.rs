@@ -0,0 +1,51 @@
+use tracing::instrument;
+use utoipa::OpenApi;
+use warp::http::StatusCode;
+
+use crate::utils::openapi::ApiDoc;
+
+// Redoc page rendering the document of `/api/openapi.json`, the bundle is pinned
+// to a release so the page doesn't change with a new version of Redoc
+const DOCS_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+  <head>
+    <title>Simple Web With Rust API</title>
+    <meta charset="utf-8"/>
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+  </head>
+  <body>
+    <redoc spec-url="/api/openapi.json"></redoc>
+    <script src="https://cdn.jsdelivr.net/npm/redoc@2.1.5/bundles/redoc.standalone.js" crossorigin="anonymous"></script>
+  </body>
+</html>
+"#;
+
+// Handle for publishing the OpenAPI document of every route
+#[utoipa::path(
+    get,
+    path = "/api/openapi.json",
+    tag = "docs",
+    responses((status = 200, description = "OpenAPI 3.1 document of the api", body = Object))
+)]
+#[instrument(level = "info")]
+pub async fn get_openapi() -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::with_status(
+        warp::reply::json(&ApiDoc::openapi()),
+        StatusCode::OK,
+    ))
+}
+
+// Handle for the docs page of the OpenAPI document
+#[utoipa::path(
+    get,
+    path = "/api/docs",
+    tag = "docs",
+    responses((status = 200, description = "Redoc page of the OpenAPI document", body = String, content_type = "text/html"))
+)]
+#[instrument(level = "info")]
+pub async fn get_docs_page() -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::with_status(
+        warp::reply::html(DOCS_PAGE),
+        StatusCode::OK,
+    ))
+}
@@ -2,27 +2,106 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use tracing::instrument;
-use warp::http::StatusCode;
+use warp::http::{header, Response, StatusCode};
 
 use crate::errors::Error;
+use crate::models::audit::{AuditAction, AuditEntity};
 use crate::models::job::JobId;
 use crate::models::pagination::{Pagination, PaginationForJob};
-use crate::models::resume::{NewResume, Resume, ResumeId};
+use crate::models::permission::Permission;
+use crate::models::resume::{
+    check_resume_source, FileUpload, NewResume, Resume, ResumeForm, ResumeId,
+};
+use crate::models::resume_search::ResumeSearch;
 use crate::models::store_trait::StoreMethods;
+use crate::services::audit::{record_audit, snapshot};
 use crate::services::jwt::Claims;
-use crate::utils::convert_to_json::{Data, PayloadNoData, PayloadWithData};
+use crate::services::resume_file::ResumeFiles;
+use crate::utils::convert_to_json::{Data, PageInfo, PayloadWithData, Problem};
 
-// Handle for create resume
-#[instrument(level = "info", skip(store))]
+// Check the user owns the resume, or is hr of a company of a job the resume applied to,
+// or is hr and the candidate opted in to the search
+async fn can_read_resume(
+    store: &Arc<dyn StoreMethods + Send + Sync>,
+    claims: &Claims,
+    resume: &Resume,
+) -> Result<bool, warp::Rejection> {
+    if resume.user_id == claims.id {
+        return Ok(true);
+    }
+    let permissions = store.get_role_permissions(claims.role_id.clone()).await?;
+    if !permissions.contains(&Permission::ManageJob) {
+        return Ok(false);
+    }
+    if resume.is_searchable {
+        return Ok(true);
+    }
+    let user = store.get_user_by_id(claims.id.clone()).await?;
+    for application in store
+        .get_list_job_by_resume(resume.id.clone().unwrap())
+        .await?
+    {
+        let job = store.get_job_by_id(application.job_id).await?;
+        if job.company_id == user.company_id {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+// Handle for create resume, the file is sent with a multipart form
+#[utoipa::path(
+    post,
+    path = "/api/v1/resume/create-resume",
+    tag = "resume",
+    request_body(content(
+        (NewResume = "application/json"),
+        (ResumeForm = "multipart/form-data"),
+    )),
+    responses((status = 201, description = "Created resume", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store, files))]
 pub async fn create_resume(
     store: Arc<dyn StoreMethods + Send + Sync>,
+    files: Arc<ResumeFiles>,
     claims: Claims,
-    new_resume: NewResume,
+    body: (NewResume, Option<FileUpload>),
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    let res = store.create_resume(new_resume).await.map_err(Error::from)?;
+    let (mut new_resume, upload) = body;
+    if new_resume.user_id != claims.id {
+        return Err(warp::reject::custom(Error::ResumeNotOwned));
+    }
+    // A file sent in the json body is ignored, it only comes from the upload
+    new_resume.file = match upload {
+        Some(upload) => Some(files.save(&claims.id, upload).await?),
+        None => None,
+    };
+    check_resume_source(&new_resume.url, &new_resume.file)?;
+    let file = new_resume.file.clone();
+    let res = match store.create_resume(new_resume).await {
+        Ok(res) => res,
+        Err(e) => {
+            if let Some(file) = &file {
+                files.remove(file).await;
+            }
+            return Err(warp::reject::custom(e));
+        }
+    };
+    record_audit(
+        &store,
+        Some(claims.id.clone()),
+        AuditAction::Create,
+        AuditEntity::Resume,
+        res.id.clone().unwrap().0,
+        None,
+        snapshot(&res),
+    )
+    .await;
     let payload = PayloadWithData {
         message: "Success".to_string(),
-        data: Data::Resume(res),
+        data: Data::Resume(Box::new(res)),
+        page: None,
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -31,6 +110,16 @@ pub async fn create_resume(
 }
 
 // Handle for retrieving resume by ID
+#[utoipa::path(
+    get,
+    path = "/api/v1/resume/get-resume/{id}",
+    tag = "resume",
+    params(
+        ("id" = i32, Path, description = "Id of the item"),
+    ),
+    responses((status = 200, description = "Resume of the id", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn get_resume(
     store: Arc<dyn StoreMethods + Send + Sync>,
@@ -41,9 +130,13 @@ pub async fn get_resume(
         .get_resume_by_id(ResumeId(resume_id))
         .await
         .map_err(Error::from)?;
+    if !can_read_resume(&store, &claims, &res).await? {
+        return Err(warp::reject::custom(Error::Unauthorized));
+    }
     let payload = PayloadWithData {
         message: "Success".to_string(),
-        data: Data::Resume(res),
+        data: Data::Resume(Box::new(res)),
+        page: None,
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -52,6 +145,18 @@ pub async fn get_resume(
 }
 
 // Handle for retrieving list resumes based on query parameters by user ID
+#[utoipa::path(
+    get,
+    path = "/api/v1/resume/list-resume-by-user",
+    tag = "resume",
+    params(
+        ("limit" = Option<i32>, Query, description = "Number of items of the page, at most 100"),
+        ("offset" = Option<i32>, Query, description = "Number of items skipped, replaced by the cursor"),
+        ("cursor" = Option<String>, Query, description = "next_cursor of the previous page"),
+    ),
+    responses((status = 200, description = "Page of resumes of the user", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn get_list_resume_by_user_id(
     store: Arc<dyn StoreMethods + Send + Sync>,
@@ -64,12 +169,12 @@ pub async fn get_list_resume_by_user_id(
         pagination = Pagination::extract_pagination(params)?;
     }
     let res = store
-        .get_list_resume_by_user_id(pagination.limit, pagination.offset, claims.id)
-        .await
-        .map_err(Error::from)?;
+        .get_list_resume_by_user_id(pagination, claims.id)
+        .await?;
     let payload = PayloadWithData {
         message: "Success".to_string(),
-        data: Data::ListResume(res),
+        page: Some(PageInfo::new(&res, None)),
+        data: Data::ListResume(res.items),
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -78,9 +183,23 @@ pub async fn get_list_resume_by_user_id(
 }
 
 // Handle for retrieving list resumes based on query parameters by job ID
+#[utoipa::path(
+    get,
+    path = "/api/v1/resume/list-resume-by-job",
+    tag = "resume",
+    params(
+        ("limit" = Option<i32>, Query, description = "Number of items of the page, at most 100"),
+        ("offset" = Option<i32>, Query, description = "Number of items skipped, replaced by the cursor"),
+        ("cursor" = Option<String>, Query, description = "next_cursor of the previous page"),
+        ("jobId" = i32, Query, description = "Id of the job"),
+    ),
+    responses((status = 200, description = "Page of resumes applied to the job", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn get_list_resume_by_job(
     store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
     params: HashMap<String, String>,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let mut pagination = PaginationForJob::default();
@@ -89,22 +208,25 @@ pub async fn get_list_resume_by_job(
         // event!(Level::INFO, pagination = true);
         pagination = PaginationForJob::extract_pagination_job(params)?;
     }
+    // Only hr of the company posting the job see who applied
+    let job = store.get_job_by_id(JobId(pagination.job_id)).await?;
+    let user = store.get_user_by_id(claims.id.clone()).await?;
+    if user.company_id != job.company_id {
+        return Err(warp::reject::custom(Error::Unauthorized));
+    }
     let res = store
-        .get_list_resume_by_job_id(
-            pagination.limit,
-            pagination.offset,
-            JobId(pagination.job_id),
-        )
-        .await
-        .map_err(Error::from)?;
+        .get_list_resume_by_job_id(pagination.pagination(), JobId(pagination.job_id))
+        .await?;
+    let page = PageInfo::new(&res, None);
     let mut resume_list = Vec::new();
-    for element in res {
+    for element in res.items {
         let resume = store.clone().get_resume_by_id(element.resume_id).await?;
         resume_list.push(resume);
     }
     let payload = PayloadWithData {
         message: "Success".to_string(),
         data: Data::ListResume(resume_list),
+        page: Some(page),
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -112,27 +234,112 @@ pub async fn get_list_resume_by_job(
     ))
 }
 
-// Handler for updating resume.
+// Handle for the candidate search of hr over the searchable resumes
+#[utoipa::path(
+    get,
+    path = "/api/v1/resume/search",
+    tag = "resume",
+    params(
+        ("q" = Option<String>, Query, description = "Text matched against the headline and the uploaded file"),
+        ("skills" = Option<String>, Query, description = "Comma separated skills, every one is required"),
+        ("location" = Option<String>, Query, description = "Part of the candidate location"),
+        ("minExperience" = Option<i32>, Query, description = "Fewest years of experience, inclusive"),
+        ("maxExperience" = Option<i32>, Query, description = "Most years of experience, inclusive"),
+        ("limit" = Option<i32>, Query, description = "Number of items of the page, at most 100"),
+        ("offset" = Option<i32>, Query, description = "Number of items skipped, replaced by the cursor"),
+        ("cursor" = Option<String>, Query, description = "next_cursor of the previous page"),
+    ),
+    responses((status = 200, description = "Page of resumes ordered by relevance then years of experience, with the total count", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
 #[instrument(level = "info", skip(store))]
+pub async fn search_resume(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+    params: HashMap<String, String>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let search = ResumeSearch::extract_resume_search(params)?;
+    let res = store.search_resumes(search).await?;
+    let payload = PayloadWithData {
+        message: "Success".to_string(),
+        page: Some(PageInfo::new(&res.resumes, Some(res.total))),
+        data: Data::ListResume(res.resumes.items),
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
+
+// Handler for updating resume, a new file sent with a multipart form replaces the old one
+#[utoipa::path(
+    put,
+    path = "/api/v1/resume/update-resume",
+    tag = "resume",
+    request_body(content(
+        (Resume = "application/json"),
+        (ResumeForm = "multipart/form-data"),
+    )),
+    responses((status = 200, description = "Updated resume", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store, files))]
 pub async fn update_resume(
     store: Arc<dyn StoreMethods + Send + Sync>,
+    files: Arc<ResumeFiles>,
     claims: Claims,
-    resume: Resume,
+    body: (Resume, Option<FileUpload>),
 ) -> Result<impl warp::Reply, warp::Rejection> {
+    let (mut resume, upload) = body;
     // Check valid of resume update
     if claims.id != resume.user_id.clone() {
-        let payload = PayloadNoData {
-            message: "Can't update".to_string(),
-        };
-        return Ok(warp::reply::with_status(
-            warp::reply::json(&payload),
-            StatusCode::BAD_REQUEST,
-        ));
-    }
-    let res = store.update_resume(resume).await.map_err(Error::from)?;
+        return Err(warp::reject::custom(Error::ResumeNotOwned));
+    }
+    let resume_id = resume
+        .id
+        .clone()
+        .ok_or_else(|| Error::MissingField("id".to_string()))?;
+    let before = store.get_resume_by_id(resume_id.clone()).await?;
+    if before.user_id != claims.id {
+        return Err(warp::reject::custom(Error::ResumeNotOwned));
+    }
+    resume.file = match upload {
+        Some(upload) => Some(files.save(&claims.id, upload).await?),
+        None => before.file.clone(),
+    };
+    check_resume_source(&resume.url, &resume.file)?;
+    let file = resume.file.clone();
+    let res = match store.update_resume(resume).await {
+        Ok(res) => res,
+        Err(e) => {
+            if let Some(file) = file.filter(|file| Some(file) != before.file.as_ref()) {
+                files.remove(&file).await;
+            }
+            return Err(warp::reject::custom(e));
+        }
+    };
+    // The replaced file is no longer referenced
+    if let Some(old) = before
+        .file
+        .as_ref()
+        .filter(|old| res.file.as_ref() != Some(*old))
+    {
+        files.remove(old).await;
+    }
+    record_audit(
+        &store,
+        Some(claims.id.clone()),
+        AuditAction::Update,
+        AuditEntity::Resume,
+        resume_id.0,
+        snapshot(&before),
+        snapshot(&res),
+    )
+    .await;
     let payload = PayloadWithData {
         message: "Success".to_string(),
-        data: Data::Resume(res),
+        data: Data::Resume(Box::new(res)),
+        page: None,
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -141,18 +348,90 @@ pub async fn update_resume(
 }
 
 // Handler for deleting resume by ID.
-#[instrument(level = "info", skip(store))]
+#[utoipa::path(
+    put,
+    path = "/api/v1/resume/delete-resume",
+    tag = "resume",
+    request_body = Resume,
+    responses((status = 200, description = "Resume is deleted", body = String, content_type = "text/plain")),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store, files))]
 pub async fn delete_resume(
     store: Arc<dyn StoreMethods + Send + Sync>,
+    files: Arc<ResumeFiles>,
     claims: Claims,
     resume: Resume,
 ) -> Result<impl warp::Reply, warp::Rejection> {
+    let resume_id = resume
+        .id
+        .ok_or_else(|| Error::MissingField("id".to_string()))?;
+    let before = store.get_resume_by_id(resume_id.clone()).await?;
+    if before.user_id != claims.id {
+        return Err(warp::reject::custom(Error::ResumeNotOwned));
+    }
     let _ = store
-        .delete_resume(resume.id.unwrap())
+        .delete_resume(resume_id.clone())
         .await
         .map_err(Error::from)?;
+    // The file of a deleted resume is never downloaded again
+    if let Some(file) = &before.file {
+        files.remove(file).await;
+    }
+    record_audit(
+        &store,
+        Some(claims.id.clone()),
+        AuditAction::Delete,
+        AuditEntity::Resume,
+        resume_id.0,
+        snapshot(&before),
+        None,
+    )
+    .await;
     Ok(warp::reply::with_status(
         "Success".to_string(),
         StatusCode::OK,
     ))
 }
+
+// Handler for downloading the file of a resume, allowed to the owner, hr of the
+// companies of the jobs the resume applied to and hr when the resume is searchable
+#[utoipa::path(
+    get,
+    path = "/api/v1/resume/download/{id}",
+    tag = "resume",
+    params(
+        ("id" = i32, Path, description = "Id of the resume"),
+    ),
+    responses(
+        (status = 200, description = "File of the resume", content_type = "application/octet-stream"),
+        (status = 404, description = "Resume has no file", body = Problem, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store, files))]
+pub async fn download_resume(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    files: Arc<ResumeFiles>,
+    claims: Claims,
+    resume_id: i32,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let resume = store.get_resume_by_id(ResumeId(resume_id)).await?;
+    if !can_read_resume(&store, &claims, &resume).await? {
+        return Err(warp::reject::custom(Error::Unauthorized));
+    }
+    let file = resume
+        .file
+        .filter(|_| !resume.is_delete)
+        .ok_or(Error::NotFound)?;
+    let content = files.load(&file).await?;
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, &file.content_type)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", file.name),
+        )
+        .body(content)
+        .map_err(|_| warp::reject::custom(Error::BlobStore("invalid file header".to_string())))
+}
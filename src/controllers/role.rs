@@ -0,0 +1,303 @@
+use std::sync::Arc;
+
+use tracing::instrument;
+use warp::http::StatusCode;
+
+use crate::errors::Error;
+use crate::models::audit::{AuditAction, AuditEntity};
+use crate::models::role::{Role, RoleId, RoleInfo, RolePermissions, HR_ROLE_ID};
+use crate::models::store_trait::StoreMethods;
+use crate::services::audit::{record_audit, snapshot};
+use crate::services::jwt::Claims;
+use crate::utils::convert_to_json::{Data, PayloadNoData, PayloadWithData};
+
+// Check the parent role exists and inheriting from it does not make a cycle
+async fn valid_inherits_role(
+    store: &Arc<dyn StoreMethods + Send + Sync>,
+    role_id: Option<&RoleId>,
+    inherits_role_id: &RoleId,
+) -> Result<bool, warp::Rejection> {
+    let parent = store.get_role_by_id(inherits_role_id.clone()).await?;
+    if parent.is_delete {
+        return Ok(false);
+    }
+    if let Some(role_id) = role_id {
+        let chain = store.get_role_chain(inherits_role_id.clone()).await?;
+        if chain.iter().any(|role| role.id.as_ref() == Some(role_id)) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+// Handle for creating role
+#[utoipa::path(
+    post,
+    path = "/api/v1/role/create-role",
+    tag = "role",
+    request_body = RoleInfo,
+    responses((status = 201, description = "Created role", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store))]
+pub async fn create_role(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+    new_role: RoleInfo,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if let Some(inherits_role_id) = &new_role.inherits_role_id {
+        if !valid_inherits_role(&store, None, inherits_role_id).await? {
+            return Err(warp::reject::custom(Error::InvalidInheritedRole));
+        }
+    }
+    let res = store.create_role(new_role).await?;
+    record_audit(
+        &store,
+        Some(claims.id.clone()),
+        AuditAction::Create,
+        AuditEntity::Role,
+        res.id.clone().unwrap().0,
+        None,
+        snapshot(&res),
+    )
+    .await;
+    let payload = PayloadWithData {
+        message: "Success".to_string(),
+        data: Data::Role(res),
+        page: None,
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::CREATED,
+    ))
+}
+
+// Handle for retrieving role by ID
+#[utoipa::path(
+    get,
+    path = "/api/v1/role/get-role/{id}",
+    tag = "role",
+    params(
+        ("id" = i32, Path, description = "Id of the item"),
+    ),
+    responses((status = 200, description = "Role of the id", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store))]
+pub async fn get_role(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+    role_id: i32,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let res = store.get_role_by_id(RoleId(role_id)).await?;
+    let payload = PayloadWithData {
+        message: "Success".to_string(),
+        data: Data::Role(res),
+        page: None,
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
+
+// Handle for retrieving list roles
+#[utoipa::path(
+    get,
+    path = "/api/v1/role/list-role",
+    tag = "role",
+    responses((status = 200, description = "Every role", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store))]
+pub async fn get_list_roles(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut res = store.get_list_roles().await?;
+    res.sort_by_key(|role| role.id.as_ref().map(|id| id.0));
+    let payload = PayloadWithData {
+        message: "Success".to_string(),
+        data: Data::ListRole(res),
+        page: None,
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
+
+// Handler for updating role
+#[utoipa::path(
+    put,
+    path = "/api/v1/role/update-role",
+    tag = "role",
+    request_body = Role,
+    responses((status = 200, description = "Updated role", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store))]
+pub async fn update_role(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+    role: Role,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let role_id = match &role.id {
+        Some(role_id) => role_id.clone(),
+        None => {
+            return Err(warp::reject::custom(Error::MissingField("id".to_string())));
+        }
+    };
+    let before = store.get_role_by_id(role_id.clone()).await?;
+    // The hierarchy of the default roles is kept, as they can't be deleted
+    if role_id.0 <= HR_ROLE_ID && role.inherits_role_id != before.inherits_role_id {
+        return Err(warp::reject::custom(Error::DefaultRoleInheritance));
+    }
+    if let Some(inherits_role_id) = &role.inherits_role_id {
+        if !valid_inherits_role(&store, Some(&role_id), inherits_role_id).await? {
+            return Err(warp::reject::custom(Error::InvalidInheritedRole));
+        }
+    }
+    let res = store.update_role(role).await?;
+    record_audit(
+        &store,
+        Some(claims.id.clone()),
+        AuditAction::Update,
+        AuditEntity::Role,
+        role_id.0,
+        snapshot(&before),
+        snapshot(&res),
+    )
+    .await;
+    let payload = PayloadWithData {
+        message: "Success".to_string(),
+        data: Data::Role(res),
+        page: None,
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
+
+// Handler for deleting role by ID
+#[utoipa::path(
+    put,
+    path = "/api/v1/role/delete-role",
+    tag = "role",
+    request_body = Role,
+    responses((status = 200, description = "Role is deleted", body = PayloadNoData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store))]
+pub async fn delete_role(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+    role: Role,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let role_id = role.id.unwrap_or(RoleId(0));
+    // Default roles are referenced by register and the role hierarchy
+    if role_id.0 <= HR_ROLE_ID {
+        return Err(warp::reject::custom(Error::DefaultRoleDeletion));
+    }
+    let before = store.get_role_by_id(role_id.clone()).await?;
+    let _ = store.delete_role(role_id.clone()).await?;
+    record_audit(
+        &store,
+        Some(claims.id.clone()),
+        AuditAction::Delete,
+        AuditEntity::Role,
+        role_id.0,
+        snapshot(&before),
+        None,
+    )
+    .await;
+    let payload = PayloadNoData {
+        message: "Success".to_string(),
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
+
+// Handle for retrieving effective permissions of role, inherited permissions included
+#[utoipa::path(
+    get,
+    path = "/api/v1/role/get-permissions/{id}",
+    tag = "role",
+    params(
+        ("id" = i32, Path, description = "Id of the item"),
+    ),
+    responses((status = 200, description = "Effective permissions of the role", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store))]
+pub async fn get_role_permissions(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+    role_id: i32,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let _ = store.get_role_by_id(RoleId(role_id)).await?;
+    let permissions = store.get_role_permissions(RoleId(role_id)).await?;
+    let payload = PayloadWithData {
+        message: "Success".to_string(),
+        data: Data::RolePermissions(RolePermissions {
+            role_id: RoleId(role_id),
+            permissions,
+        }),
+        page: None,
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
+
+// Handler for replacing own permissions of role
+#[utoipa::path(
+    put,
+    path = "/api/v1/role/set-permissions",
+    tag = "role",
+    request_body = RolePermissions,
+    responses((status = 200, description = "Own permissions of the role", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store))]
+pub async fn set_role_permissions(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+    role_permissions: RolePermissions,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let role_id = role_permissions.role_id.clone();
+    let _ = store.get_role_by_id(role_id.clone()).await?;
+    // Effective permissions are recorded, the own ones are only a part of them
+    let before = store.get_role_permissions(role_id.clone()).await?;
+    let res = store.set_role_permissions(role_permissions).await?;
+    let after = store.get_role_permissions(role_id.clone()).await?;
+    record_audit(
+        &store,
+        Some(claims.id.clone()),
+        AuditAction::Update,
+        AuditEntity::Role,
+        role_id.0,
+        snapshot(&RolePermissions {
+            role_id: role_id.clone(),
+            permissions: before,
+        }),
+        snapshot(&RolePermissions {
+            role_id: role_id.clone(),
+            permissions: after,
+        }),
+    )
+    .await;
+    let payload = PayloadWithData {
+        message: "Success".to_string(),
+        data: Data::RolePermissions(res),
+        page: None,
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
@@ -1,27 +1,49 @@
 use std::collections::HashMap;
+use std::net::IpAddr;
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::Result;
 use argon2::Config;
+use chrono::Utc;
 use rand::random;
-use tracing::instrument;
+use tracing::{event, instrument, Level};
 use warp::http::StatusCode;
 
 use crate::errors::Error;
+use crate::models::audit::{AuditAction, AuditEntity};
+use crate::models::email_token::{
+    EmailRequest, EmailTokenPurpose, ResetPasswordRequest, VerifyEmailRequest,
+};
+use crate::models::login_attempt::{account_subject, ip_subject, UnlockLogin};
 use crate::models::pagination::Pagination;
-use crate::models::role::{RoleId, ADMIN_ROLE_ID, HR_ROLE_ID};
+use crate::models::refresh_token::{hash_refresh_token, RefreshTokenRequest};
+use crate::models::revoked_token::LogoutRequest;
+use crate::models::role::{AssignRole, RoleId, ADMIN_ROLE_ID, HR_ROLE_ID};
 use crate::models::store_trait::StoreMethods;
 use crate::models::user::{AuthInfo, User, UserId, UserInfo};
+use crate::services::audit::{record_audit, snapshot};
 use crate::services::jwt::{Claims, Jwt, JwtActions};
-use crate::utils::convert_to_json::{Data, PayloadForLogin, PayloadNoData, PayloadWithData};
+use crate::services::login_guard::LoginGuard;
+use crate::services::mailer::AccountMailer;
+use crate::services::metrics::METRICS;
+use crate::utils::convert_to_json::{
+    Data, PageInfo, PayloadForLogin, PayloadNoData, PayloadWithData, Problem,
+};
 
 pub fn hash_password(password: &[u8]) -> String {
     let salt = random::<[u8; 32]>();
     let config = Config::default();
-    argon2::hash_encoded(password, &salt, &config).unwrap()
+    let start = Instant::now();
+    let hash = argon2::hash_encoded(password, &salt, &config).unwrap();
+    METRICS.observe_password_hash("hash", start.elapsed());
+    hash
 }
 pub fn verify_password(hash: &str, password: &[u8]) -> Result<bool, argon2::Error> {
-    argon2::verify_encoded(hash, password)
+    let start = Instant::now();
+    let res = argon2::verify_encoded(hash, password);
+    METRICS.observe_password_hash("verify", start.elapsed());
+    res
 }
 // Convert User Object to UserInfo Object for hide password when returning response
 fn convert_user_to_user_info(user: User) -> UserInfo {
@@ -33,22 +55,63 @@ fn convert_user_to_user_info(user: User) -> UserInfo {
         is_delete: user.is_delete,
     }
 }
+
+// Snapshot of the user for the audit log, the password hash is left out
+fn user_snapshot(user: &User) -> Option<serde_json::Value> {
+    let mut value = snapshot(user)?;
+    if let Some(object) = value.as_object_mut() {
+        object.remove("hash_password");
+    }
+    Some(value)
+}
+
+// Issue a one-time token for the purpose, store it and send it by email to the user
+async fn send_email_token(
+    store: &Arc<dyn StoreMethods + Send + Sync>,
+    jwt: &Jwt,
+    account_mailer: &AccountMailer,
+    user: &User,
+    purpose: EmailTokenPurpose,
+) -> Result<(), Error> {
+    let minutes = match purpose {
+        EmailTokenPurpose::VerifyEmail => account_mailer.config.verification_token_minutes,
+        EmailTokenPurpose::ResetPassword => account_mailer.config.reset_token_minutes,
+    };
+    let (token, new_email_token) = jwt.issue_email_token(user, purpose, minutes)?;
+    store.create_email_token(new_email_token).await?;
+    match purpose {
+        EmailTokenPurpose::VerifyEmail => {
+            account_mailer.send_verification(&user.email, &token).await
+        }
+        EmailTokenPurpose::ResetPassword => {
+            account_mailer
+                .send_password_reset(&user.email, &token)
+                .await
+        }
+    }
+}
+
 // Handle for register user by email and password
-#[instrument(level = "info", skip(store))]
+#[utoipa::path(
+    post,
+    path = "/api/v1/register",
+    tag = "user",
+    request_body = AuthInfo,
+    responses((status = 201, description = "Created user", body = PayloadWithData))
+)]
+#[instrument(level = "info", skip(store, jwt, account_mailer))]
 pub async fn register(
     store: Arc<dyn StoreMethods + Send + Sync>,
+    jwt: Arc<Jwt>,
+    account_mailer: Arc<AccountMailer>,
     new_user: AuthInfo,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     // Check valid user
     let new_email = new_user.email;
     if let Ok(_res) = store.get_user_by_email(new_email.clone()).await {
-        let payload = PayloadNoData {
-            message: "Email already exists".to_string(),
-        };
-        return Ok(warp::reply::with_status(
-            warp::reply::json(&payload),
-            StatusCode::BAD_REQUEST,
-        ));
+        return Err(warp::reject::custom(Error::AlreadyExists(
+            "User".to_string(),
+        )));
     }
     let hash_password = hash_password(new_user.hash_password.as_bytes());
     let user = AuthInfo {
@@ -56,10 +119,33 @@ pub async fn register(
         hash_password,
     };
     let res = store.create_user(user).await.map_err(Error::from)?;
+    record_audit(
+        &store,
+        res.id.clone(),
+        AuditAction::Create,
+        AuditEntity::User,
+        res.id.clone().unwrap().0,
+        None,
+        user_snapshot(&res),
+    )
+    .await;
+    // The user is created even when the email can't be sent, it can be sent again later
+    if let Err(e) = send_email_token(
+        &store,
+        &jwt,
+        &account_mailer,
+        &res,
+        EmailTokenPurpose::VerifyEmail,
+    )
+    .await
+    {
+        event!(Level::ERROR, "Send verification email has error: {:?}", e);
+    }
     let user_info = convert_user_to_user_info(res);
     let payload = PayloadWithData {
         message: "Success".to_string(),
         data: Data::UserInfo(user_info),
+        page: None,
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -67,21 +153,58 @@ pub async fn register(
     ))
 }
 
-// Handle for login system by email and password
-#[instrument(level = "info", skip(store))]
+// Handle for login system by email and password.
+// Failed logins of the account and of the client ip are throttled, then locked.
+#[utoipa::path(
+    post,
+    path = "/api/v1/login",
+    tag = "user",
+    request_body = AuthInfo,
+    responses(
+        (status = 200, description = "Access token and refresh token of the user", body = PayloadForLogin),
+        (status = 429, description = "Too many failed logins, the Retry-After header tells when to retry", body = Problem, content_type = "application/problem+json")
+    )
+)]
+#[instrument(level = "info", skip(store, jwt, account_mailer, login_guard))]
 pub async fn login(
     store: Arc<dyn StoreMethods + Send + Sync>,
+    jwt: Arc<Jwt>,
+    account_mailer: Arc<AccountMailer>,
+    login_guard: Arc<LoginGuard>,
+    client_ip: Option<IpAddr>,
     login_info: AuthInfo,
 ) -> Result<impl warp::Reply, warp::Rejection> {
+    let reserved = login_guard
+        .reserve(&store, &login_info.email, client_ip)
+        .await?;
     // Login
-    let user = store.get_user_by_email(login_info.email.clone()).await?;
+    let user = match store.get_user_by_email(login_info.email.clone()).await {
+        Ok(user) => user,
+        Err(e) => {
+            login_guard.record_failure(&store, &reserved).await;
+            return Err(warp::reject::custom(e));
+        }
+    };
     let verified = verify_password(&user.hash_password, login_info.hash_password.as_bytes())
         .map_err(Error::from)?;
     if verified {
-        let token = Jwt::issue_access_token(user.clone()).unwrap();
+        login_guard
+            .record_success(&store, &login_info.email, &reserved)
+            .await?;
+        if account_mailer.config.require_verification && !user.email_verified {
+            return Err(warp::reject::custom(Error::EmailNotVerified));
+        }
+        let token_version = store
+            .get_user_token_version(user.id.clone().unwrap())
+            .await?;
+        let token = jwt.issue_access_token(user.clone(), token_version).unwrap();
+        let (refresh_token, new_refresh_token) =
+            jwt.issue_refresh_token(user.id.clone().unwrap(), None);
+        store.create_refresh_token(new_refresh_token).await?;
         let user_info = convert_user_to_user_info(user);
         let payload = PayloadForLogin {
             access_token: token,
+            refresh_token,
             message: "Login success".to_string(),
             data: Data::UserInfo(user_info),
         };
@@ -90,11 +213,379 @@ pub async fn login(
             StatusCode::OK,
         ))
     } else {
+        login_guard.record_failure(&store, &reserved).await;
         Err(warp::reject::custom(Error::WrongPassword))
     }
 }
 
+// Handle for unlocking the login of an account or a client ip locked after failed logins
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/unlock-login",
+    tag = "admin",
+    request_body = UnlockLogin,
+    responses((status = 200, description = "Failed logins of the account and the ip are forgotten", body = PayloadNoData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store))]
+pub async fn unlock_login(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+    unlock: UnlockLogin,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut subjects = Vec::new();
+    if let Some(email) = &unlock.email {
+        subjects.push(account_subject(email));
+    }
+    if let Some(ip) = &unlock.ip {
+        // Validated as an ip address, parsed again so every form of the address matches
+        let ip = ip
+            .parse::<IpAddr>()
+            .map_err(|_| Error::InvalidParameter("ip".to_string()))?;
+        subjects.push(ip_subject(&ip));
+    }
+    for subject in subjects {
+        let before = store.get_login_attempt(&subject).await?;
+        if store.clear_login_attempts(&subject).await? {
+            record_audit(
+                &store,
+                Some(claims.id.clone()),
+                AuditAction::LoginUnlocked,
+                AuditEntity::Login,
+                &subject,
+                before.as_ref().and_then(snapshot),
+                None,
+            )
+            .await;
+        }
+    }
+    let payload = PayloadNoData {
+        message: "Login unlocked".to_string(),
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
+
+// Handle for verifying email of user with the token of the verification email
+#[utoipa::path(
+    post,
+    path = "/api/v1/verify-email",
+    tag = "user",
+    request_body = VerifyEmailRequest,
+    responses((status = 200, description = "User with verified email", body = PayloadWithData))
+)]
+#[instrument(level = "info", skip(store, jwt, request))]
+pub async fn verify_email(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    jwt: Arc<Jwt>,
+    request: VerifyEmailRequest,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let claims = jwt.verify_email_token(&request.token, EmailTokenPurpose::VerifyEmail)?;
+    let email_token = store
+        .use_email_token(&claims.jti, EmailTokenPurpose::VerifyEmail)
+        .await?;
+    // The email of the user may have changed since the token was sent
+    let user = store.get_user_by_id(email_token.user_id).await?;
+    if user.email != claims.email {
+        return Err(warp::reject::custom(Error::InvalidEmailToken));
+    }
+    let res = store.set_email_verified(user.id.clone().unwrap()).await?;
+    record_audit(
+        &store,
+        user.id.clone(),
+        AuditAction::Update,
+        AuditEntity::User,
+        user.id.clone().unwrap().0,
+        user_snapshot(&user),
+        user_snapshot(&res),
+    )
+    .await;
+    let payload = PayloadWithData {
+        message: "Success".to_string(),
+        data: Data::UserInfo(convert_user_to_user_info(res)),
+        page: None,
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
+
+// Handle for sending the verification email again.
+// The response doesn't tell whether the email belongs to a user.
+#[utoipa::path(
+    post,
+    path = "/api/v1/verify-email/resend",
+    tag = "user",
+    request_body = EmailRequest,
+    responses((status = 200, description = "Verification email is sent when the email is waiting for verification", body = PayloadNoData))
+)]
+#[instrument(level = "info", skip(store, jwt, account_mailer))]
+pub async fn resend_verification(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    jwt: Arc<Jwt>,
+    account_mailer: Arc<AccountMailer>,
+    request: EmailRequest,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if let Ok(user) = store.get_user_by_email(request.email).await {
+        if !user.is_delete && !user.email_verified {
+            // Failures are logged only, the response is the same for every email
+            if let Err(e) = send_email_token(
+                &store,
+                &jwt,
+                &account_mailer,
+                &user,
+                EmailTokenPurpose::VerifyEmail,
+            )
+            .await
+            {
+                event!(Level::ERROR, "Send verification email has error: {:?}", e);
+            }
+        }
+    }
+    let payload = PayloadNoData {
+        message: "Verification email sent".to_string(),
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
+
+// Handle for sending the password reset email.
+// The response doesn't tell whether the email belongs to a user.
+#[utoipa::path(
+    post,
+    path = "/api/v1/forgot-password",
+    tag = "user",
+    request_body = EmailRequest,
+    responses((status = 200, description = "Password reset email is sent when the email belongs to a user", body = PayloadNoData))
+)]
+#[instrument(level = "info", skip(store, jwt, account_mailer))]
+pub async fn forgot_password(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    jwt: Arc<Jwt>,
+    account_mailer: Arc<AccountMailer>,
+    request: EmailRequest,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if let Ok(user) = store.get_user_by_email(request.email).await {
+        if !user.is_delete {
+            // Failures are logged only, the response is the same for every email
+            if let Err(e) = send_email_token(
+                &store,
+                &jwt,
+                &account_mailer,
+                &user,
+                EmailTokenPurpose::ResetPassword,
+            )
+            .await
+            {
+                event!(Level::ERROR, "Send password reset email has error: {:?}", e);
+            }
+        }
+    }
+    let payload = PayloadNoData {
+        message: "Password reset email sent".to_string(),
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
+
+// Handle for setting a new password with the token of the password reset email.
+// Every session of the user is logged out.
+#[utoipa::path(
+    post,
+    path = "/api/v1/reset-password",
+    tag = "user",
+    request_body = ResetPasswordRequest,
+    responses((status = 200, description = "User with the new password", body = PayloadWithData))
+)]
+#[instrument(level = "info", skip(store, jwt, request))]
+pub async fn reset_password(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    jwt: Arc<Jwt>,
+    request: ResetPasswordRequest,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let claims = jwt.verify_email_token(&request.token, EmailTokenPurpose::ResetPassword)?;
+    let email_token = store
+        .use_email_token(&claims.jti, EmailTokenPurpose::ResetPassword)
+        .await?;
+    let user = store.get_user_by_id(email_token.user_id).await?;
+    if user.email != claims.email || user.is_delete {
+        return Err(warp::reject::custom(Error::InvalidEmailToken));
+    }
+    let auth_info = AuthInfo {
+        email: user.email.clone(),
+        hash_password: hash_password(request.new_password.as_bytes()),
+    };
+    let res = store.update_password(auth_info).await?;
+    let user_id = res.id.clone().unwrap();
+    store.revoke_user_tokens(user_id.clone()).await?;
+    // Opening the link of the email proves the user owns the email
+    let res = store.set_email_verified(user_id.clone()).await?;
+    record_audit(
+        &store,
+        Some(user_id.clone()),
+        AuditAction::Update,
+        AuditEntity::User,
+        user_id.0,
+        user_snapshot(&user),
+        user_snapshot(&res),
+    )
+    .await;
+    let payload = PayloadWithData {
+        message: "Success".to_string(),
+        data: Data::UserInfo(convert_user_to_user_info(res)),
+        page: None,
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
+
+// Handle for rotating refresh token, the used token is exchanged for a new
+// access token and a new refresh token of the same family.
+// Presenting an already used token revokes the whole family.
+#[utoipa::path(
+    post,
+    path = "/api/v1/token/refresh",
+    tag = "user",
+    request_body = RefreshTokenRequest,
+    responses((status = 200, description = "New access token and refresh token", body = PayloadForLogin))
+)]
+#[instrument(level = "info", skip(store, jwt, request))]
+pub async fn refresh_token(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    jwt: Arc<Jwt>,
+    request: RefreshTokenRequest,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let current = store
+        .get_refresh_token(&hash_refresh_token(&request.refresh_token))
+        .await
+        .map_err(|_| Error::InvalidRefreshToken)?;
+    if current.is_revoked {
+        return Err(warp::reject::custom(Error::InvalidRefreshToken));
+    }
+    // Check reuse of token, mark used fails when the token was rotated concurrently
+    if current.is_used
+        || !store
+            .mark_refresh_token_used(current.id.clone().unwrap())
+            .await?
+    {
+        event!(
+            Level::WARN,
+            "Refresh token reused, revoke token family of user {}",
+            current.user_id.0
+        );
+        store
+            .revoke_refresh_token_family(&current.family_id)
+            .await?;
+        return Err(warp::reject::custom(Error::RefreshTokenReused));
+    }
+    if current.expires_at < Utc::now().timestamp() {
+        return Err(warp::reject::custom(Error::RefreshTokenExpired));
+    }
+    let user = store.get_user_by_id(current.user_id.clone()).await?;
+    if user.is_delete {
+        store
+            .revoke_refresh_token_family(&current.family_id)
+            .await?;
+        return Err(warp::reject::custom(Error::Unauthenticated));
+    }
+    let token_version = store
+        .get_user_token_version(current.user_id.clone())
+        .await?;
+    let token = jwt.issue_access_token(user.clone(), token_version)?;
+    let (refresh_token, new_refresh_token) =
+        jwt.issue_refresh_token(current.user_id, Some(current.family_id));
+    store.create_refresh_token(new_refresh_token).await?;
+    let user_info = convert_user_to_user_info(user);
+    let payload = PayloadForLogin {
+        access_token: token,
+        refresh_token,
+        message: "Refresh token success".to_string(),
+        data: Data::UserInfo(user_info),
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
+
+// Handle for logout current session, the refresh token sent from user is revoked as well
+#[utoipa::path(
+    post,
+    path = "/api/v1/logout",
+    tag = "user",
+    request_body = LogoutRequest,
+    responses((status = 200, description = "Current session is logged out", body = PayloadNoData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store, request))]
+pub async fn logout(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+    request: LogoutRequest,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    store
+        .revoke_access_token(&claims.jti, claims.id.clone(), claims.exp as i64)
+        .await?;
+    if let Some(token) = request.refresh_token {
+        if let Ok(refresh_token) = store.get_refresh_token(&hash_refresh_token(&token)).await {
+            if refresh_token.user_id == claims.id {
+                store
+                    .revoke_refresh_token_family(&refresh_token.family_id)
+                    .await?;
+            }
+        }
+    }
+    let payload = PayloadNoData {
+        message: "Logout success".to_string(),
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
+
+// Handle for logout all sessions of the user
+#[utoipa::path(
+    post,
+    path = "/api/v1/logout-all",
+    tag = "user",
+    responses((status = 200, description = "Every session of the user is logged out", body = PayloadNoData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store))]
+pub async fn logout_all(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    store.revoke_user_tokens(claims.id.clone()).await?;
+    let payload = PayloadNoData {
+        message: "Logout all sessions success".to_string(),
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
+
 // Handle for retrieving user by ID
+#[utoipa::path(
+    get,
+    path = "/api/v1/user/get-user/{id}",
+    tag = "user",
+    params(
+        ("id" = i32, Path, description = "Id of the item"),
+    ),
+    responses((status = 200, description = "User of the id", body = PayloadWithData))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn get_user_by_id(
     store: Arc<dyn StoreMethods + Send + Sync>,
@@ -108,6 +599,7 @@ pub async fn get_user_by_id(
     let payload = PayloadWithData {
         message: "Success".to_string(),
         data: Data::UserInfo(user_info),
+        page: None,
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -116,6 +608,17 @@ pub async fn get_user_by_id(
 }
 
 // Handle for retrieving list users based on query parameters
+#[utoipa::path(
+    get,
+    path = "/api/v1/user/list-user",
+    tag = "user",
+    params(
+        ("limit" = Option<i32>, Query, description = "Number of items of the page, at most 100"),
+        ("offset" = Option<i32>, Query, description = "Number of items skipped, replaced by the cursor"),
+        ("cursor" = Option<String>, Query, description = "next_cursor of the previous page"),
+    ),
+    responses((status = 200, description = "Page of users", body = PayloadWithData))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn get_list_users(
     store: Arc<dyn StoreMethods + Send + Sync>,
@@ -128,18 +631,17 @@ pub async fn get_list_users(
         pagination = Pagination::extract_pagination(params)?;
     }
     // Get list users with pagination filters
-    let res = store
-        .get_list_user(pagination.limit, pagination.offset)
-        .await
-        .map_err(Error::from)?;
+    let res = store.get_list_user(pagination).await?;
+    let page = PageInfo::new(&res, None);
     let mut list_user_info = Vec::new();
-    for element in res {
+    for element in res.items {
         let user_info = convert_user_to_user_info(element);
         list_user_info.push(user_info);
     }
     let payload = PayloadWithData {
         message: "Success".to_string(),
         data: Data::ListUserInfo(list_user_info),
+        page: Some(page),
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -148,21 +650,48 @@ pub async fn get_list_users(
 }
 
 // Handler for updating user.
+#[utoipa::path(
+    put,
+    path = "/api/v1/user/update-user",
+    tag = "user",
+    request_body = UserInfo,
+    responses((status = 200, description = "Updated user", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn update_user(
     store: Arc<dyn StoreMethods + Send + Sync>,
     claims: Claims,
     user_update: UserInfo,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    // Check id of user sending request and id of UserInfo sent from user
-    if claims.id != user_update.id {
-        return Err(warp::reject::custom(Error::Unauthenticated));
+    // Users update their own account, admins any account
+    let is_admin = claims.role_id.0 == ADMIN_ROLE_ID;
+    if claims.id != user_update.id && !is_admin {
+        return Err(warp::reject::custom(Error::Unauthorized));
     };
+    let before = store.get_user_by_id(user_update.id.clone()).await?;
+    // Hr reads the resumes applied to the jobs of their company, so only admins
+    // move users between companies
+    if user_update.company_id != before.company_id && !is_admin {
+        return Err(warp::reject::custom(Error::Unauthorized));
+    }
+    let user_id = user_update.id.clone();
     let res = store.update_user(user_update).await.map_err(Error::from)?;
+    record_audit(
+        &store,
+        Some(claims.id.clone()),
+        AuditAction::Update,
+        AuditEntity::User,
+        user_id.0,
+        user_snapshot(&before),
+        user_snapshot(&res),
+    )
+    .await;
     let user_info = convert_user_to_user_info(res);
     let payload = PayloadWithData {
         message: "Success".to_string(),
         data: Data::UserInfo(user_info),
+        page: None,
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -171,6 +700,14 @@ pub async fn update_user(
 }
 
 // Handler for updating password of user.
+#[utoipa::path(
+    put,
+    path = "/api/v1/user/update-password",
+    tag = "user",
+    request_body = AuthInfo,
+    responses((status = 200, description = "User with the new password", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn update_password(
     store: Arc<dyn StoreMethods + Send + Sync>,
@@ -179,18 +716,31 @@ pub async fn update_password(
 ) -> Result<impl warp::Reply, warp::Rejection> {
     // Check email of user sending request and email of AuthInfo sent from user
     if claims.email != user_update.email {
-        return Err(warp::reject::custom(Error::Unauthenticated));
+        return Err(warp::reject::custom(Error::Unauthorized));
     };
     let hash_password = hash_password(user_update.hash_password.as_bytes());
     let user = AuthInfo {
         email: user_update.email,
         hash_password,
     };
+    let before = store.get_user_by_id(claims.id.clone()).await?;
     let res = store.update_password(user).await.map_err(Error::from)?;
+    store.revoke_user_tokens(claims.id.clone()).await?;
+    record_audit(
+        &store,
+        Some(claims.id.clone()),
+        AuditAction::Update,
+        AuditEntity::User,
+        claims.id.0,
+        user_snapshot(&before),
+        user_snapshot(&res),
+    )
+    .await;
     let user_info = convert_user_to_user_info(res);
     let payload = PayloadWithData {
         message: "Success".to_string(),
         data: Data::UserInfo(user_info),
+        page: None,
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -198,21 +748,49 @@ pub async fn update_password(
     ))
 }
 
+// Set role of the user and revoke tokens issued with the outdated role
+async fn set_user_role(
+    store: &Arc<dyn StoreMethods + Send + Sync>,
+    claims: &Claims,
+    user: UserInfo,
+    role_id: RoleId,
+) -> Result<UserInfo, warp::Rejection> {
+    let before = store.get_user_by_id(user.id.clone()).await?;
+    let res = store.set_role(user, role_id).await?;
+    store.revoke_user_tokens(res.id.clone().unwrap()).await?;
+    record_audit(
+        store,
+        Some(claims.id.clone()),
+        AuditAction::Update,
+        AuditEntity::User,
+        res.id.clone().unwrap().0,
+        user_snapshot(&before),
+        user_snapshot(&res),
+    )
+    .await;
+    Ok(convert_user_to_user_info(res))
+}
+
 // 2 function handler for updating role of user.
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/set-admin",
+    tag = "user",
+    request_body = UserInfo,
+    responses((status = 200, description = "User with admin role", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn set_admin_role(
     store: Arc<dyn StoreMethods + Send + Sync>,
     claims: Claims,
     user: UserInfo,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    let res = store
-        .set_role(user, RoleId(ADMIN_ROLE_ID))
-        .await
-        .map_err(Error::from)?;
-    let user_info = convert_user_to_user_info(res);
+    let user_info = set_user_role(&store, &claims, user, RoleId(ADMIN_ROLE_ID)).await?;
     let payload = PayloadWithData {
         message: "Success".to_string(),
         data: Data::UserInfo(user_info),
+        page: None,
     };
     Ok(warp::reply::with_status(
         warp::reply::json(&payload),
@@ -220,25 +798,76 @@ pub async fn set_admin_role(
     ))
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/set-hr",
+    tag = "user",
+    request_body = UserInfo,
+    responses((status = 200, description = "User with hr role", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn set_hr_role(
     store: Arc<dyn StoreMethods + Send + Sync>,
     claims: Claims,
     user: UserInfo,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    let res = store
-        .set_role(user, RoleId(HR_ROLE_ID))
-        .await
-        .map_err(Error::from)?;
-    let user_info = convert_user_to_user_info(res);
+    let user_info = set_user_role(&store, &claims, user, RoleId(HR_ROLE_ID)).await?;
     let payload = PayloadWithData {
         message: "Success".to_string(),
         data: Data::UserInfo(user_info),
+        page: None,
     };
     Ok(warp::reply::json(&payload))
 }
 
+// Handler for assigning any role stored in database to user
+#[utoipa::path(
+    put,
+    path = "/api/v1/role/assign-role",
+    tag = "user",
+    request_body = AssignRole,
+    responses((status = 200, description = "User with the assigned role", body = PayloadWithData)),
+    security(("bearer_auth" = []))
+)]
+#[instrument(level = "info", skip(store))]
+pub async fn assign_role(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+    assign: AssignRole,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let role = store.get_role_by_id(assign.role_id.clone()).await?;
+    if role.is_delete {
+        return Err(warp::reject::custom(Error::RoleDeleted));
+    }
+    let user = store.get_user_by_id(assign.user_id).await?;
+    let user_info = set_user_role(
+        &store,
+        &claims,
+        convert_user_to_user_info(user),
+        assign.role_id,
+    )
+    .await?;
+    let payload = PayloadWithData {
+        message: "Success".to_string(),
+        data: Data::UserInfo(user_info),
+        page: None,
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&payload),
+        StatusCode::OK,
+    ))
+}
+
 // Handler for deleting user by ID.
+#[utoipa::path(
+    put,
+    path = "/api/v1/user/delete-user",
+    tag = "user",
+    request_body = UserInfo,
+    responses((status = 200, description = "User is deleted", body = String, content_type = "text/plain")),
+    security(("bearer_auth" = []))
+)]
 #[instrument(level = "info", skip(store))]
 pub async fn delete(
     store: Arc<dyn StoreMethods + Send + Sync>,
@@ -247,12 +876,24 @@ pub async fn delete(
 ) -> Result<impl warp::Reply, warp::Rejection> {
     // Check id of user sending request and id of UserInfo sent from user
     if claims.id != user_delete.id {
-        return Err(warp::reject::custom(Error::Unauthenticated));
+        return Err(warp::reject::custom(Error::Unauthorized));
     };
+    let before = store.get_user_by_id(user_delete.id.clone()).await?;
     let _ = store
-        .delete_user_by_id(user_delete.id)
+        .delete_user_by_id(user_delete.id.clone())
         .await
         .map_err(Error::from)?;
+    store.revoke_user_tokens(user_delete.id.clone()).await?;
+    record_audit(
+        &store,
+        Some(claims.id.clone()),
+        AuditAction::Delete,
+        AuditEntity::User,
+        user_delete.id.0,
+        user_snapshot(&before),
+        None,
+    )
+    .await;
     Ok(warp::reply::with_status(
         "Success".to_string(),
         StatusCode::OK,
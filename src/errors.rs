@@ -3,15 +3,23 @@ use tracing::{event, Level};
 use warp::{
     filters::{body::BodyDeserializeError, cors::CorsForbidden},
     http::StatusCode,
-    reject::Reject,
+    reject::{
+        InvalidQuery, MethodNotAllowed, MissingHeader, PayloadTooLarge, Reject,
+        UnsupportedMediaType,
+    },
     Rejection, Reply,
 };
 
+use crate::services::rate_limiter::RateLimitStatus;
+use crate::utils::convert_to_json::{Problem, ProblemDetail};
+
 #[derive(Error, Debug)]
 pub enum Error {
     //Error of database
     #[error("Database query error, invalid data")]
     DatabaseQuery(#[from] sqlx::Error),
+    #[error("Can't connect to the database: {0}")]
+    DatabaseConnection(sqlx::Error),
 
     //Error of In Memory
     #[error("Not found data")]
@@ -22,18 +30,36 @@ pub enum Error {
     CannotDecryptToken,
     #[error("Can't encrypt token error")]
     CannotEncryptToken,
+    #[error("Invalid refresh token")]
+    InvalidRefreshToken,
+    #[error("Refresh token expired")]
+    RefreshTokenExpired,
+    #[error("Refresh token reused")]
+    RefreshTokenReused,
+    #[error("Invalid or already used email token")]
+    InvalidEmailToken,
+    #[error("Email token expired")]
+    EmailTokenExpired,
 
     //Error of hash and verify password
     #[error("Can't verify password")]
     ArgonLibrary(#[from] argon2::Error),
-    #[error("Un authenticated")]
+    #[error("Wrong E-Mail/Password combination")]
     WrongPassword,
+    #[error("Too many failed logins, retry in {0} seconds")]
+    LoginThrottled(i64),
+    #[error("Login is locked after too many failed logins, retry in {0} seconds")]
+    LoginLocked(i64),
 
     //Error of authorized user and authenticated user
     #[error("No permission to change the underlying resource")]
     Unauthorized,
     #[error("UnAuthenticated")]
     Unauthenticated,
+    #[error("Access token expired")]
+    AccessTokenExpired,
+    #[error("Email is not verified")]
+    EmailNotVerified,
     #[error("Utf8 error: {0}")]
     Utf8(#[from] std::str::Utf8Error),
     #[error("Missing bearer auth type")]
@@ -45,88 +71,381 @@ pub enum Error {
     Migration(#[from] sqlx::migrate::MigrateError),
     #[error("Load configs error: {0}")]
     LoadConfigErr(#[from] serde_yaml::Error),
+    #[error("Invalid jwt key config: {0}")]
+    JwtKeyConfig(String),
+    #[error("Invalid email config: {0}")]
+    MailConfig(String),
+    #[error("Can't send email: {0}")]
+    Mail(String),
+    #[error("Invalid storage config: {0}")]
+    StorageConfig(String),
+    #[error("Blob store error: {0}")]
+    BlobStore(String),
     #[error("Missing parameter")]
     MissingParameters,
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+    #[error("Unknown permission: {0}")]
+    UnknownPermission(String),
+    #[error("Request body has invalid fields")]
+    Validation(Vec<ProblemDetail>),
+    #[error("Invalid multipart form: {0}")]
+    InvalidMultipart(String),
+    #[error("File is larger than {0} bytes")]
+    FileTooLarge(u64),
+    #[error("Unsupported file type {0}, only pdf and docx are accepted")]
+    UnsupportedFileType(String),
+    #[error("Too many requests, retry in {} seconds", .0.retry_after.unwrap_or(1))]
+    RateLimited(RateLimitStatus),
+
+    //Error of unique and foreign key constraints
+    #[error("{0} already exists")]
+    AlreadyExists(String),
+    #[error("{0} doesn't exist")]
+    MissingReference(String),
+
+    //Error of business rules
+    #[error("Missing field: {0}")]
+    MissingField(String),
+    #[error("Job belongs to another company")]
+    JobCompanyMismatch,
+    #[error("Inherited role is invalid")]
+    InvalidInheritedRole,
+    #[error("Default role can't be deleted")]
+    DefaultRoleDeletion,
+    #[error("Inherited role of a default role can't be changed")]
+    DefaultRoleInheritance,
+    #[error("Role was deleted")]
+    RoleDeleted,
+    #[error("Can't change application status from {0} to {1}")]
+    InvalidStatusTransition(String, String),
+    #[error("Application can only be withdrawn by the candidate")]
+    WithdrawNotAllowed,
+
+    //Error of applying job
+    #[error("Resume doesn't belong to the user")]
+    ResumeNotOwned,
+    #[error("Resume was deleted, can't apply job")]
+    ResumeDeleted,
+    #[error("Job not found")]
+    JobNotFound,
+    #[error("Job was closed, can't apply job")]
+    JobClosed,
+    #[error("User already applied to this job")]
+    DuplicateApplication,
+    #[error("Job has already hired its quantity of candidates")]
+    JobQuantityExceeded,
+    #[error("All positions of the job are filled, can't hire another candidate")]
+    JobFilled,
 }
 
 impl Reject for Error {}
 // search in
 //https://www.ibm.com/docs/en/db2-for-zos/13?topic=codes-sqlstate-values-common-error#db2z_sqlstatevalues__classcode02
-const DUPLICATE_KEY: u32 = 23505;
+const DUPLICATE_KEY: &str = "23505";
+const FOREIGN_KEY_VIOLATION: &str = "23503";
 
-pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
-    if let Some(Error::DatabaseQuery(e)) = r.find() {
-        event!(Level::ERROR, "Database query error");
-        match e {
-            sqlx::Error::Database(err) => {
-                if err.code().unwrap().parse::<u32>().unwrap() == DUPLICATE_KEY {
-                    Ok(warp::reply::with_status(
-                        "User already exists".to_string(),
-                        StatusCode::UNPROCESSABLE_ENTITY,
-                    ))
-                } else {
-                    Ok(warp::reply::with_status(
-                        "Can't update data".to_string(),
-                        StatusCode::UNPROCESSABLE_ENTITY,
-                    ))
-                }
+// Name of the data guarded by a unique index or referenced by a foreign key of the schema
+fn constraint_subject(constraint: Option<&str>) -> &'static str {
+    match constraint.unwrap_or_default() {
+        "users_email_idx"
+        | "resumes_user_id_fkey"
+        | "application_status_history_changed_by_fkey"
+        | "refresh_tokens_user_id_fkey"
+        | "revoked_tokens_user_id_fkey"
+        | "user_token_versions_user_id_fkey"
+        | "email_tokens_user_id_fkey" => "User",
+        "companies_email_idx" | "users_company_id_fkey" | "jobs_company_id_fkey" => "Company",
+        "users_role_id_fkey" | "roles_inherits_role_id_fkey" | "role_permissions_role_id_fkey" => {
+            "Role"
+        }
+        "map_resume_job_resume_job_idx" | "application_status_history_application_id_fkey" => {
+            "Application"
+        }
+        "map_resume_job_resume_id_fkey" => "Resume",
+        "map_resume_job_applied_by_fkey" => "User",
+        "map_resume_job_job_id_fkey" => "Job",
+        _ => "Data",
+    }
+}
+
+// Convert a constraint violation of the database into the error the in memory store returns
+fn constraint_error(err: &dyn sqlx::error::DatabaseError) -> Option<Error> {
+    // One application of a user to a job, whichever resume is sent
+    if err.constraint() == Some("map_resume_job_applied_by_job_idx") {
+        return Some(Error::DuplicateApplication);
+    }
+    let subject = constraint_subject(err.constraint()).to_string();
+    match err.code().as_deref() {
+        Some(DUPLICATE_KEY) => Some(Error::AlreadyExists(subject)),
+        Some(FOREIGN_KEY_VIOLATION) => Some(Error::MissingReference(subject)),
+        _ => None,
+    }
+}
+
+impl Error {
+    // HTTP status of the problem response
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Error::DatabaseQuery(sqlx::Error::RowNotFound) | Error::NotFound => {
+                StatusCode::NOT_FOUND
+            }
+            // Only a known constraint violation is the fault of the request
+            Error::DatabaseQuery(sqlx::Error::Database(err)) => constraint_error(err.as_ref())
+                .map_or(StatusCode::INTERNAL_SERVER_ERROR, |error| error.status()),
+            Error::DatabaseQuery(_)
+            | Error::DatabaseConnection(_)
+            | Error::CannotEncryptToken
+            | Error::ArgonLibrary(_)
+            | Error::Migration(_)
+            | Error::LoadConfigErr(_)
+            | Error::JwtKeyConfig(_)
+            | Error::MailConfig(_)
+            | Error::Mail(_)
+            | Error::StorageConfig(_)
+            | Error::BlobStore(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Error::CannotDecryptToken
+            | Error::InvalidRefreshToken
+            | Error::RefreshTokenExpired
+            | Error::RefreshTokenReused
+            | Error::WrongPassword
+            | Error::Unauthenticated
+            | Error::AccessTokenExpired
+            | Error::MissingBearerAuthType => StatusCode::UNAUTHORIZED,
+            Error::MissingField(_)
+            | Error::InvalidInheritedRole
+            | Error::DefaultRoleDeletion
+            | Error::DefaultRoleInheritance
+            | Error::RoleDeleted
+            | Error::WithdrawNotAllowed
+            | Error::InvalidEmailToken
+            | Error::InvalidMultipart(_) => StatusCode::BAD_REQUEST,
+            Error::FileTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            Error::UnsupportedFileType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Error::Unauthorized
+            | Error::ResumeNotOwned
+            | Error::JobCompanyMismatch
+            | Error::EmailNotVerified => StatusCode::FORBIDDEN,
+            Error::JobNotFound => StatusCode::NOT_FOUND,
+            Error::AlreadyExists(_)
+            | Error::InvalidStatusTransition(..)
+            | Error::DuplicateApplication
+            | Error::JobFilled => StatusCode::CONFLICT,
+            Error::ResumeDeleted | Error::JobClosed | Error::EmailTokenExpired => StatusCode::GONE,
+            Error::Utf8(_)
+            | Error::Parse(_)
+            | Error::MissingParameters
+            | Error::InvalidParameter(_)
+            | Error::UnknownPermission(_)
+            | Error::Validation(_)
+            | Error::MissingReference(_)
+            | Error::JobQuantityExceeded => StatusCode::UNPROCESSABLE_ENTITY,
+            Error::LoginThrottled(_) | Error::LoginLocked(_) | Error::RateLimited(_) => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+        }
+    }
+
+    // Machine readable code of the problem response
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::DatabaseQuery(sqlx::Error::RowNotFound) | Error::NotFound => "not_found",
+            Error::DatabaseQuery(sqlx::Error::Database(err)) => {
+                constraint_error(err.as_ref()).map_or("database_error", |error| error.code())
+            }
+            Error::DatabaseQuery(_) => "database_error",
+            Error::DatabaseConnection(_) => "database_connection_error",
+            Error::CannotDecryptToken => "cannot_decrypt_token",
+            Error::CannotEncryptToken => "cannot_encrypt_token",
+            Error::InvalidRefreshToken => "invalid_refresh_token",
+            Error::RefreshTokenExpired => "refresh_token_expired",
+            Error::RefreshTokenReused => "refresh_token_reused",
+            Error::InvalidEmailToken => "invalid_email_token",
+            Error::EmailTokenExpired => "email_token_expired",
+            Error::ArgonLibrary(_) => "password_hash_error",
+            Error::WrongPassword => "wrong_credentials",
+            Error::LoginThrottled(_) => "login_throttled",
+            Error::LoginLocked(_) => "login_locked",
+            Error::Unauthorized => "unauthorized",
+            Error::Unauthenticated => "unauthenticated",
+            Error::AccessTokenExpired => "access_token_expired",
+            Error::EmailNotVerified => "email_not_verified",
+            Error::Utf8(_) => "invalid_utf8",
+            Error::MissingBearerAuthType => "missing_bearer_auth_type",
+            Error::Parse(_) => "invalid_number",
+            Error::Migration(_) => "migration_error",
+            Error::LoadConfigErr(_) => "config_error",
+            Error::JwtKeyConfig(_) => "jwt_key_config_error",
+            Error::MailConfig(_) => "mail_config_error",
+            Error::Mail(_) => "mail_error",
+            Error::StorageConfig(_) => "storage_config_error",
+            Error::BlobStore(_) => "blob_store_error",
+            Error::MissingParameters => "missing_parameters",
+            Error::InvalidParameter(_) => "invalid_parameter",
+            Error::UnknownPermission(_) => "unknown_permission",
+            Error::Validation(_) => "validation_failed",
+            Error::InvalidMultipart(_) => "invalid_multipart",
+            Error::FileTooLarge(_) => "file_too_large",
+            Error::UnsupportedFileType(_) => "unsupported_file_type",
+            Error::RateLimited(_) => "rate_limited",
+            Error::AlreadyExists(_) => "already_exists",
+            Error::MissingReference(_) => "missing_reference",
+            Error::MissingField(_) => "missing_field",
+            Error::JobCompanyMismatch => "job_company_mismatch",
+            Error::InvalidInheritedRole => "invalid_inherited_role",
+            Error::DefaultRoleDeletion => "default_role_deletion",
+            Error::DefaultRoleInheritance => "default_role_inheritance",
+            Error::RoleDeleted => "role_deleted",
+            Error::InvalidStatusTransition(..) => "invalid_status_transition",
+            Error::WithdrawNotAllowed => "withdraw_not_allowed",
+            Error::ResumeNotOwned => "resume_not_owned",
+            Error::ResumeDeleted => "resume_deleted",
+            Error::JobNotFound => "job_not_found",
+            Error::JobClosed => "job_closed",
+            Error::DuplicateApplication => "duplicate_application",
+            Error::JobQuantityExceeded => "job_quantity_exceeded",
+            Error::JobFilled => "job_filled",
+        }
+    }
+
+    // Problem response of the error, internal errors don't leak their cause
+    pub fn problem(&self) -> Problem {
+        if let Error::DatabaseQuery(sqlx::Error::Database(err)) = self {
+            if let Some(error) = constraint_error(err.as_ref()) {
+                return error.problem();
+            }
+        }
+        let detail = match self.status() {
+            StatusCode::INTERNAL_SERVER_ERROR => "Internal server error".to_string(),
+            _ => self.to_string(),
+        };
+        let problem = Problem::new(self.status(), self.code(), detail);
+        match self {
+            Error::InvalidParameter(field) | Error::MissingField(field) => {
+                problem.with_details(vec![ProblemDetail {
+                    field: field.clone(),
+                    message: self.to_string(),
+                }])
+            }
+            Error::Validation(details) => problem.with_details(details.clone()),
+            Error::LoginThrottled(seconds) | Error::LoginLocked(seconds) => {
+                problem.with_retry_after(*seconds)
             }
-            _ => Ok(warp::reply::with_status(
-                "Not Found".to_string(),
-                StatusCode::NOT_FOUND,
-            )),
+            Error::RateLimited(status) => status
+                .headers()
+                .into_iter()
+                .fold(problem, |problem, (name, value)| {
+                    problem.with_header(name, value)
+                }),
+            _ => problem,
         }
-    } else if let Some(Error::NotFound) = r.find() {
-        Ok(warp::reply::with_status(
-            "Not Found Data".to_string(),
-            StatusCode::NOT_FOUND,
-        ))
-    } else if let Some(Error::WrongPassword) = r.find() {
-        Ok(warp::reply::with_status(
-            "Wrong E-Mail/Password combination".to_string(),
-            StatusCode::UNAUTHORIZED,
-        ))
-    } else if let Some(Error::Unauthorized) = r.find() {
-        event!(Level::ERROR, "Not matching account id");
-        Ok(warp::reply::with_status(
-            "No permission to change underlying resource".to_string(),
-            StatusCode::UNAUTHORIZED,
-        ))
-    } else if let Some(Error::Unauthenticated) = r.find() {
-        event!(Level::ERROR, "Un authenticated");
-        Ok(warp::reply::with_status(
-            "Un authenticated".to_string(),
-            StatusCode::UNAUTHORIZED,
-        ))
-    } else if let Some(Error::MissingBearerAuthType) = r.find() {
-        event!(Level::ERROR, "Un authenticated");
-        Ok(warp::reply::with_status(
-            "Missing bearer auth type in header".to_string(),
-            StatusCode::UNAUTHORIZED,
-        ))
+    }
+}
+
+// Convert every rejection into a RFC 7807 problem response
+pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
+    let problem = if let Some(error) = r.find::<Error>() {
+        error.problem()
     } else if let Some(error) = r.find::<CorsForbidden>() {
-        event!(Level::ERROR, "CORS forbidden error: {}", error);
-        Ok(warp::reply::with_status(
-            error.to_string(),
-            StatusCode::FORBIDDEN,
-        ))
+        Problem::new(StatusCode::FORBIDDEN, "cors_forbidden", error.to_string())
     } else if let Some(error) = r.find::<BodyDeserializeError>() {
-        event!(Level::ERROR, "Can't deserialize request body: {}", error);
-        Ok(warp::reply::with_status(
-            error.to_string(),
+        Problem::new(
             StatusCode::UNPROCESSABLE_ENTITY,
-        ))
-    } else if let Some(error) = r.find::<Error>() {
-        event!(Level::ERROR, "{}", error);
-        Ok(warp::reply::with_status(
+            "invalid_body",
             error.to_string(),
-            StatusCode::UNPROCESSABLE_ENTITY,
-        ))
+        )
+    } else if let Some(error) = r.find::<MissingHeader>() {
+        Problem::new(StatusCode::BAD_REQUEST, "missing_header", error.to_string())
+    } else if let Some(error) = r.find::<InvalidQuery>() {
+        Problem::new(StatusCode::BAD_REQUEST, "invalid_query", error.to_string())
+    } else if let Some(error) = r.find::<PayloadTooLarge>() {
+        Problem::new(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "payload_too_large",
+            error.to_string(),
+        )
+    } else if let Some(error) = r.find::<UnsupportedMediaType>() {
+        Problem::new(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "unsupported_media_type",
+            error.to_string(),
+        )
+    } else if let Some(error) = r.find::<MethodNotAllowed>() {
+        // Checked last, the other routes sharing the path refuse the method of the
+        // route rejecting the request for another reason
+        Problem::new(
+            StatusCode::METHOD_NOT_ALLOWED,
+            "method_not_allowed",
+            error.to_string(),
+        )
     } else {
-        event!(Level::WARN, "Requested route was not found");
-        Ok(warp::reply::with_status(
-            "Route not found".to_string(),
-            StatusCode::NOT_FOUND,
-        ))
+        Problem::new(StatusCode::NOT_FOUND, "route_not_found", "Route not found")
+    };
+    match r.find::<Error>() {
+        Some(error) => event!(
+            Level::ERROR,
+            request_id = %problem.request_id,
+            code = problem.code,
+            "{:?}",
+            error
+        ),
+        None => event!(
+            Level::WARN,
+            request_id = %problem.request_id,
+            code = problem.code,
+            "{}",
+            problem.detail
+        ),
+    }
+    Ok(problem.into_reply())
+}
+
+#[cfg(test)]
+mod errors_tests {
+    use super::Error;
+    use crate::utils::convert_to_json::ProblemDetail;
+
+    #[test]
+    fn problem_of_error() {
+        let problem = Error::DuplicateApplication.problem();
+        assert_eq!(problem.status, 409);
+        assert_eq!(problem.code, "duplicate_application");
+        assert_eq!(problem.title, "Conflict");
+        assert_eq!(problem.detail, "User already applied to this job");
+        assert!(problem.details.is_empty());
+    }
+
+    #[test]
+    fn problem_with_field_details() {
+        let problem = Error::InvalidParameter("cursor".to_string()).problem();
+        assert_eq!(problem.status, 422);
+        assert_eq!(problem.details[0].field, "cursor");
+    }
+
+    #[test]
+    fn problem_of_validation() {
+        let problem = Error::Validation(vec![ProblemDetail {
+            field: "email".to_string(),
+            message: "must be a valid email address".to_string(),
+        }])
+        .problem();
+        assert_eq!(problem.status, 422);
+        assert_eq!(problem.code, "validation_failed");
+        assert_eq!(problem.details[0].field, "email");
+    }
+
+    #[test]
+    fn problem_of_locked_login() {
+        let problem = Error::LoginLocked(900).problem();
+        assert_eq!(problem.status, 429);
+        assert_eq!(problem.code, "login_locked");
+        assert_eq!(problem.headers, vec![("retry-after", "900".to_string())]);
+    }
+
+    #[test]
+    fn internal_error_is_hidden() {
+        let problem = Error::JwtKeyConfig("missing secret of hs512".to_string()).problem();
+        assert_eq!(problem.status, 500);
+        assert_eq!(problem.detail, "Internal server error");
     }
 }
@@ -1,19 +1,39 @@
+// The filter type of every route combined is deeper than the default limit
+#![recursion_limit = "256"]
+
 use std::sync::Arc;
+use std::time::Duration;
 
 use tokio::sync::{oneshot, oneshot::Sender};
-use tracing::{info, instrument};
+use tracing::{event, info, instrument, warn, Level};
 use warp::{http::Method, Filter};
 
-use crate::configs::config::Config;
+use crate::configs::config::{Config, DatabaseKind};
 use crate::errors::{return_error, Error};
+use crate::middleware::metrics::observe_request;
+use crate::middleware::rate_limit::{rate_limit, with_rate_limit_headers};
 use crate::models::store_db::DatabaseStore;
 use crate::models::store_in_memory::InMemoryStore;
 use crate::models::store_trait::StoreMethods;
+use crate::routes::application::application_route;
+use crate::routes::audit::audit_route;
 use crate::routes::company::company_route;
+use crate::routes::health::health_route;
 use crate::routes::job::job_route;
+use crate::routes::jwks::jwks_route;
+use crate::routes::openapi::openapi_route;
 use crate::routes::resume::resume_route;
+use crate::routes::role::role_route;
 use crate::routes::user::user_route;
-use crate::services::telemetry::init_telemetry;
+use crate::services::blob_store::build_blob_store;
+use crate::services::job_expiry::spawn_job_expiry;
+use crate::services::jwt::Jwt;
+use crate::services::login_guard::LoginGuard;
+use crate::services::mailer::{build_mailer, AccountMailer};
+use crate::services::rate_limiter::RateLimiter;
+use crate::services::resume_file::ResumeFiles;
+use crate::services::shutdown::{drain, shutdown_signal, within};
+use crate::services::telemetry::{init_telemetry, shutdown_telemetry};
 
 mod configs;
 mod controllers;
@@ -28,72 +48,162 @@ mod utils;
 #[tokio::main]
 #[instrument]
 async fn main() {
-    let config = Config::new().expect("Config env not set");
+    let config = match Config::new() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Can't load config: {}", e);
+            std::process::exit(1);
+        }
+    };
     let log_filter = format!(
         "handle_errors={},rust-api-services={},warp={}",
         config.log_level, config.log_level, config.log_level
     );
-    init_telemetry(
+    let tracer_provider = init_telemetry(
         &config.service_name,
         &config.server.host,
         &config.server.jaeger_port,
         log_filter.as_str(),
     );
 
-    let store = build_store(&config).await;
-    let routes = build_routes(store).recover(return_error);
+    let store = or_exit(build_store(&config).await, "Can't set up the store");
+    let jwt = Arc::new(or_exit(Jwt::new(&config.jwt), "Invalid jwt config"));
+    let mailer = or_exit(build_mailer(&config.email), "Invalid email config");
+    let account_mailer = Arc::new(AccountMailer::new(config.email.clone(), mailer));
+    let login_guard = Arc::new(LoginGuard::new(config.login.clone()));
+    let rate_limiter = Arc::new(RateLimiter::new(config.rate_limit.clone()));
+    let blobs = or_exit(build_blob_store(&config.storage), "Invalid storage config");
+    let files = Arc::new(ResumeFiles::new(config.storage.max_file_bytes, blobs));
+    let job_expiry = spawn_job_expiry(
+        store.clone(),
+        Duration::from_secs(config.job.expiry_interval_seconds),
+    );
+    let routes = build_routes(
+        store.clone(),
+        jwt,
+        account_mailer,
+        login_guard,
+        rate_limiter,
+        files,
+    );
 
-    let address_listen = format!("{}:{}", config.server.host, config.server.port);
-    let socket: std::net::SocketAddr = address_listen.parse().expect("Not a valid address");
-    warp::serve(routes).run(socket).await;
+    let socket = or_exit(config.server.address(), "Invalid server address");
+    let (tx, rx) = oneshot::channel::<()>();
+    // New connections are refused once the signal is sent, open ones are served until done
+    let (_, server) = warp::serve(routes).bind_with_graceful_shutdown(socket, async {
+        rx.await.ok();
+    });
+    let server = tokio::task::spawn(server);
+
+    shutdown_signal().await;
+    let _ = tx.send(());
+    let timeout = Duration::from_secs(config.server.shutdown_timeout_seconds);
+    if drain(server, timeout).await {
+        info!("Warp server shut down");
+    } else {
+        warn!(
+            "Requests still running after {} seconds are dropped",
+            config.server.shutdown_timeout_seconds
+        );
+    }
+    job_expiry.abort();
+    // Spans are flushed first, closing the pool waits for connections still checked out
+    shutdown_telemetry(tracer_provider).await;
+    if !within(store.close(), timeout).await {
+        warn!(
+            "Store connections still in use after {} seconds are dropped",
+            config.server.shutdown_timeout_seconds
+        );
+    }
 }
 
-pub async fn build_store(config: &Config) -> Arc<dyn StoreMethods + Send + Sync> {
-    let url = config.postgres.url.clone();
+// Stop the service at startup with a readable error instead of a panic
+fn or_exit<T, E: std::fmt::Display>(result: Result<T, E>, context: &str) -> T {
+    result.unwrap_or_else(|e| {
+        event!(Level::ERROR, "{}: {}", context, e);
+        std::process::exit(1);
+    })
+}
 
-    let store: Arc<dyn StoreMethods + Send + Sync> =
-        if config.database.clone().unwrap() == *"postgres".to_string() {
+pub async fn build_store(config: &Config) -> Result<Arc<dyn StoreMethods + Send + Sync>, Error> {
+    let store: Arc<dyn StoreMethods + Send + Sync> = match config.database {
+        DatabaseKind::Postgres => {
             info!("Using postgres database");
             // set up database
-            let pool = DatabaseStore::new(&url).await;
+            let pool = DatabaseStore::new(&config.postgres.url).await?;
             sqlx::migrate!()
                 .run(&pool.clone().connection)
                 .await
-                .map_err(Error::Migration)
-                .unwrap();
+                .map_err(Error::Migration)?;
             Arc::new(pool)
-        } else {
+        }
+        DatabaseKind::InMemory => {
             info!("Using in-memory database");
             Arc::new(InMemoryStore::new())
-        };
-    store
+        }
+    };
+    Ok(store)
 }
 
 pub fn build_routes(
     store: Arc<dyn StoreMethods + Send + Sync>,
+    jwt: Arc<Jwt>,
+    account_mailer: Arc<AccountMailer>,
+    login_guard: Arc<LoginGuard>,
+    rate_limiter: Arc<RateLimiter>,
+    files: Arc<ResumeFiles>,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     let cors = warp::cors()
         .allow_any_origin()
         .allow_header("content-type")
         .allow_methods(&[Method::PUT, Method::DELETE, Method::GET, Method::POST]);
 
-    let user_routes = user_route("api", store.clone());
-    let company_routes = company_route("api", store.clone());
-    let resume_routes = resume_route("api", store.clone());
-    let job_routes = job_route("api", store.clone());
-    user_routes
+    let user_routes = user_route(
+        "api",
+        store.clone(),
+        jwt.clone(),
+        account_mailer,
+        login_guard,
+    );
+    let company_routes = company_route("api", store.clone(), jwt.clone());
+    let resume_routes = resume_route("api", store.clone(), jwt.clone(), files);
+    let job_routes = job_route("api", store.clone(), jwt.clone());
+    let role_routes = role_route("api", store.clone(), jwt.clone());
+    let application_routes = application_route("api", store.clone(), jwt.clone());
+    let audit_routes = audit_route("api", store.clone(), jwt.clone());
+    let health_routes = health_route(store.clone());
+    let jwks_routes = jwks_route(jwt.clone());
+    let openapi_routes = openapi_route("api");
+    let routes = user_routes
         .or(company_routes)
         .or(resume_routes)
         .or(job_routes)
+        .or(role_routes)
+        .or(application_routes)
+        .or(audit_routes)
+        .or(jwks_routes)
+        .or(openapi_routes);
+    let limited_routes = rate_limit(rate_limiter, jwt)
+        .and(routes)
+        .map(with_rate_limit_headers);
+    health_routes
+        .or(limited_routes)
         .with(cors)
         .with(warp::trace::request())
+        .recover(return_error)
+        .with(warp::log::custom(observe_request))
 }
 
 pub async fn init_test_server(
     address_listen: String,
     store: Arc<dyn StoreMethods + Send + Sync>,
+    jwt: Arc<Jwt>,
+    account_mailer: Arc<AccountMailer>,
+    login_guard: Arc<LoginGuard>,
+    rate_limiter: Arc<RateLimiter>,
+    files: Arc<ResumeFiles>,
 ) -> Sender<i32> {
-    let routes = build_routes(store).recover(return_error);
+    let routes = build_routes(store, jwt, account_mailer, login_guard, rate_limiter, files);
     let (tx, rx) = oneshot::channel::<i32>();
     let socket: std::net::SocketAddr = address_listen.parse().expect("Not a valid address");
 
@@ -107,13 +217,13 @@ pub async fn init_test_server(
 
 pub async fn build_store_for_test(
     url: String,
-    database: String,
+    database: DatabaseKind,
     sample_data_url: String,
 ) -> Arc<dyn StoreMethods + Send + Sync> {
-    let store: Arc<dyn StoreMethods + Send + Sync> = if database == *"postgres".to_string() {
+    let store: Arc<dyn StoreMethods + Send + Sync> = if database == DatabaseKind::Postgres {
         info!("Using postgres database");
         // set up database
-        let pool = DatabaseStore::new(&url).await;
+        let pool = DatabaseStore::new(&url).await.unwrap();
 
         sqlx::migrate!()
             .run(&pool.clone().connection)
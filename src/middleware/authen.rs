@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use chrono::Utc;
 use tracing::{event, Level};
 use warp::{
@@ -7,21 +9,68 @@ use warp::{
 };
 
 use crate::errors::Error;
+use crate::models::permission::Permission;
+use crate::models::store_trait::StoreMethods;
 use crate::services::jwt::{Claims, Jwt, JwtActions};
 
 const BEARER: &str = "Bearer";
 
+// Access requirement of a route
+#[derive(Debug, Clone)]
+pub enum Access {
+    // Role of the user must grant the permission, directly or by inheritance
+    Permission(Permission),
+    // Role of the user must be one of the roles or inherit from one of them
+    Roles(Vec<i32>),
+}
+
 // Authentication
-pub fn auth(role_id: i32) -> impl Filter<Extract = (Claims,), Error = warp::Rejection> + Clone {
+pub fn auth(
+    access: Access,
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    jwt: Arc<Jwt>,
+) -> impl Filter<Extract = (Claims,), Error = warp::Rejection> + Clone {
+    authenticated(store.clone(), jwt).and_then(move |claims: Claims| {
+        let access = access.clone();
+        let store = store.clone();
+        async move { authorize_access(access, store, claims).await }
+    })
+}
+
+// Authentication for routes open to every role
+pub fn authenticated(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    jwt: Arc<Jwt>,
+) -> impl Filter<Extract = (Claims,), Error = warp::Rejection> + Clone {
     headers_cloned()
-        .map(move |headers: HeaderMap<HeaderValue>| (role_id, headers))
+        .map(move |headers: HeaderMap<HeaderValue>| (store.clone(), jwt.clone(), headers))
         .and_then(authorize)
 }
 
+// Authentication for public routes which show more to some users, the claims
+// are missing when the request has no token
+pub fn optional_authenticated(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    jwt: Arc<Jwt>,
+) -> impl Filter<Extract = (Option<Claims>,), Error = warp::Rejection> + Clone {
+    headers_cloned()
+        .map(move |headers: HeaderMap<HeaderValue>| (store.clone(), jwt.clone(), headers))
+        .and_then(
+            |(store, jwt, headers): (_, _, HeaderMap<HeaderValue>)| async move {
+                if headers.contains_key(AUTHORIZATION) {
+                    authorize((store, jwt, headers)).await.map(Some)
+                } else {
+                    Ok(None)
+                }
+            },
+        )
+}
+
 // Handle authentication
 //
 // # Arguments
-// * `role_id` - A id of the role.
+// * `store` - A store used to check revoked tokens.
+// * `jwt` - A key set used to verify access token.
 // * `headers` - Headers values of request.
 //
 // # Return
@@ -29,20 +78,62 @@ pub fn auth(role_id: i32) -> impl Filter<Extract = (Claims,), Error = warp::Reje
 //```
 
 async fn authorize(
-    (role_id, headers): (i32, HeaderMap<HeaderValue>),
+    (store, jwt, headers): (
+        Arc<dyn StoreMethods + Send + Sync>,
+        Arc<Jwt>,
+        HeaderMap<HeaderValue>,
+    ),
 ) -> Result<Claims, warp::Rejection> {
     // Get access token from headers
     let token = jwt_from_header(&headers).map_err(Error::from)?;
-    let claims = Jwt::verify_access_token(&token).map_err(Error::from)?;
+    let claims = jwt.verify_access_token(&token).map_err(Error::from)?;
     let current_date_time = Utc::now();
     if claims.is_delete {
-        return Err(warp::reject());
+        return Err(warp::reject::custom(Error::Unauthenticated));
     }
     if claims.exp < current_date_time.timestamp() as usize {
-        return Err(warp::reject());
+        return Err(warp::reject::custom(Error::AccessTokenExpired));
     }
-    if claims.role_id.0 != role_id {
-        return Err(warp::reject());
+    // Check token was revoked by logout, role or password changes
+    if store
+        .is_access_token_revoked(&claims.jti, claims.id.clone(), claims.token_version)
+        .await?
+    {
+        event!(Level::ERROR, "Access token was revoked");
+        return Err(warp::reject::custom(Error::Unauthenticated));
+    }
+    Ok(claims)
+}
+
+// Handle checks the role of the user grants the access
+//
+// # Arguments
+// * `access` - A permission or a set of roles required by the route.
+// * `store` - A store used to load the role hierarchy and permissions.
+// * `claims` - A claim data decoded from access token.
+//
+// # Return
+// A claim data decoded from access token.
+//```
+async fn authorize_access(
+    access: Access,
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    claims: Claims,
+) -> Result<Claims, warp::Rejection> {
+    let allowed = match &access {
+        Access::Permission(permission) => store
+            .get_role_permissions(claims.role_id.clone())
+            .await?
+            .contains(permission),
+        Access::Roles(role_ids) => store
+            .get_role_chain(claims.role_id.clone())
+            .await?
+            .iter()
+            .any(|role| matches!(&role.id, Some(id) if role_ids.contains(&id.0))),
+    };
+    if !allowed {
+        event!(Level::ERROR, "Role does not grant access {:?}", access);
+        return Err(warp::reject::custom(Error::Unauthorized));
     };
     Ok(claims)
 }
@@ -55,11 +146,11 @@ async fn authorize(
 // # Return
 // A access token.
 //```
-fn jwt_from_header(headers: &HeaderMap<HeaderValue>) -> Result<String, Error> {
+pub(crate) fn jwt_from_header(headers: &HeaderMap<HeaderValue>) -> Result<String, Error> {
     // Get header value from header with key value is AUTHORIZATION
     let header = match headers.get(AUTHORIZATION) {
         Some(v) => v,
-        None => return Err(Error::Unauthorized),
+        None => return Err(Error::Unauthenticated),
     };
 
     // Convert header value as &[u8] to &str
@@ -0,0 +1,9 @@
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+
+use warp::Filter;
+
+// Ip address of the client connected to the server, empty when the transport has no address
+pub fn client_ip() -> impl Filter<Extract = (Option<IpAddr>,), Error = Infallible> + Clone {
+    warp::addr::remote().map(|addr: Option<SocketAddr>| addr.map(|addr| addr.ip()))
+}
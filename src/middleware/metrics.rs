@@ -0,0 +1,13 @@
+use warp::log::Info;
+
+use crate::services::metrics::METRICS;
+
+// Count the answered request and its latency, used with `warp::log::custom`
+pub fn observe_request(info: Info) {
+    METRICS.observe_request(
+        info.method().as_str(),
+        info.path(),
+        info.status().as_u16(),
+        info.elapsed(),
+    );
+}
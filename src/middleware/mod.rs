@@ -1 +1,6 @@
 pub mod authen;
+pub mod client_ip;
+pub mod metrics;
+pub mod rate_limit;
+pub mod upload;
+pub mod validation;
@@ -0,0 +1,67 @@
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use warp::{
+    filters::{header::headers_cloned, path::FullPath},
+    http::header::{HeaderMap, HeaderValue},
+    Filter, Reply,
+};
+
+use crate::errors::Error;
+use crate::middleware::authen::jwt_from_header;
+use crate::middleware::client_ip::client_ip;
+use crate::services::jwt::{Jwt, JwtActions};
+use crate::services::rate_limiter::{RateLimitStatus, RateLimiter};
+
+// Take a token of the quota of the route group, requests are counted per user
+// when the access token is valid and per client ip otherwise.
+// Refused requests are rejected with RateLimited, handled by `return_error`.
+pub fn rate_limit(
+    limiter: Arc<RateLimiter>,
+    jwt: Arc<Jwt>,
+) -> impl Filter<Extract = (Option<RateLimitStatus>,), Error = warp::Rejection> + Clone {
+    warp::path::full()
+        .and(headers_cloned())
+        .and(client_ip())
+        .and_then(
+            move |path: FullPath, headers: HeaderMap<HeaderValue>, ip: Option<IpAddr>| {
+                let limiter = limiter.clone();
+                let jwt = jwt.clone();
+                async move {
+                    if !limiter.config.enabled {
+                        return Ok(None);
+                    }
+                    let client = client_key(&jwt, &headers, ip);
+                    match limiter.check(path.as_str(), &client) {
+                        Ok(status) => Ok(Some(status)),
+                        Err(status) => Err(warp::reject::custom(Error::RateLimited(status))),
+                    }
+                }
+            },
+        )
+}
+
+fn client_key(jwt: &Jwt, headers: &HeaderMap<HeaderValue>, ip: Option<IpAddr>) -> String {
+    let claims = jwt_from_header(headers)
+        .ok()
+        .and_then(|token| jwt.verify_access_token(&token).ok());
+    match (claims, ip) {
+        (Some(claims), _) => format!("user:{}", claims.id.0),
+        (None, Some(ip)) => format!("ip:{}", ip),
+        (None, None) => "unknown".to_string(),
+    }
+}
+
+// Send the state of the quota with the response of the route
+pub fn with_rate_limit_headers(
+    status: Option<RateLimitStatus>,
+    reply: impl Reply,
+) -> warp::reply::Response {
+    let mut response = reply.into_response();
+    for (name, value) in status.map(|status| status.headers()).unwrap_or_default() {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            response.headers_mut().insert(name, value);
+        }
+    }
+    response
+}
@@ -0,0 +1,100 @@
+use futures_util::{Stream, TryStreamExt};
+use serde::de::DeserializeOwned;
+use warp::hyper::body::Buf;
+use warp::multipart::{FormData, Part};
+use warp::Filter;
+
+use crate::errors::Error;
+use crate::middleware::validation::validated_json;
+use crate::models::resume::FileUpload;
+use crate::models::validation::Validate;
+
+// Largest json part of a multipart form
+const MAX_JSON_PART_BYTES: u64 = 64 * 1024;
+
+// Body of a resume request: the json body, or a multipart form holding the json
+// body in its `resume` part and the pdf or docx file in its `file` part
+pub fn resume_body<T>(
+    max_file_bytes: u64,
+) -> impl Filter<Extract = ((T, Option<FileUpload>),), Error = warp::Rejection> + Clone
+where
+    T: DeserializeOwned + Validate + Send + 'static,
+{
+    let json = validated_json::<T>().map(|body: T| (body, None));
+    // The size of the file is checked while reading, so no content-length is needed
+    let form =
+        warp::multipart::form()
+            .max_length(None)
+            .and_then(move |form: FormData| async move {
+                read_form::<T>(form, max_file_bytes)
+                    .await
+                    .map_err(warp::reject::custom)
+            });
+    json.or(form).unify()
+}
+
+async fn read_form<T>(
+    mut form: FormData,
+    max_file_bytes: u64,
+) -> Result<(T, Option<FileUpload>), Error>
+where
+    T: DeserializeOwned + Validate,
+{
+    let mut body = None;
+    let mut upload = None;
+    while let Some(part) = form
+        .try_next()
+        .await
+        .map_err(|e| Error::InvalidMultipart(e.to_string()))?
+    {
+        match part.name() {
+            "resume" => {
+                let content = read_part(part.stream(), MAX_JSON_PART_BYTES).await?;
+                body = Some(
+                    serde_json::from_slice::<T>(&content)
+                        .map_err(|e| Error::InvalidMultipart(format!("resume part: {}", e)))?,
+                );
+            }
+            "file" => upload = Some(read_file(part, max_file_bytes).await?),
+            _ => {}
+        }
+    }
+    let body = body.ok_or_else(|| Error::MissingField("resume".to_string()))?;
+    body.validate()?;
+    Ok((body, upload))
+}
+
+async fn read_file(part: Part, max_file_bytes: u64) -> Result<FileUpload, Error> {
+    let file_name = part.filename().unwrap_or_default().to_string();
+    let content_type = part.content_type().unwrap_or_default().to_string();
+    Ok(FileUpload {
+        file_name,
+        content_type,
+        content: read_part(part.stream(), max_file_bytes).await?,
+    })
+}
+
+// Read the part chunk by chunk, stopping as soon as it is over the limit
+async fn read_part<B: Buf>(
+    stream: impl Stream<Item = Result<B, warp::Error>>,
+    max_bytes: u64,
+) -> Result<Vec<u8>, Error> {
+    futures_util::pin_mut!(stream);
+    let mut content = Vec::new();
+    while let Some(mut chunk) = stream
+        .try_next()
+        .await
+        .map_err(|e| Error::InvalidMultipart(e.to_string()))?
+    {
+        if (content.len() + chunk.remaining()) as u64 > max_bytes {
+            return Err(Error::FileTooLarge(max_bytes));
+        }
+        while chunk.has_remaining() {
+            let slice = chunk.chunk();
+            let len = slice.len();
+            content.extend_from_slice(slice);
+            chunk.advance(len);
+        }
+    }
+    Ok(content)
+}
@@ -0,0 +1,16 @@
+use serde::de::DeserializeOwned;
+use warp::Filter;
+
+use crate::models::validation::Validate;
+
+// Json body of the request, rejected with the errors of its fields
+// when the model breaks one of its validation rules
+pub fn validated_json<T>() -> impl Filter<Extract = (T,), Error = warp::Rejection> + Clone
+where
+    T: DeserializeOwned + Validate + Send,
+{
+    warp::body::json().and_then(|body: T| async move {
+        body.validate().map_err(warp::reject::custom)?;
+        Ok::<T, warp::Rejection>(body)
+    })
+}
@@ -0,0 +1,310 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::errors::Error;
+use crate::models::job::Job;
+use crate::models::map_resume_job::MapResumeJobId;
+use crate::models::resume::Resume;
+use crate::models::user::UserId;
+use crate::models::validation::{Validate, Validator, MAX_TEXT_LENGTH};
+
+// Status of an application, stored as text in `map_resume_job.status`
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ApplicationStatus {
+    #[default]
+    Applied,
+    Screening,
+    Interview,
+    Offer,
+    Hired,
+    Rejected,
+    Withdrawn,
+}
+
+pub const ALL_APPLICATION_STATUSES: [ApplicationStatus; 7] = [
+    ApplicationStatus::Applied,
+    ApplicationStatus::Screening,
+    ApplicationStatus::Interview,
+    ApplicationStatus::Offer,
+    ApplicationStatus::Hired,
+    ApplicationStatus::Rejected,
+    ApplicationStatus::Withdrawn,
+];
+
+impl ApplicationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApplicationStatus::Applied => "applied",
+            ApplicationStatus::Screening => "screening",
+            ApplicationStatus::Interview => "interview",
+            ApplicationStatus::Offer => "offer",
+            ApplicationStatus::Hired => "hired",
+            ApplicationStatus::Rejected => "rejected",
+            ApplicationStatus::Withdrawn => "withdrawn",
+        }
+    }
+
+    // Hired, rejected and withdrawn applications can't change anymore
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            ApplicationStatus::Hired | ApplicationStatus::Rejected | ApplicationStatus::Withdrawn
+        )
+    }
+
+    // The pipeline moves forward one step at a time, an open application
+    // can be rejected by hr or withdrawn by the candidate at any step
+    pub fn can_transition_to(&self, next: ApplicationStatus) -> bool {
+        if self.is_final() {
+            return false;
+        }
+        match next {
+            ApplicationStatus::Rejected | ApplicationStatus::Withdrawn => true,
+            ApplicationStatus::Screening => *self == ApplicationStatus::Applied,
+            ApplicationStatus::Interview => *self == ApplicationStatus::Screening,
+            ApplicationStatus::Offer => *self == ApplicationStatus::Interview,
+            ApplicationStatus::Hired => *self == ApplicationStatus::Offer,
+            ApplicationStatus::Applied => false,
+        }
+    }
+}
+
+impl fmt::Display for ApplicationStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ApplicationStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ALL_APPLICATION_STATUSES
+            .iter()
+            .find(|status| status.as_str() == s)
+            .copied()
+            .ok_or_else(|| Error::InvalidParameter(format!("status {}", s)))
+    }
+}
+
+// One entry of the application timeline, from_status is empty for the entry created on apply
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct ApplicationStatusChange {
+    pub id: Option<ApplicationStatusChangeId>,
+    pub application_id: MapResumeJobId,
+    pub from_status: Option<ApplicationStatus>,
+    pub to_status: ApplicationStatus,
+    pub changed_by: UserId,
+    pub note: Option<String>,
+    // Unix timestamp in seconds
+    pub changed_at: i64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct NewApplicationStatusChange {
+    pub application_id: MapResumeJobId,
+    pub from_status: Option<ApplicationStatus>,
+    pub to_status: ApplicationStatus,
+    pub changed_by: UserId,
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, ToSchema)]
+pub struct ApplicationStatusChangeId(pub i32);
+
+// Body of the hr request moving an application through the pipeline
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct UpdateApplicationStatus {
+    pub application_id: MapResumeJobId,
+    pub status: ApplicationStatus,
+    pub note: Option<String>,
+}
+
+impl Validate for UpdateApplicationStatus {
+    fn validate(&self) -> Result<(), Error> {
+        let mut validator = Validator::new();
+        validator.id("application_id", self.application_id.0);
+        if let Some(note) = &self.note {
+            validator.length("note", note, 0, MAX_TEXT_LENGTH);
+        }
+        validator.finish()
+    }
+}
+
+// Body of the candidate request withdrawing an application
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct WithdrawApplication {
+    pub application_id: MapResumeJobId,
+    pub note: Option<String>,
+}
+
+impl Validate for WithdrawApplication {
+    fn validate(&self) -> Result<(), Error> {
+        let mut validator = Validator::new();
+        validator.id("application_id", self.application_id.0);
+        if let Some(note) = &self.note {
+            validator.length("note", note, 0, MAX_TEXT_LENGTH);
+        }
+        validator.finish()
+    }
+}
+
+// Rules a new application must pass, `hired_applications` counts the
+// applications of the job which are hired, the quantity of the job is the
+// number of positions and applications are taken until they are filled
+pub fn check_application(
+    user_id: &UserId,
+    resume: &Resume,
+    job: &Job,
+    already_applied: bool,
+    hired_applications: i64,
+    now: i64,
+) -> Result<(), Error> {
+    if resume.user_id != *user_id {
+        return Err(Error::ResumeNotOwned);
+    }
+    if resume.is_delete {
+        return Err(Error::ResumeDeleted);
+    }
+    if !job.is_open(now) {
+        return Err(Error::JobClosed);
+    }
+    if already_applied {
+        return Err(Error::DuplicateApplication);
+    }
+    if hired_applications >= job.quantity as i64 {
+        return Err(Error::JobQuantityExceeded);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod application_check_tests {
+    use super::check_application;
+    use crate::errors::Error;
+    use crate::models::job::{job_fixture, Job, JobStatus};
+    use crate::models::resume::{Resume, ResumeId, ResumeProfile};
+    use crate::models::user::UserId;
+
+    const NOW: i64 = 1_724_000_000;
+
+    fn resume() -> Resume {
+        Resume {
+            id: Some(ResumeId(1)),
+            user_id: UserId(7),
+            email: "user2@gmail.com".to_string(),
+            url: "resume.pdf".to_string(),
+            is_delete: false,
+            file: None,
+            is_searchable: false,
+            profile: ResumeProfile::default(),
+        }
+    }
+
+    fn job() -> Job {
+        Job {
+            published_at: Some(NOW - 3600),
+            expires_at: Some(NOW + 3600),
+            ..job_fixture()
+        }
+    }
+
+    #[test]
+    fn valid_application() {
+        assert!(check_application(&UserId(7), &resume(), &job(), false, 1, NOW).is_ok());
+    }
+
+    #[test]
+    fn invalid_application() {
+        let deleted_resume = Resume {
+            is_delete: true,
+            ..resume()
+        };
+        let closed_job = Job {
+            is_delete: true,
+            ..job()
+        };
+        let paused_job = Job {
+            status: JobStatus::Paused,
+            ..job()
+        };
+        let expired_job = Job {
+            expires_at: Some(NOW),
+            ..job()
+        };
+        let cases = [
+            (
+                check_application(&UserId(6), &resume(), &job(), false, 0, NOW),
+                Error::ResumeNotOwned,
+            ),
+            (
+                check_application(&UserId(7), &deleted_resume, &job(), false, 0, NOW),
+                Error::ResumeDeleted,
+            ),
+            (
+                check_application(&UserId(7), &resume(), &closed_job, false, 0, NOW),
+                Error::JobClosed,
+            ),
+            (
+                check_application(&UserId(7), &resume(), &paused_job, false, 0, NOW),
+                Error::JobClosed,
+            ),
+            (
+                check_application(&UserId(7), &resume(), &expired_job, false, 0, NOW),
+                Error::JobClosed,
+            ),
+            (
+                check_application(&UserId(7), &resume(), &job(), true, 0, NOW),
+                Error::DuplicateApplication,
+            ),
+            (
+                check_application(&UserId(7), &resume(), &job(), false, 2, NOW),
+                Error::JobQuantityExceeded,
+            ),
+        ];
+        for (result, expect) in cases {
+            assert_eq!(result.unwrap_err().to_string(), expect.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod application_status_tests {
+    use super::{ApplicationStatus, ALL_APPLICATION_STATUSES};
+
+    #[test]
+    fn status_name_round_trip() {
+        for status in ALL_APPLICATION_STATUSES {
+            assert_eq!(
+                status.as_str().parse::<ApplicationStatus>().unwrap(),
+                status
+            );
+            assert_eq!(
+                serde_json::to_string(&status).unwrap(),
+                format!("\"{}\"", status)
+            );
+        }
+    }
+
+    #[test]
+    fn pipeline_transitions() {
+        use ApplicationStatus::*;
+        assert!(Applied.can_transition_to(Screening));
+        assert!(Screening.can_transition_to(Interview));
+        assert!(Interview.can_transition_to(Offer));
+        assert!(Offer.can_transition_to(Hired));
+        assert!(Interview.can_transition_to(Rejected));
+        assert!(Applied.can_transition_to(Withdrawn));
+
+        assert!(!Applied.can_transition_to(Hired));
+        assert!(!Screening.can_transition_to(Applied));
+        assert!(!Offer.can_transition_to(Interview));
+        assert!(!Hired.can_transition_to(Withdrawn));
+        assert!(!Rejected.can_transition_to(Screening));
+        assert!(!Withdrawn.can_transition_to(Withdrawn));
+    }
+}
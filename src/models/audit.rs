@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::errors::Error;
+use crate::models::pagination::Pagination;
+use crate::models::user::UserId;
+
+// What was done to the entity, stored as text in `audit_log.action`
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    LoginLocked,
+    LoginUnlocked,
+}
+
+pub const ALL_AUDIT_ACTIONS: [AuditAction; 5] = [
+    AuditAction::Create,
+    AuditAction::Update,
+    AuditAction::Delete,
+    AuditAction::LoginLocked,
+    AuditAction::LoginUnlocked,
+];
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Create => "create",
+            AuditAction::Update => "update",
+            AuditAction::Delete => "delete",
+            AuditAction::LoginLocked => "login_locked",
+            AuditAction::LoginUnlocked => "login_unlocked",
+        }
+    }
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ALL_AUDIT_ACTIONS
+            .iter()
+            .find(|action| action.as_str() == s)
+            .copied()
+            .ok_or_else(|| Error::InvalidParameter(format!("action={}", s)))
+    }
+}
+
+// Kind of the changed entity, stored as text in `audit_log.entity_type`
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEntity {
+    User,
+    Company,
+    Job,
+    Resume,
+    Role,
+    Application,
+    // Login of an account or a client ip, the id is the subject of the login attempts
+    Login,
+}
+
+pub const ALL_AUDIT_ENTITIES: [AuditEntity; 7] = [
+    AuditEntity::User,
+    AuditEntity::Company,
+    AuditEntity::Job,
+    AuditEntity::Resume,
+    AuditEntity::Role,
+    AuditEntity::Application,
+    AuditEntity::Login,
+];
+
+impl AuditEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditEntity::User => "user",
+            AuditEntity::Company => "company",
+            AuditEntity::Job => "job",
+            AuditEntity::Resume => "resume",
+            AuditEntity::Role => "role",
+            AuditEntity::Application => "application",
+            AuditEntity::Login => "login",
+        }
+    }
+}
+
+impl fmt::Display for AuditEntity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AuditEntity {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ALL_AUDIT_ENTITIES
+            .iter()
+            .find(|entity| entity.as_str() == s)
+            .copied()
+            .ok_or_else(|| Error::InvalidParameter(format!("entityType={}", s)))
+    }
+}
+
+// One mutating operation with the state of the entity before and after it,
+// the actor is empty for operations made by the service itself
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct AuditEntry {
+    pub id: Option<AuditEntryId>,
+    pub actor_id: Option<UserId>,
+    pub action: AuditAction,
+    pub entity_type: AuditEntity,
+    pub entity_id: String,
+    #[schema(value_type = Option<Object>)]
+    pub before: Option<serde_json::Value>,
+    #[schema(value_type = Option<Object>)]
+    pub after: Option<serde_json::Value>,
+    // Unix timestamp in seconds
+    pub created_at: i64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, ToSchema)]
+pub struct AuditEntryId(pub i32);
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct NewAuditEntry {
+    pub actor_id: Option<UserId>,
+    pub action: AuditAction,
+    pub entity_type: AuditEntity,
+    pub entity_id: String,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub created_at: i64,
+}
+
+/// Audit filter struct which is getting extract
+/// from query params
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct AuditFilter {
+    pub actor_id: Option<UserId>,
+    pub action: Option<AuditAction>,
+    pub entity_type: Option<AuditEntity>,
+    pub entity_id: Option<String>,
+    /// Inclusive range of `created_at`, unix timestamps in seconds
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    /// Limit with offset or cursor, entries are listed newest first
+    pub pagination: Pagination,
+}
+
+/// Extract query parameters from the `/admin/audit` route
+/// # Example query
+/// `/admin/audit?actorId=1&action=update&entityType=job&entityId=3
+/// &from=1723000000&to=1724000000&limit=10&offset=0`
+///
+/// Every parameter is optional, `limit` has to be sent with `offset` or `cursor`.
+impl AuditFilter {
+    pub fn extract_audit_filter(params: HashMap<String, String>) -> Result<AuditFilter, Error> {
+        let mut filter = AuditFilter::default();
+        if params.contains_key("limit")
+            || params.contains_key("offset")
+            || params.contains_key("cursor")
+        {
+            filter.pagination = Pagination::extract_pagination(params.clone())?;
+        }
+        if let Some(actor_id) = params.get("actorId") {
+            filter.actor_id = Some(UserId(actor_id.parse().map_err(Error::Parse)?));
+        }
+        if let Some(action) = params.get("action") {
+            filter.action = Some(action.parse()?);
+        }
+        if let Some(entity_type) = params.get("entityType") {
+            filter.entity_type = Some(entity_type.parse()?);
+        }
+        filter.entity_id = params
+            .get("entityId")
+            .map(|entity_id| entity_id.trim().to_string())
+            .filter(|entity_id| !entity_id.is_empty());
+        if let Some(from) = params.get("from") {
+            filter.from = Some(from.parse().map_err(Error::Parse)?);
+        }
+        if let Some(to) = params.get("to") {
+            filter.to = Some(to.parse().map_err(Error::Parse)?);
+        }
+        Ok(filter)
+    }
+
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.actor_id
+            .as_ref()
+            .is_none_or(|actor_id| entry.actor_id.as_ref() == Some(actor_id))
+            && self.action.is_none_or(|action| entry.action == action)
+            && self
+                .entity_type
+                .is_none_or(|entity_type| entry.entity_type == entity_type)
+            && self
+                .entity_id
+                .as_ref()
+                .is_none_or(|entity_id| &entry.entity_id == entity_id)
+            && self.from.is_none_or(|from| entry.created_at >= from)
+            && self.to.is_none_or(|to| entry.created_at <= to)
+    }
+}
+
+#[cfg(test)]
+mod audit_tests {
+    use super::{AuditAction, AuditEntity, AuditEntry, AuditFilter, HashMap, Pagination, UserId};
+
+    fn entry(action: AuditAction, created_at: i64) -> AuditEntry {
+        AuditEntry {
+            id: None,
+            actor_id: Some(UserId(1)),
+            action,
+            entity_type: AuditEntity::Job,
+            entity_id: "3".to_string(),
+            before: None,
+            after: None,
+            created_at,
+        }
+    }
+
+    #[test]
+    fn valid_audit_filter() {
+        let mut params = HashMap::new();
+        params.insert(String::from("actorId"), String::from("1"));
+        params.insert(String::from("action"), String::from("login_unlocked"));
+        params.insert(String::from("entityType"), String::from("login"));
+        params.insert(String::from("entityId"), String::from(" 3 "));
+        params.insert(String::from("from"), String::from("100"));
+        params.insert(String::from("limit"), String::from("5"));
+        params.insert(String::from("offset"), String::from("0"));
+        let expected = AuditFilter {
+            actor_id: Some(UserId(1)),
+            action: Some(AuditAction::LoginUnlocked),
+            entity_type: Some(AuditEntity::Login),
+            entity_id: Some(String::from("3")),
+            from: Some(100),
+            to: None,
+            pagination: Pagination {
+                limit: Some(5),
+                offset: 0,
+                cursor: None,
+            },
+        };
+        assert_eq!(AuditFilter::extract_audit_filter(params).unwrap(), expected);
+    }
+
+    #[test]
+    fn invalid_audit_filter() {
+        let mut params = HashMap::new();
+        params.insert(String::from("entityType"), String::from("invoice"));
+        let filter_result = format!("{}", AuditFilter::extract_audit_filter(params).unwrap_err());
+        assert_eq!(filter_result, "Invalid parameter: entityType=invoice");
+    }
+
+    #[test]
+    fn filter_matches_entries() {
+        let filter = AuditFilter {
+            action: Some(AuditAction::Update),
+            from: Some(100),
+            to: Some(200),
+            ..AuditFilter::default()
+        };
+        assert!(filter.matches(&entry(AuditAction::Update, 150)));
+        assert!(!filter.matches(&entry(AuditAction::Delete, 150)));
+        assert!(!filter.matches(&entry(AuditAction::Update, 201)));
+        assert!(AuditFilter::default().matches(&entry(AuditAction::Delete, 0)));
+    }
+}
@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+use crate::errors::Error;
+use crate::models::validation::{Validate, Validator, MAX_NAME_LENGTH, MAX_TEXT_LENGTH};
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
 pub struct Company {
     pub id: Option<CompanyId>,
     pub name: String,
@@ -10,13 +14,40 @@ pub struct Company {
     pub is_delete: bool,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+impl Validate for Company {
+    fn validate(&self) -> Result<(), Error> {
+        let mut validator = Validator::new();
+        if let Some(id) = &self.id {
+            validator.id("id", id.0);
+        }
+        validator
+            .length("name", &self.name, 1, MAX_NAME_LENGTH)
+            .email("email", &self.email)
+            .length("address", &self.address, 0, MAX_NAME_LENGTH)
+            .length("description", &self.description, 0, MAX_TEXT_LENGTH)
+            .finish()
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, ToSchema)]
 pub struct CompanyId(pub i32);
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
 pub struct NewCompany {
     pub email: String,
     pub name: String,
     pub address: String,
     pub description: String,
 }
+
+impl Validate for NewCompany {
+    fn validate(&self) -> Result<(), Error> {
+        let mut validator = Validator::new();
+        validator
+            .email("email", &self.email)
+            .length("name", &self.name, 1, MAX_NAME_LENGTH)
+            .length("address", &self.address, 0, MAX_NAME_LENGTH)
+            .length("description", &self.description, 0, MAX_TEXT_LENGTH)
+            .finish()
+    }
+}
@@ -0,0 +1,97 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::errors::Error;
+use crate::models::user::UserId;
+use crate::models::validation::{Validate, Validator, MAX_TEXT_LENGTH};
+
+// What a token sent by email allows, a token can't be used for another purpose
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailTokenPurpose {
+    VerifyEmail,
+    ResetPassword,
+}
+
+impl EmailTokenPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmailTokenPurpose::VerifyEmail => "verify_email",
+            EmailTokenPurpose::ResetPassword => "reset_password",
+        }
+    }
+}
+
+impl fmt::Display for EmailTokenPurpose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// One-time token sent by email, only the id of the signed token is stored
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct EmailToken {
+    pub id: Option<EmailTokenId>,
+    pub jti: String,
+    pub user_id: UserId,
+    pub purpose: EmailTokenPurpose,
+    pub expires_at: i64,
+    // Unix timestamp in seconds, empty until the token is used
+    pub used_at: Option<i64>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EmailTokenId(pub i32);
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct NewEmailToken {
+    pub jti: String,
+    pub user_id: UserId,
+    pub purpose: EmailTokenPurpose,
+    pub expires_at: i64,
+}
+
+// Body of the request verifying the email with the token of the verification email
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+impl Validate for VerifyEmailRequest {
+    fn validate(&self) -> Result<(), Error> {
+        Validator::new()
+            .length("token", &self.token, 1, MAX_TEXT_LENGTH)
+            .finish()
+    }
+}
+
+// Body of the requests sending an email to the address, forgot password or resend verification
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct EmailRequest {
+    pub email: String,
+}
+
+impl Validate for EmailRequest {
+    fn validate(&self) -> Result<(), Error> {
+        Validator::new().email("email", &self.email).finish()
+    }
+}
+
+// Body of the request setting a new password with the token of the reset email
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+impl Validate for ResetPasswordRequest {
+    fn validate(&self) -> Result<(), Error> {
+        let mut validator = Validator::new();
+        validator
+            .length("token", &self.token, 1, MAX_TEXT_LENGTH)
+            .password("new_password", &self.new_password)
+            .finish()
+    }
+}
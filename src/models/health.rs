@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// Result of one check of the readiness probe
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct HealthCheck {
+    pub name: String,
+    pub ok: bool,
+    // Why the check failed, empty when it passed
+    pub detail: Option<String>,
+}
+
+// Body of the liveness and readiness probes, status is ok, ready or not_ready
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct Health {
+    pub status: String,
+    pub checks: Vec<HealthCheck>,
+}
+
+impl Health {
+    pub fn is_ready(&self) -> bool {
+        self.checks.iter().all(|check| check.ok)
+    }
+}
+
+// Connections of the pool of the store, exported as metrics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStatus {
+    // Open connections, idle ones included
+    pub size: u32,
+    pub idle: u32,
+    pub max: u32,
+}
@@ -1,8 +1,17 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
+use crate::errors::Error;
 use crate::models::company::CompanyId;
+use crate::models::validation::{
+    Validate, Validator, MAX_JOB_QUANTITY, MAX_NAME_LENGTH, MAX_TEXT_LENGTH,
+};
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
 pub struct Job {
     pub id: Option<JobId>,
     pub job_name: String,
@@ -13,12 +22,121 @@ pub struct Job {
     pub job_level: String,
     pub description: String,
     pub is_delete: bool,
+    // Changed through the status route only, ignored by the update
+    #[serde(default)]
+    pub status: JobStatus,
+    // Unix timestamps in seconds, published_at is set the first time the job is published
+    #[serde(default)]
+    pub published_at: Option<i64>,
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+}
+
+impl Job {
+    // Open to candidates: published and not past the expiry date, even before
+    // the expiry task marked it expired
+    pub fn is_open(&self, now: i64) -> bool {
+        !self.is_delete
+            && self.status == JobStatus::Published
+            && self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+impl Validate for Job {
+    fn validate(&self) -> Result<(), Error> {
+        let mut validator = Validator::new();
+        if let Some(id) = &self.id {
+            validator.id("id", id.0);
+        }
+        validator
+            .length("job_name", &self.job_name, 1, MAX_NAME_LENGTH)
+            .id("company_id", self.company_id.0)
+            .length("location", &self.location, 1, MAX_NAME_LENGTH)
+            .range("quantity", self.quantity as i64, 1, MAX_JOB_QUANTITY)
+            .range("salary", self.salary as i64, 0, i32::MAX as i64)
+            .length("job_level", &self.job_level, 1, MAX_NAME_LENGTH)
+            .length("description", &self.description, 0, MAX_TEXT_LENGTH);
+        // As for a new job, the expiry date can't be moved to the past
+        if let Some(expires_at) = self.expires_at {
+            validator.future("expires_at", expires_at, Utc::now().timestamp());
+        }
+        validator.finish()
+    }
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, ToSchema)]
 pub struct JobId(pub i32);
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+// Status of a job, stored as text in `jobs.status`
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Draft,
+    // Jobs created without a status are published right away, as before the statuses
+    #[default]
+    Published,
+    Paused,
+    Closed,
+    Expired,
+}
+
+pub const ALL_JOB_STATUSES: [JobStatus; 5] = [
+    JobStatus::Draft,
+    JobStatus::Published,
+    JobStatus::Paused,
+    JobStatus::Closed,
+    JobStatus::Expired,
+];
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Draft => "draft",
+            JobStatus::Published => "published",
+            JobStatus::Paused => "paused",
+            JobStatus::Closed => "closed",
+            JobStatus::Expired => "expired",
+        }
+    }
+
+    // Closed and expired jobs can't change anymore
+    pub fn is_final(&self) -> bool {
+        matches!(self, JobStatus::Closed | JobStatus::Expired)
+    }
+
+    // Transitions made by hr, jobs are only expired by the expiry task
+    pub fn can_transition_to(&self, next: JobStatus) -> bool {
+        if self.is_final() {
+            return false;
+        }
+        match next {
+            JobStatus::Closed => true,
+            JobStatus::Published => matches!(self, JobStatus::Draft | JobStatus::Paused),
+            JobStatus::Paused => *self == JobStatus::Published,
+            JobStatus::Draft | JobStatus::Expired => false,
+        }
+    }
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for JobStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ALL_JOB_STATUSES
+            .iter()
+            .find(|status| status.as_str() == s)
+            .copied()
+            .ok_or_else(|| Error::InvalidParameter(format!("status {}", s)))
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, ToSchema)]
 pub struct NewJob {
     pub job_name: String,
     pub company_id: CompanyId,
@@ -27,4 +145,179 @@ pub struct NewJob {
     pub salary: i32,
     pub job_level: String,
     pub description: String,
+    // Draft or published
+    #[serde(default)]
+    pub status: JobStatus,
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+}
+
+impl Validate for NewJob {
+    fn validate(&self) -> Result<(), Error> {
+        let mut validator = Validator::new();
+        validator
+            .length("job_name", &self.job_name, 1, MAX_NAME_LENGTH)
+            .id("company_id", self.company_id.0)
+            .length("location", &self.location, 1, MAX_NAME_LENGTH)
+            .range("quantity", self.quantity as i64, 1, MAX_JOB_QUANTITY)
+            .range("salary", self.salary as i64, 0, i32::MAX as i64)
+            .length("job_level", &self.job_level, 1, MAX_NAME_LENGTH)
+            .length("description", &self.description, 0, MAX_TEXT_LENGTH)
+            .one_of(
+                "status",
+                self.status.as_str(),
+                &[JobStatus::Draft.as_str(), JobStatus::Published.as_str()],
+            );
+        if let Some(expires_at) = self.expires_at {
+            validator.future("expires_at", expires_at, Utc::now().timestamp());
+        }
+        validator.finish()
+    }
+}
+
+// Body of the hr request moving a job through its lifecycle
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct UpdateJobStatus {
+    pub job_id: JobId,
+    pub status: JobStatus,
+}
+
+impl Validate for UpdateJobStatus {
+    fn validate(&self) -> Result<(), Error> {
+        Validator::new().id("job_id", self.job_id.0).finish()
+    }
+}
+
+// Published job of company 1 shared by the tests, which change the fields they
+// need with the struct update syntax
+#[cfg(test)]
+pub fn job_fixture() -> Job {
+    Job {
+        id: Some(JobId(1)),
+        job_name: "Intern Smart Contract".to_string(),
+        company_id: CompanyId(1),
+        location: "So 2 Pham Van Bach".to_string(),
+        quantity: 2,
+        salary: 2_000_000,
+        job_level: "Intern".to_string(),
+        description: "Intern Smart Contract".to_string(),
+        is_delete: false,
+        status: JobStatus::Published,
+        published_at: None,
+        expires_at: None,
+    }
+}
+
+// The fields of job_fixture creating a job
+#[cfg(test)]
+pub fn new_job_fixture() -> NewJob {
+    let job = job_fixture();
+    NewJob {
+        job_name: job.job_name,
+        company_id: job.company_id,
+        location: job.location,
+        quantity: job.quantity,
+        salary: job.salary,
+        job_level: job.job_level,
+        description: job.description,
+        status: job.status,
+        expires_at: job.expires_at,
+    }
+}
+
+#[cfg(test)]
+mod job_status_tests {
+    use chrono::Utc;
+
+    use super::{job_fixture, new_job_fixture, Job, JobStatus, NewJob, ALL_JOB_STATUSES};
+    use crate::models::validation::Validate;
+
+    const NOW: i64 = 1_724_000_000;
+
+    fn job(status: JobStatus, expires_at: Option<i64>) -> Job {
+        Job {
+            status,
+            expires_at,
+            ..job_fixture()
+        }
+    }
+
+    #[test]
+    fn status_name_round_trip() {
+        for status in ALL_JOB_STATUSES {
+            assert_eq!(status.as_str().parse::<JobStatus>().unwrap(), status);
+            assert_eq!(
+                serde_json::to_string(&status).unwrap(),
+                format!("\"{}\"", status)
+            );
+        }
+    }
+
+    #[test]
+    fn lifecycle_transitions() {
+        use JobStatus::*;
+        assert!(Draft.can_transition_to(Published));
+        assert!(Published.can_transition_to(Paused));
+        assert!(Paused.can_transition_to(Published));
+        assert!(Draft.can_transition_to(Closed));
+        assert!(Paused.can_transition_to(Closed));
+
+        assert!(!Draft.can_transition_to(Paused));
+        assert!(!Published.can_transition_to(Draft));
+        assert!(!Published.can_transition_to(Expired));
+        assert!(!Closed.can_transition_to(Published));
+        assert!(!Expired.can_transition_to(Published));
+    }
+
+    #[test]
+    fn only_published_jobs_before_expiry_are_open() {
+        assert!(job(JobStatus::Published, None).is_open(NOW));
+        assert!(job(JobStatus::Published, Some(NOW + 1)).is_open(NOW));
+        // Not expired by the task yet, but already past the expiry date
+        assert!(!job(JobStatus::Published, Some(NOW)).is_open(NOW));
+        assert!(!job(JobStatus::Draft, None).is_open(NOW));
+        assert!(!job(JobStatus::Paused, None).is_open(NOW));
+    }
+
+    #[test]
+    fn new_job_is_draft_or_published() {
+        let new_job = NewJob {
+            status: JobStatus::Draft,
+            ..new_job_fixture()
+        };
+        assert!(new_job.validate().is_ok());
+        let closed = NewJob {
+            status: JobStatus::Closed,
+            ..new_job.clone()
+        };
+        assert!(closed.validate().is_err());
+        let expired = NewJob {
+            expires_at: Some(NOW),
+            ..new_job
+        };
+        assert!(expired.validate().is_err());
+    }
+
+    #[test]
+    fn updated_job_expires_in_future() {
+        assert!(job_fixture().validate().is_ok());
+        assert!(job(JobStatus::Published, Some(NOW)).validate().is_err());
+        let in_future = Utc::now().timestamp() + 3600;
+        assert!(job(JobStatus::Published, Some(in_future))
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn status_defaults_to_published() {
+        let new_job: NewJob = serde_json::from_str(
+            r#"{"job_name": "Junior Web3", "company_id": 1, "location": "Ha Noi",
+                "quantity": 1, "salary": 0, "job_level": "Junior", "description": ""}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            (new_job.status, new_job.expires_at),
+            (JobStatus::Published, None)
+        );
+    }
 }
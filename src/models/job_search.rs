@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use crate::errors::Error;
+use crate::models::company::CompanyId;
+use crate::models::job::Job;
+use crate::models::pagination::{Page, Pagination};
+
+/// Column the job list is ordered by
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobSortField {
+    Salary,
+    #[default]
+    CreatedOn,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Job search struct which is getting extract
+/// from query params
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct JobSearch {
+    /// Free text matched against job name and description
+    pub q: Option<String>,
+    /// Part of the job location, case insensitive
+    pub location: Option<String>,
+    /// Job level, case insensitive
+    pub job_level: Option<String>,
+    pub company_id: Option<CompanyId>,
+    /// Inclusive salary range
+    pub min_salary: Option<i32>,
+    pub max_salary: Option<i32>,
+    pub sort: JobSortField,
+    pub order: SortOrder,
+    /// Limit with offset or cursor, the cursor key holds the value of the sort column
+    pub pagination: Pagination,
+}
+
+/// Page of jobs matching a search with the total count of matching jobs
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobSearchResult {
+    pub jobs: Page<Job>,
+    pub total: i64,
+}
+
+/// Extract query parameters from the `/job/list-job` route
+/// # Example query
+/// `/job/list-job?q=smart contract&location=Pham Van Bach&jobLevel=junior
+/// &minSalary=1000000&maxSalary=9000000&sort=salary&order=asc&limit=10&offset=0`
+///
+/// Every parameter is optional, `limit` has to be sent with `offset` or `cursor`.
+impl JobSearch {
+    pub fn extract_job_search(params: HashMap<String, String>) -> Result<JobSearch, Error> {
+        let mut search = JobSearch::default();
+        if params.contains_key("limit")
+            || params.contains_key("offset")
+            || params.contains_key("cursor")
+        {
+            search.pagination = Pagination::extract_pagination(params.clone())?;
+        }
+        search.q = non_empty(params.get("q"));
+        search.location = non_empty(params.get("location"));
+        search.job_level = non_empty(params.get("jobLevel"));
+        if let Some(company_id) = params.get("companyId") {
+            search.company_id = Some(CompanyId(company_id.parse().map_err(Error::Parse)?));
+        }
+        if let Some(min_salary) = params.get("minSalary") {
+            search.min_salary = Some(min_salary.parse().map_err(Error::Parse)?);
+        }
+        if let Some(max_salary) = params.get("maxSalary") {
+            search.max_salary = Some(max_salary.parse().map_err(Error::Parse)?);
+        }
+        if let Some(sort) = params.get("sort") {
+            search.sort = match sort.as_str() {
+                "salary" => JobSortField::Salary,
+                "created_on" => JobSortField::CreatedOn,
+                _ => return Err(Error::InvalidParameter(format!("sort={}", sort))),
+            };
+        }
+        if let Some(order) = params.get("order") {
+            search.order = match order.to_lowercase().as_str() {
+                "asc" => SortOrder::Asc,
+                "desc" => SortOrder::Desc,
+                _ => return Err(Error::InvalidParameter(format!("order={}", order))),
+            };
+        }
+        Ok(search)
+    }
+}
+
+pub fn non_empty(value: Option<&String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Split a text into lowercase alphanumeric tokens, used by the in memory search index
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod job_search_tests {
+    use super::{
+        tokenize, CompanyId, Error, HashMap, JobSearch, JobSortField, Pagination, SortOrder,
+    };
+
+    #[test]
+    fn empty_job_search() {
+        let search = JobSearch::extract_job_search(HashMap::new()).unwrap();
+        assert_eq!(search, JobSearch::default());
+        assert_eq!(search.sort, JobSortField::CreatedOn);
+        assert_eq!(search.order, SortOrder::Desc);
+    }
+
+    #[test]
+    fn valid_job_search() {
+        let mut params = HashMap::new();
+        params.insert(String::from("q"), String::from(" smart contract "));
+        params.insert(String::from("jobLevel"), String::from("Junior"));
+        params.insert(String::from("companyId"), String::from("2"));
+        params.insert(String::from("minSalary"), String::from("1000"));
+        params.insert(String::from("sort"), String::from("salary"));
+        params.insert(String::from("order"), String::from("ASC"));
+        params.insert(String::from("limit"), String::from("5"));
+        params.insert(String::from("offset"), String::from("10"));
+        let expected = JobSearch {
+            q: Some(String::from("smart contract")),
+            location: None,
+            job_level: Some(String::from("Junior")),
+            company_id: Some(CompanyId(2)),
+            min_salary: Some(1000),
+            max_salary: None,
+            sort: JobSortField::Salary,
+            order: SortOrder::Asc,
+            pagination: Pagination {
+                limit: Some(5),
+                offset: 10,
+                cursor: None,
+            },
+        };
+        assert_eq!(JobSearch::extract_job_search(params).unwrap(), expected);
+    }
+
+    #[test]
+    fn missing_offset_parameter() {
+        let mut params = HashMap::new();
+        params.insert(String::from("limit"), String::from("1"));
+        params.insert(String::from("q"), String::from("rust"));
+
+        let search_result = format!("{}", JobSearch::extract_job_search(params).unwrap_err());
+        assert_eq!(search_result, format!("{}", Error::MissingParameters));
+    }
+
+    #[test]
+    fn sort_by_created_on() {
+        let mut params = HashMap::new();
+        params.insert(String::from("sort"), String::from("created_on"));
+        params.insert(String::from("order"), String::from("asc"));
+
+        let search = JobSearch::extract_job_search(params).unwrap();
+        assert_eq!(search.sort, JobSortField::CreatedOn);
+        assert_eq!(search.order, SortOrder::Asc);
+    }
+
+    #[test]
+    fn invalid_sort_parameter() {
+        let mut params = HashMap::new();
+        params.insert(String::from("sort"), String::from("quantity"));
+
+        let search_result = format!("{}", JobSearch::extract_job_search(params).unwrap_err());
+        assert_eq!(search_result, "Invalid parameter: sort=quantity");
+    }
+
+    #[test]
+    fn wrong_salary_type() {
+        let mut params = HashMap::new();
+        params.insert(String::from("maxSalary"), String::from("C"));
+
+        let search_result = format!("{}", JobSearch::extract_job_search(params).unwrap_err());
+        assert_eq!(
+            search_result,
+            "Can't parse parameter: invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn tokenize_text() {
+        assert_eq!(
+            tokenize("Senior Rust/Go-Developer, 2 years"),
+            vec!["senior", "rust", "go", "developer", "2", "years"]
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use std::net::IpAddr;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::errors::Error;
+use crate::models::validation::{Validate, Validator};
+
+// Failed logins of an account or a client ip, kept in the store so every instance shares them
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct LoginAttempt {
+    pub subject: String,
+    // Failed logins since `first_failed_at`
+    pub failed_count: i32,
+    pub first_failed_at: i64,
+    // Unix timestamps in seconds, login of the subject is refused before them
+    pub next_attempt_at: i64,
+    pub locked_until: Option<i64>,
+}
+
+// Time of the next allowed attempt and end of the lockout after the given failed logins
+pub type LoginPenalty<'a> = &'a (dyn Fn(i32) -> (i64, Option<i64>) + Send + Sync);
+
+impl LoginAttempt {
+    pub fn new(subject: &str, now: i64) -> LoginAttempt {
+        LoginAttempt {
+            subject: subject.to_string(),
+            failed_count: 0,
+            first_failed_at: now,
+            next_attempt_at: now,
+            locked_until: None,
+        }
+    }
+
+    // Error refusing the login while the subject is locked or waiting for its delay
+    pub fn refusal(&self, now: i64) -> Option<Error> {
+        if let Some(locked_until) = self.locked_until.filter(|until| *until > now) {
+            return Some(Error::LoginLocked(locked_until - now));
+        }
+        if self.next_attempt_at > now {
+            return Some(Error::LoginThrottled(self.next_attempt_at - now));
+        }
+        None
+    }
+
+    // Count the attempt as failed before the password is verified and apply its penalty,
+    // so concurrent attempts see it. The count starts again when the first failed login
+    // is older than `window_start` or the lockout is over.
+    pub fn reserve(
+        &mut self,
+        now: i64,
+        window_start: i64,
+        penalty: LoginPenalty,
+    ) -> Result<(), Error> {
+        if let Some(refusal) = self.refusal(now) {
+            return Err(refusal);
+        }
+        if self.first_failed_at < window_start || self.locked_until.is_some() {
+            self.failed_count = 0;
+            self.first_failed_at = now;
+        }
+        self.failed_count += 1;
+        (self.next_attempt_at, self.locked_until) = penalty(self.failed_count);
+        Ok(())
+    }
+
+    // Give back a reserved attempt which turned out to be a valid login, its penalty is
+    // lifted unless another attempt has changed it since
+    pub fn release(&mut self, reserved: &LoginAttempt, now: i64) {
+        self.failed_count = (self.failed_count - 1).max(0);
+        if self.next_attempt_at == reserved.next_attempt_at
+            && self.locked_until == reserved.locked_until
+        {
+            self.next_attempt_at = self.next_attempt_at.min(now);
+            self.locked_until = None;
+        }
+    }
+}
+
+pub fn account_subject(email: &str) -> String {
+    format!("account:{}", email)
+}
+
+pub fn ip_subject(ip: &IpAddr) -> String {
+    format!("ip:{}", ip)
+}
+
+// Body of the request unlocking the login of an account, a client ip or both
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct UnlockLogin {
+    pub email: Option<String>,
+    pub ip: Option<String>,
+}
+
+impl Validate for UnlockLogin {
+    fn validate(&self) -> Result<(), Error> {
+        let mut validator = Validator::new();
+        if let Some(email) = &self.email {
+            validator.email("email", email);
+        }
+        if let Some(ip) = &self.ip {
+            validator.ip("ip", ip);
+        }
+        validator
+            .required("email", self.email.is_some() || self.ip.is_some())
+            .finish()
+    }
+}
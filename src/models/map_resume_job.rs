@@ -1,20 +1,36 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
+use crate::errors::Error;
+use crate::models::application::ApplicationStatus;
 use crate::models::job::JobId;
 use crate::models::resume::ResumeId;
+use crate::models::validation::{Validate, Validator};
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
 pub struct MapResumeJob {
     pub id: Option<MapResumeJobId>,
     pub resume_id: ResumeId,
     pub job_id: JobId,
+    #[serde(default)]
+    pub status: ApplicationStatus,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
 pub struct NewMapResumeJob {
     pub resume_id: ResumeId,
     pub job_id: JobId,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+impl Validate for NewMapResumeJob {
+    fn validate(&self) -> Result<(), Error> {
+        let mut validator = Validator::new();
+        validator
+            .id("resume_id", self.resume_id.0)
+            .id("job_id", self.job_id.0)
+            .finish()
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, ToSchema)]
 pub struct MapResumeJobId(pub i32);
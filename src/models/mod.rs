@@ -1,10 +1,22 @@
+pub mod application;
+pub mod audit;
 pub mod company;
+pub mod email_token;
+pub mod health;
 pub mod job;
+pub mod job_search;
+pub mod login_attempt;
 pub mod map_resume_job;
 pub mod pagination;
+pub mod permission;
+pub mod recommendation;
+pub mod refresh_token;
 pub mod resume;
+pub mod resume_search;
+pub mod revoked_token;
 pub mod role;
 pub mod store_db;
 pub mod store_in_memory;
 pub mod store_trait;
 pub mod user;
+pub mod validation;
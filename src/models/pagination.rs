@@ -1,26 +1,54 @@
 use std::collections::HashMap;
 
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+
 use crate::errors::Error;
+use crate::models::validation::{Validate, Validator};
+
+/// Largest number of items a page can hold
+pub const MAX_PAGE_LIMIT: i64 = 100;
 
 /// Pagination struct which is getting extract
 /// from query params
-#[derive(Default, Debug, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct Pagination {
     /// The index of the last item which has to be returned
     pub limit: Option<i32>,
     /// The index of the first item which has to be returned
     pub offset: i32,
+    /// Position after which the page starts, replaces the offset
+    pub cursor: Option<Cursor>,
 }
 
-#[derive(Default, Debug, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct PaginationForJob {
     /// The index of the last item which has to be returned
     pub limit: Option<i32>,
     /// The index of the first item which has to be returned
     pub offset: i32,
+    /// Position after which the page starts, replaces the offset
+    pub cursor: Option<Cursor>,
     pub job_id: i32,
 }
 
+/// Keyset position of the last item of a page.
+/// `key` is the value of the sort column, `created_on` in microseconds by default,
+/// and `id` breaks ties between items with the same key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    pub key: i64,
+    pub id: i32,
+}
+
+/// A page of items with the cursor of the next page
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<Cursor>,
+    pub has_more: bool,
+}
+
 /// Extract query parameters from the `/questions` route
 /// # Example query
 /// GET requests to this route can have a pagination attached so we just
@@ -42,8 +70,18 @@ pub struct PaginationForJob {
 impl Pagination {
     pub fn extract_pagination(params: HashMap<String, String>) -> Result<Pagination, Error> {
         // Could be improved in the future
+        if params.contains_key("limit") && params.contains_key("cursor") {
+            let pagination = Pagination {
+                limit: Some(params.get("limit").unwrap().parse().map_err(Error::Parse)?),
+                offset: 0,
+                // Takes the "cursor" parameter in the query and tries to decode it
+                cursor: Some(Cursor::decode(params.get("cursor").unwrap())?),
+            };
+            pagination.validate()?;
+            return Ok(pagination);
+        }
         if params.contains_key("limit") && params.contains_key("offset") {
-            return Ok(Pagination {
+            let pagination = Pagination {
                 // Takes the "limit" parameter in the query and tries to convert it to a number
                 limit: Some(params.get("limit").unwrap().parse().map_err(Error::Parse)?),
                 // Takes the "offset" parameter in the query and tries to convert it to a number
@@ -52,40 +90,127 @@ impl Pagination {
                     .unwrap()
                     .parse()
                     .map_err(Error::Parse)?,
-            });
+                cursor: None,
+            };
+            pagination.validate()?;
+            return Ok(pagination);
         }
         Err(Error::MissingParameters)
     }
 }
 
+impl Validate for Pagination {
+    // The stores fetch one item more than the limit to know if there is a next page
+    fn validate(&self) -> Result<(), Error> {
+        let mut validator = Validator::new();
+        if let Some(limit) = self.limit {
+            validator.range("limit", limit as i64, 1, MAX_PAGE_LIMIT);
+        }
+        validator
+            .range("offset", self.offset as i64, 0, i32::MAX as i64)
+            .finish()
+    }
+}
+
 impl PaginationForJob {
     pub fn extract_pagination_job(
         params: HashMap<String, String>,
     ) -> Result<PaginationForJob, Error> {
-        if params.contains_key("limit")
-            && params.contains_key("offset")
-            && params.contains_key("jobId")
-        {
+        if params.contains_key("jobId") {
+            // Takes the "jobId" parameter in the query and tries to convert it to a number
+            let job_id = params.get("jobId").unwrap().parse().map_err(Error::Parse)?;
+            let pagination = Pagination::extract_pagination(params)?;
             return Ok(PaginationForJob {
-                // Takes the "limit" parameter in the query and tries to convert it to a number
-                limit: Some(params.get("limit").unwrap().parse().map_err(Error::Parse)?),
-                // Takes the "offset" parameter in the query and tries to convert it to a number
-                offset: params
-                    .get("offset")
-                    .unwrap()
-                    .parse()
-                    .map_err(Error::Parse)?,
-                // Takes the "jobId" parameter in the query and tries to convert it to a number
-                job_id: params.get("jobId").unwrap().parse().map_err(Error::Parse)?,
+                limit: pagination.limit,
+                offset: pagination.offset,
+                cursor: pagination.cursor,
+                job_id,
             });
         }
         Err(Error::MissingParameters)
     }
+
+    pub fn pagination(&self) -> Pagination {
+        Pagination {
+            limit: self.limit,
+            offset: self.offset,
+            cursor: self.cursor.clone(),
+        }
+    }
+}
+
+impl Cursor {
+    /// Encode the cursor as an opaque url safe string
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}:{}", self.key, self.id))
+    }
+
+    pub fn decode(value: &str) -> Result<Cursor, Error> {
+        let invalid = || Error::InvalidParameter("cursor".to_string());
+        let decoded = URL_SAFE_NO_PAD.decode(value).map_err(|_| invalid())?;
+        let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+        let (key, id) = decoded.split_once(':').ok_or_else(invalid)?;
+        Ok(Cursor {
+            key: key.parse().map_err(|_| invalid())?,
+            id: id.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl<T> Page<T> {
+    /// Build a page from rows fetched with one extra row over the limit,
+    /// the extra row only tells there is a next page
+    pub fn from_rows(mut rows: Vec<(T, Cursor)>, limit: Option<i32>) -> Page<T> {
+        let has_more = match limit {
+            Some(limit) if rows.len() > limit.max(0) as usize => {
+                rows.truncate(limit.max(0) as usize);
+                true
+            }
+            _ => false,
+        };
+        let next_cursor = if has_more {
+            rows.last().map(|(_, cursor)| cursor.clone())
+        } else {
+            None
+        };
+        Page {
+            items: rows.into_iter().map(|(item, _)| item).collect(),
+            next_cursor,
+            has_more,
+        }
+    }
+
+    /// Sort rows by their cursor and cut the requested page, for stores without keyset queries
+    pub fn paginate(
+        mut rows: Vec<(T, Cursor)>,
+        pagination: &Pagination,
+        descending: bool,
+    ) -> Page<T> {
+        rows.sort_by_key(|(_, cursor)| (cursor.key, cursor.id));
+        if descending {
+            rows.reverse();
+        }
+        let rows = rows
+            .into_iter()
+            .filter(|(_, cursor)| match &pagination.cursor {
+                Some(after) if descending => (cursor.key, cursor.id) < (after.key, after.id),
+                Some(after) => (cursor.key, cursor.id) > (after.key, after.id),
+                None => true,
+            })
+            .skip(pagination.offset.max(0) as usize)
+            .take(
+                pagination
+                    .limit
+                    .map_or(usize::MAX, |limit| limit.max(0) as usize + 1),
+            )
+            .collect();
+        Page::from_rows(rows, pagination.limit)
+    }
 }
 
 #[cfg(test)]
 mod pagination_tests {
-    use super::{Error, HashMap, Pagination};
+    use super::{Cursor, Error, HashMap, Page, Pagination, MAX_PAGE_LIMIT};
 
     #[test]
     fn valid_pagination() {
@@ -97,6 +222,7 @@ mod pagination_tests {
         let expected = Pagination {
             limit: Some(1),
             offset: 1,
+            cursor: None,
         };
         assert_eq!(pagination_result.unwrap(), expected);
     }
@@ -146,4 +272,82 @@ mod pagination_tests {
 
         assert_eq!(pagination_result, expected);
     }
+
+    #[test]
+    fn limit_out_of_range() {
+        for limit in ["0", "-1", &(MAX_PAGE_LIMIT + 1).to_string(), "2147483647"] {
+            let mut params = HashMap::new();
+            params.insert(String::from("limit"), String::from(limit));
+            params.insert(String::from("offset"), String::from("0"));
+            assert!(matches!(
+                Pagination::extract_pagination(params),
+                Err(Error::Validation(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn negative_offset() {
+        let mut params = HashMap::new();
+        params.insert(String::from("limit"), String::from("10"));
+        params.insert(String::from("offset"), String::from("-1"));
+        assert!(matches!(
+            Pagination::extract_pagination(params),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn valid_cursor_pagination() {
+        let cursor = Cursor {
+            key: 1723600000123456,
+            id: 7,
+        };
+        let mut params = HashMap::new();
+        params.insert(String::from("limit"), String::from("2"));
+        params.insert(String::from("cursor"), cursor.encode());
+        let expected = Pagination {
+            limit: Some(2),
+            offset: 0,
+            cursor: Some(cursor),
+        };
+        assert_eq!(Pagination::extract_pagination(params).unwrap(), expected);
+    }
+
+    #[test]
+    fn invalid_cursor() {
+        let mut params = HashMap::new();
+        params.insert(String::from("limit"), String::from("2"));
+        params.insert(String::from("cursor"), String::from("not a cursor"));
+        let pagination_result = format!("{}", Pagination::extract_pagination(params).unwrap_err());
+
+        assert_eq!(pagination_result, "Invalid parameter: cursor");
+    }
+
+    #[test]
+    fn paginate_with_cursor() {
+        let rows = |ids: &[i32]| {
+            ids.iter()
+                .map(|id| (*id, Cursor { key: 0, id: *id }))
+                .collect::<Vec<_>>()
+        };
+        let mut pagination = Pagination {
+            limit: Some(2),
+            offset: 0,
+            cursor: None,
+        };
+        let first = Page::paginate(rows(&[3, 1, 5, 2, 4]), &pagination, false);
+        assert_eq!(first.items, vec![1, 2]);
+        assert!(first.has_more);
+
+        pagination.cursor = first.next_cursor;
+        let second = Page::paginate(rows(&[3, 1, 5, 2, 4]), &pagination, false);
+        assert_eq!(second.items, vec![3, 4]);
+
+        pagination.cursor = second.next_cursor;
+        let last = Page::paginate(rows(&[3, 1, 5, 2, 4]), &pagination, false);
+        assert_eq!(last.items, vec![5]);
+        assert!(!last.has_more);
+        assert_eq!(last.next_cursor, None);
+    }
 }
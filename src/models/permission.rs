@@ -0,0 +1,90 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::errors::Error;
+
+// Named permission granted to a role, stored as text in the `role_permissions` table
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash, ToSchema)]
+pub enum Permission {
+    // Update, change password and delete the own account
+    #[serde(rename = "account:manage")]
+    ManageAccount,
+    #[serde(rename = "resume:manage")]
+    ManageResume,
+    #[serde(rename = "job:apply")]
+    ApplyJob,
+    #[serde(rename = "job:manage")]
+    ManageJob,
+    #[serde(rename = "company:manage")]
+    ManageCompany,
+    #[serde(rename = "role:assign")]
+    AssignRole,
+}
+
+pub const ALL_PERMISSIONS: [Permission; 6] = [
+    Permission::ManageAccount,
+    Permission::ManageResume,
+    Permission::ApplyJob,
+    Permission::ManageJob,
+    Permission::ManageCompany,
+    Permission::AssignRole,
+];
+
+impl Permission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::ManageAccount => "account:manage",
+            Permission::ManageResume => "resume:manage",
+            Permission::ApplyJob => "job:apply",
+            Permission::ManageJob => "job:manage",
+            Permission::ManageCompany => "company:manage",
+            Permission::AssignRole => "role:assign",
+        }
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Permission {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ALL_PERMISSIONS
+            .iter()
+            .find(|permission| permission.as_str() == s)
+            .copied()
+            .ok_or_else(|| Error::UnknownPermission(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod permission_tests {
+    use super::{Permission, ALL_PERMISSIONS};
+
+    #[test]
+    fn permission_name_round_trip() {
+        for permission in ALL_PERMISSIONS {
+            assert_eq!(
+                permission.as_str().parse::<Permission>().unwrap(),
+                permission
+            );
+            assert_eq!(
+                serde_json::to_string(&permission).unwrap(),
+                format!("\"{}\"", permission)
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_permission() {
+        let result = format!("{}", "job:delete".parse::<Permission>().unwrap_err());
+        assert_eq!(result, "Unknown permission: job:delete".to_string());
+    }
+}
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::errors::Error;
+use crate::models::job::Job;
+use crate::models::job_search::tokenize;
+use crate::models::resume::{Resume, ResumeId};
+
+pub const DEFAULT_RECOMMENDATION_LIMIT: usize = 10;
+pub const MAX_RECOMMENDATION_LIMIT: usize = 50;
+// Jobs or resumes loaded from the store to be scored, the ones sharing the most
+// words with the other side are kept
+pub const MAX_RECOMMENDATION_CANDIDATES: i64 = 200;
+
+// Score of a job for a resume out of 100, the sum of the parts so the
+// client can explain why a job or a candidate was suggested
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq, ToSchema)]
+pub struct MatchScore {
+    pub total: i32,
+    // Skills of the resume mentioned by the job name or description
+    pub skills: i32,
+    pub location: i32,
+    // Years of experience of the resume against the job level
+    pub level: i32,
+    // Job salary against the expected salary of the resume
+    pub salary: i32,
+    pub matched_skills: Vec<String>,
+}
+
+// Job suggested to a candidate, scored with the best matching resume
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct JobRecommendation {
+    pub job: Job,
+    pub resume_id: ResumeId,
+    pub score: MatchScore,
+}
+
+// Searchable resume suggested to hr for a job
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct CandidateSuggestion {
+    pub resume: Resume,
+    pub score: MatchScore,
+}
+
+/// Extract the `limit` query parameter of the recommendation routes,
+/// `DEFAULT_RECOMMENDATION_LIMIT` when it isn't sent
+pub fn extract_limit(params: &HashMap<String, String>) -> Result<usize, Error> {
+    match params.get("limit") {
+        Some(limit) => {
+            let limit: usize = limit.parse().map_err(Error::Parse)?;
+            match (1..=MAX_RECOMMENDATION_LIMIT).contains(&limit) {
+                true => Ok(limit),
+                false => Err(Error::InvalidParameter(format!("limit={}", limit))),
+            }
+        }
+        None => Ok(DEFAULT_RECOMMENDATION_LIMIT),
+    }
+}
+
+/// Distinct lowercase words of the skills, only jobs and resumes sharing one
+/// of them are scored by the recommendation
+pub fn skill_words<'a>(skills: impl IntoIterator<Item = &'a String>) -> Vec<String> {
+    let mut words: Vec<String> = skills
+        .into_iter()
+        .flat_map(|skill| tokenize(skill))
+        .collect();
+    words.sort();
+    words.dedup();
+    words
+}
+
+#[cfg(test)]
+mod recommendation_tests {
+    use super::{extract_limit, skill_words, Error, HashMap, DEFAULT_RECOMMENDATION_LIMIT};
+
+    #[test]
+    fn words_of_skills() {
+        let skills = vec!["Node.js".to_string(), "node".to_string(), "C++".to_string()];
+        assert_eq!(skill_words(&skills), vec!["c", "js", "node"]);
+    }
+
+    #[test]
+    fn limit_of_recommendations() {
+        assert_eq!(
+            extract_limit(&HashMap::new()).unwrap(),
+            DEFAULT_RECOMMENDATION_LIMIT
+        );
+        let mut params = HashMap::new();
+        params.insert(String::from("limit"), String::from("3"));
+        assert_eq!(extract_limit(&params).unwrap(), 3);
+        params.insert(String::from("limit"), String::from("0"));
+        assert!(matches!(
+            extract_limit(&params),
+            Err(Error::InvalidParameter(_))
+        ));
+        params.insert(String::from("limit"), String::from("-1"));
+        assert!(matches!(extract_limit(&params), Err(Error::Parse(_))));
+    }
+}
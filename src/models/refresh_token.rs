@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use utoipa::ToSchema;
+
+use crate::errors::Error;
+use crate::models::user::UserId;
+use crate::models::validation::{Validate, Validator, MAX_TOKEN_LENGTH};
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RefreshToken {
+    pub id: Option<RefreshTokenId>,
+    pub user_id: UserId,
+    // Only the hash is stored, the token itself is given to the user once
+    pub token_hash: String,
+    // Every token issued by rotating the same login shares one family
+    pub family_id: String,
+    pub expires_at: i64,
+    pub is_used: bool,
+    pub is_revoked: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RefreshTokenId(pub i32);
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct NewRefreshToken {
+    pub user_id: UserId,
+    pub token_hash: String,
+    pub family_id: String,
+    pub expires_at: i64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+impl Validate for RefreshTokenRequest {
+    fn validate(&self) -> Result<(), Error> {
+        Validator::new()
+            .length("refresh_token", &self.refresh_token, 1, MAX_TOKEN_LENGTH)
+            .finish()
+    }
+}
+
+// Hex encoded sha-256 of the token, refresh tokens are looked up by this hash
+pub fn hash_refresh_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
@@ -1,22 +1,428 @@
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
+use crate::errors::Error;
 use crate::models::user::UserId;
+use crate::models::validation::{Validate, Validator, MAX_NAME_LENGTH, MAX_TEXT_LENGTH};
+
+pub const PDF_CONTENT_TYPE: &str = "application/pdf";
+pub const DOCX_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+// Largest number of skills, languages, experiences or educations of a profile
+pub const MAX_PROFILE_ITEMS: usize = 50;
+pub const MAX_YEARS_OF_EXPERIENCE: i64 = 70;
+const MIN_YEAR: i64 = 1950;
+const MAX_YEAR: i64 = 2100;
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
 pub struct Resume {
     pub id: Option<ResumeId>,
     pub user_id: UserId,
     pub email: String,
+    // Link to a resume hosted elsewhere, empty when the resume has a file
     pub url: String,
     pub is_delete: bool,
+    // Set by the upload only, a file sent in the json body is ignored
+    #[serde(default)]
+    pub file: Option<ResumeFile>,
+    #[serde(default)]
+    pub profile: ResumeProfile,
+    // Listed by the candidate search of hr when the candidate opts in
+    #[serde(default)]
+    pub is_searchable: bool,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+impl Validate for Resume {
+    fn validate(&self) -> Result<(), Error> {
+        let mut validator = Validator::new();
+        if let Some(id) = &self.id {
+            validator.id("id", id.0);
+        }
+        if !self.url.is_empty() {
+            validator.url("url", &self.url);
+        }
+        self.profile.check(&mut validator);
+        validator
+            .id("user_id", self.user_id.0)
+            .email("email", &self.email)
+            .finish()
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, ToSchema)]
 pub struct ResumeId(pub i32);
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
 pub struct NewResume {
     pub user_id: UserId,
     pub email: String,
+    #[serde(default)]
     pub url: String,
+    #[serde(default)]
+    pub file: Option<ResumeFile>,
+    #[serde(default)]
+    pub profile: ResumeProfile,
+    // Listed by the candidate search of hr when the candidate opts in
+    #[serde(default)]
+    pub is_searchable: bool,
+}
+
+impl Validate for NewResume {
+    fn validate(&self) -> Result<(), Error> {
+        let mut validator = Validator::new();
+        if !self.url.is_empty() {
+            validator.url("url", &self.url);
+        }
+        self.profile.check(&mut validator);
+        validator
+            .id("user_id", self.user_id.0)
+            .email("email", &self.email)
+            .finish()
+    }
+}
+
+// Resume needs a link or a file, the file is only known once the upload is stored
+pub fn check_resume_source(url: &str, file: &Option<ResumeFile>) -> Result<(), Error> {
+    Validator::new()
+        .required("url", !url.is_empty() || file.is_some())
+        .finish()
+}
+
+// Metadata of the file uploaded with a resume, downloaded through
+// `/resume/download/{id}` by the owner and hr of the companies it applied to
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct ResumeFile {
+    // Key of the content in the blob store
+    pub key: String,
+    pub name: String,
+    pub content_type: String,
+    // Size in bytes
+    pub size: i64,
+    // Plain text of a pdf file, only used to search resumes
+    #[serde(skip)]
+    pub text: String,
+}
+
+// Structured content of a resume, filled by the candidate
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq, ToSchema)]
+#[serde(default)]
+pub struct ResumeProfile {
+    pub headline: String,
+    // City or region the candidate works from
+    pub location: String,
+    pub skills: Vec<String>,
+    pub experiences: Vec<Experience>,
+    pub educations: Vec<Education>,
+    pub languages: Vec<String>,
+    pub years_of_experience: i32,
+    // Monthly salary the candidate expects, in the currency of the job salaries
+    pub expected_salary: Option<i32>,
+}
+
+impl ResumeProfile {
+    // Add the broken rules of the profile to the validator of the resume
+    fn check(&self, validator: &mut Validator) {
+        validator
+            .length("profile.headline", &self.headline, 0, MAX_NAME_LENGTH)
+            .length("profile.location", &self.location, 0, MAX_NAME_LENGTH)
+            .range(
+                "profile.years_of_experience",
+                self.years_of_experience as i64,
+                0,
+                MAX_YEARS_OF_EXPERIENCE,
+            );
+        if let Some(expected_salary) = self.expected_salary {
+            validator.range(
+                "profile.expected_salary",
+                expected_salary as i64,
+                0,
+                i32::MAX as i64,
+            );
+        }
+        for (field, len) in [
+            ("profile.skills", self.skills.len()),
+            ("profile.experiences", self.experiences.len()),
+            ("profile.educations", self.educations.len()),
+            ("profile.languages", self.languages.len()),
+        ] {
+            validator.range(field, len as i64, 0, MAX_PROFILE_ITEMS as i64);
+        }
+        for (i, skill) in self.skills.iter().enumerate() {
+            validator.length(&format!("profile.skills[{}]", i), skill, 1, MAX_NAME_LENGTH);
+        }
+        for (i, language) in self.languages.iter().enumerate() {
+            validator.length(
+                &format!("profile.languages[{}]", i),
+                language,
+                1,
+                MAX_NAME_LENGTH,
+            );
+        }
+        for (i, experience) in self.experiences.iter().enumerate() {
+            experience.check(validator, &format!("profile.experiences[{}]", i));
+        }
+        for (i, education) in self.educations.iter().enumerate() {
+            education.check(validator, &format!("profile.educations[{}]", i));
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct Experience {
+    pub title: String,
+    pub company: String,
+    pub start_year: i32,
+    // Empty for the current position
+    #[serde(default)]
+    pub end_year: Option<i32>,
+    #[serde(default)]
+    pub description: String,
+}
+
+impl Experience {
+    fn check(&self, validator: &mut Validator, field: &str) {
+        validator
+            .length(&format!("{}.title", field), &self.title, 1, MAX_NAME_LENGTH)
+            .length(
+                &format!("{}.company", field),
+                &self.company,
+                1,
+                MAX_NAME_LENGTH,
+            )
+            .range(
+                &format!("{}.start_year", field),
+                self.start_year as i64,
+                MIN_YEAR,
+                MAX_YEAR,
+            )
+            .length(
+                &format!("{}.description", field),
+                &self.description,
+                0,
+                MAX_TEXT_LENGTH,
+            );
+        if let Some(end_year) = self.end_year {
+            validator.range(
+                &format!("{}.end_year", field),
+                end_year as i64,
+                self.start_year as i64,
+                MAX_YEAR,
+            );
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct Education {
+    pub school: String,
+    pub degree: String,
+    #[serde(default)]
+    pub field: String,
+    #[serde(default)]
+    pub graduation_year: Option<i32>,
+}
+
+impl Education {
+    fn check(&self, validator: &mut Validator, field: &str) {
+        validator
+            .length(
+                &format!("{}.school", field),
+                &self.school,
+                1,
+                MAX_NAME_LENGTH,
+            )
+            .length(
+                &format!("{}.degree", field),
+                &self.degree,
+                1,
+                MAX_NAME_LENGTH,
+            )
+            .length(&format!("{}.field", field), &self.field, 0, MAX_NAME_LENGTH);
+        if let Some(graduation_year) = self.graduation_year {
+            validator.range(
+                &format!("{}.graduation_year", field),
+                graduation_year as i64,
+                MIN_YEAR,
+                MAX_YEAR,
+            );
+        }
+    }
+}
+
+// Parts of the multipart form of the resume requests, only documents the form
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct ResumeForm {
+    // Json body of the request
+    pub resume: String,
+    // Pdf or docx file
+    #[schema(value_type = Option<String>, format = Binary)]
+    pub file: Option<Vec<u8>>,
+}
+
+// File part of a multipart resume request, checked before it is stored
+#[derive(Clone, PartialEq, Eq)]
+pub struct FileUpload {
+    pub file_name: String,
+    pub content_type: String,
+    pub content: Vec<u8>,
+}
+
+impl fmt::Debug for FileUpload {
+    // The content is never written into logs
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileUpload")
+            .field("file_name", &self.file_name)
+            .field("content_type", &self.content_type)
+            .field("size", &self.content.len())
+            .finish()
+    }
+}
+
+impl FileUpload {
+    // Content type and extension of the file, the content has to match the type
+    // so a renamed file of another kind is refused
+    pub fn resume_kind(&self) -> Result<(&'static str, &'static str), Error> {
+        let extension = self
+            .file_name
+            .rsplit_once('.')
+            .map(|(_, extension)| extension.to_lowercase())
+            .unwrap_or_default();
+        let kind = match (self.content_type.as_str(), extension.as_str()) {
+            (PDF_CONTENT_TYPE, _) | (_, "pdf") => (PDF_CONTENT_TYPE, "pdf"),
+            (DOCX_CONTENT_TYPE, _) | (_, "docx") => (DOCX_CONTENT_TYPE, "docx"),
+            _ => return Err(Error::UnsupportedFileType(self.content_type.clone())),
+        };
+        let magic: &[u8] = match kind.1 {
+            "pdf" => b"%PDF-",
+            // Docx is a zip archive
+            _ => b"PK\x03\x04",
+        };
+        match self.content.starts_with(magic) {
+            true => Ok(kind),
+            false => Err(Error::UnsupportedFileType(kind.0.to_string())),
+        }
+    }
+
+    // Name sent back in the Content-Disposition header of the download
+    pub fn safe_file_name(&self, extension: &str) -> String {
+        let name: String = self
+            .file_name
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or_default()
+            .chars()
+            // Header values are ascii
+            .filter(|c| (c.is_ascii_graphic() || *c == ' ') && *c != '"')
+            .take(255)
+            .collect();
+        match name.trim().is_empty() {
+            true => format!("resume.{}", extension),
+            false => name.trim().to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod resume_tests {
+    use super::{
+        Education, Experience, FileUpload, ResumeProfile, DOCX_CONTENT_TYPE, PDF_CONTENT_TYPE,
+    };
+    use crate::errors::Error;
+    use crate::models::validation::Validator;
+
+    fn upload(file_name: &str, content_type: &str, content: &[u8]) -> FileUpload {
+        FileUpload {
+            file_name: file_name.to_string(),
+            content_type: content_type.to_string(),
+            content: content.to_vec(),
+        }
+    }
+
+    #[test]
+    fn resume_kind_matches_content() {
+        assert_eq!(
+            upload("cv.pdf", PDF_CONTENT_TYPE, b"%PDF-1.7 ...")
+                .resume_kind()
+                .unwrap(),
+            (PDF_CONTENT_TYPE, "pdf")
+        );
+        // Clients sending no precise type are recognized by the extension
+        assert_eq!(
+            upload("cv.DOCX", "application/octet-stream", b"PK\x03\x04...")
+                .resume_kind()
+                .unwrap(),
+            (DOCX_CONTENT_TYPE, "docx")
+        );
+        assert!(upload("cv.pdf", PDF_CONTENT_TYPE, b"MZ\x90\x00")
+            .resume_kind()
+            .is_err());
+        assert!(upload("cv.png", "image/png", b"\x89PNG")
+            .resume_kind()
+            .is_err());
+    }
+
+    #[test]
+    fn file_name_is_safe_for_headers() {
+        assert_eq!(
+            upload("C:\\Users\\me\\my \"cv\".pdf", PDF_CONTENT_TYPE, b"").safe_file_name("pdf"),
+            "my cv.pdf"
+        );
+        assert_eq!(
+            upload("../\n", PDF_CONTENT_TYPE, b"").safe_file_name("pdf"),
+            "resume.pdf"
+        );
+        assert_eq!(
+            upload("Lý lịch.pdf", PDF_CONTENT_TYPE, b"").safe_file_name("pdf"),
+            "L lch.pdf"
+        );
+    }
+
+    #[test]
+    fn profile_entries_are_validated() {
+        let profile = ResumeProfile {
+            headline: "Rust developer".to_string(),
+            location: "Ha Noi".to_string(),
+            skills: vec!["Rust".to_string(), " ".to_string()],
+            experiences: vec![Experience {
+                title: "Backend developer".to_string(),
+                company: "".to_string(),
+                start_year: 2021,
+                end_year: Some(2020),
+                description: "".to_string(),
+            }],
+            educations: vec![Education {
+                school: "Hanoi University of Science and Technology".to_string(),
+                degree: "Bachelor".to_string(),
+                field: "".to_string(),
+                graduation_year: Some(1900),
+            }],
+            languages: vec!["English".to_string()],
+            years_of_experience: 3,
+            expected_salary: None,
+        };
+        let mut validator = Validator::new();
+        profile.check(&mut validator);
+        let fields: Vec<String> = match validator.finish() {
+            Err(Error::Validation(details)) => {
+                details.into_iter().map(|detail| detail.field).collect()
+            }
+            _ => Vec::new(),
+        };
+        assert_eq!(
+            fields,
+            vec![
+                "profile.skills[1]",
+                "profile.experiences[0].company",
+                "profile.experiences[0].end_year",
+                "profile.educations[0].graduation_year",
+            ]
+        );
+
+        let mut validator = Validator::new();
+        ResumeProfile::default().check(&mut validator);
+        assert!(validator.finish().is_ok());
+    }
 }
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use crate::errors::Error;
+use crate::models::job_search::non_empty;
+use crate::models::pagination::{Page, Pagination};
+use crate::models::resume::{Resume, MAX_YEARS_OF_EXPERIENCE};
+
+/// Weight of the keyword relevance in the rank, the years of experience
+/// (at most `MAX_YEARS_OF_EXPERIENCE`) break ties between equally relevant resumes
+pub const RELEVANCE_WEIGHT: i64 = 100;
+
+/// Candidate search of hr, extracted from query params.
+/// Only resumes the candidates made searchable are listed
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ResumeSearch {
+    /// Free text matched against the headline and the text of the uploaded file
+    pub q: Option<String>,
+    /// Every skill has to be in the resume, case insensitive
+    pub skills: Vec<String>,
+    /// Part of the candidate location, case insensitive
+    pub location: Option<String>,
+    /// Inclusive range of years of experience
+    pub min_experience: Option<i32>,
+    pub max_experience: Option<i32>,
+    /// Limit with offset or cursor, the cursor key holds the rank
+    pub pagination: Pagination,
+}
+
+/// Page of resumes ordered by rank with the total count of matching resumes
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResumeSearchResult {
+    pub resumes: Page<Resume>,
+    pub total: i64,
+}
+
+/// Extract query parameters from the `/resume/search` route
+/// # Example query
+/// `/resume/search?q=backend developer&skills=rust,postgresql&location=ha noi
+/// &minExperience=2&maxExperience=5&limit=10&offset=0`
+///
+/// Every parameter is optional, `limit` has to be sent with `offset` or `cursor`.
+impl ResumeSearch {
+    pub fn extract_resume_search(params: HashMap<String, String>) -> Result<ResumeSearch, Error> {
+        let mut search = ResumeSearch::default();
+        if params.contains_key("limit")
+            || params.contains_key("offset")
+            || params.contains_key("cursor")
+        {
+            search.pagination = Pagination::extract_pagination(params.clone())?;
+        }
+        search.q = non_empty(params.get("q"));
+        search.location = non_empty(params.get("location"));
+        if let Some(skills) = params.get("skills") {
+            search.skills = skills
+                .split(',')
+                .map(|skill| skill.trim().to_lowercase())
+                .filter(|skill| !skill.is_empty())
+                .collect();
+        }
+        if let Some(min_experience) = params.get("minExperience") {
+            search.min_experience = Some(experience(min_experience, "minExperience")?);
+        }
+        if let Some(max_experience) = params.get("maxExperience") {
+            search.max_experience = Some(experience(max_experience, "maxExperience")?);
+        }
+        Ok(search)
+    }
+}
+
+fn experience(value: &str, name: &str) -> Result<i32, Error> {
+    let years: i32 = value.parse().map_err(Error::Parse)?;
+    match (0..=MAX_YEARS_OF_EXPERIENCE).contains(&(years as i64)) {
+        true => Ok(years),
+        false => Err(Error::InvalidParameter(format!("{}={}", name, value))),
+    }
+}
+
+/// Rank of a resume, higher is listed first
+pub fn rank(relevance: i64, years_of_experience: i32) -> i64 {
+    relevance * RELEVANCE_WEIGHT + years_of_experience as i64
+}
+
+#[cfg(test)]
+mod resume_search_tests {
+    use super::{rank, Error, HashMap, ResumeSearch};
+
+    #[test]
+    fn valid_resume_search() {
+        let mut params = HashMap::new();
+        params.insert(String::from("q"), String::from(" backend "));
+        params.insert(String::from("skills"), String::from("Rust, PostgreSQL,,"));
+        params.insert(String::from("location"), String::from("Ha Noi"));
+        params.insert(String::from("minExperience"), String::from("2"));
+        let search = ResumeSearch::extract_resume_search(params).unwrap();
+        assert_eq!(search.q.as_deref(), Some("backend"));
+        assert_eq!(search.skills, vec!["rust", "postgresql"]);
+        assert_eq!(search.location.as_deref(), Some("Ha Noi"));
+        assert_eq!(
+            (search.min_experience, search.max_experience),
+            (Some(2), None)
+        );
+        assert_eq!(search.pagination.limit, None);
+    }
+
+    #[test]
+    fn experience_out_of_range() {
+        let mut params = HashMap::new();
+        params.insert(String::from("maxExperience"), String::from("-1"));
+        let search_result = format!(
+            "{}",
+            ResumeSearch::extract_resume_search(params).unwrap_err()
+        );
+        assert_eq!(search_result, "Invalid parameter: maxExperience=-1");
+
+        let mut params = HashMap::new();
+        params.insert(String::from("minExperience"), String::from("two"));
+        assert!(matches!(
+            ResumeSearch::extract_resume_search(params),
+            Err(Error::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn relevance_outweighs_experience() {
+        assert!(rank(2, 0) > rank(1, 70));
+        assert!(rank(1, 5) > rank(1, 4));
+    }
+}
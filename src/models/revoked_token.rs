@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::errors::Error;
+use crate::models::user::UserId;
+use crate::models::validation::{Validate, Validator, MAX_TOKEN_LENGTH};
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RevokedToken {
+    pub jti: String,
+    pub user_id: UserId,
+    // Entry can be dropped once the access token has expired
+    pub expires_at: i64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct LogoutRequest {
+    pub refresh_token: Option<String>,
+}
+
+impl Validate for LogoutRequest {
+    fn validate(&self) -> Result<(), Error> {
+        let mut validator = Validator::new();
+        if let Some(refresh_token) = &self.refresh_token {
+            validator.length("refresh_token", refresh_token, 1, MAX_TOKEN_LENGTH);
+        }
+        validator.finish()
+    }
+}
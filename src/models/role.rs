@@ -1,20 +1,125 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::errors::Error;
+use crate::models::permission::Permission;
+use crate::models::user::UserId;
+use crate::models::validation::{Validate, Validator, MAX_NAME_LENGTH};
 
 pub const ADMIN_ROLE_ID: i32 = 1;
 pub const USER_ROLE_ID: i32 = 2;
 pub const HR_ROLE_ID: i32 = 3;
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+// Upper bound when walking the role hierarchy, guards against cycles
+pub const MAX_ROLE_DEPTH: i32 = 16;
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
 pub struct Role {
     pub id: Option<RoleId>,
     pub role: String,
+    // Role whose permissions are granted to this role as well
+    pub inherits_role_id: Option<RoleId>,
     pub is_delete: bool,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+impl Validate for Role {
+    fn validate(&self) -> Result<(), Error> {
+        let mut validator = Validator::new();
+        if let Some(id) = &self.id {
+            validator.id("id", id.0);
+        }
+        if let Some(inherits_role_id) = &self.inherits_role_id {
+            validator.id("inherits_role_id", inherits_role_id.0);
+        }
+        validator
+            .length("role", &self.role, 1, MAX_NAME_LENGTH)
+            .finish()
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, ToSchema)]
 pub struct RoleId(pub i32);
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, ToSchema)]
 pub struct RoleInfo {
     pub role: String,
+    pub inherits_role_id: Option<RoleId>,
     pub is_delete: bool,
 }
+
+impl Validate for RoleInfo {
+    fn validate(&self) -> Result<(), Error> {
+        let mut validator = Validator::new();
+        if let Some(inherits_role_id) = &self.inherits_role_id {
+            validator.id("inherits_role_id", inherits_role_id.0);
+        }
+        validator
+            .length("role", &self.role, 1, MAX_NAME_LENGTH)
+            .finish()
+    }
+}
+
+// Default roles and their own permissions, following the role_permissions migration
+// admin inherits hr and hr inherits user
+pub fn default_roles() -> Vec<(Role, Vec<Permission>)> {
+    vec![
+        (
+            Role {
+                id: Some(RoleId(ADMIN_ROLE_ID)),
+                role: "admin".to_string(),
+                inherits_role_id: Some(RoleId(HR_ROLE_ID)),
+                is_delete: false,
+            },
+            vec![Permission::ManageCompany, Permission::AssignRole],
+        ),
+        (
+            Role {
+                id: Some(RoleId(USER_ROLE_ID)),
+                role: "user".to_string(),
+                inherits_role_id: None,
+                is_delete: false,
+            },
+            vec![
+                Permission::ManageAccount,
+                Permission::ManageResume,
+                Permission::ApplyJob,
+            ],
+        ),
+        (
+            Role {
+                id: Some(RoleId(HR_ROLE_ID)),
+                role: "hr".to_string(),
+                inherits_role_id: Some(RoleId(USER_ROLE_ID)),
+                is_delete: false,
+            },
+            vec![Permission::ManageJob],
+        ),
+    ]
+}
+
+// Own permissions of a role, inherited permissions are not included
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct RolePermissions {
+    pub role_id: RoleId,
+    pub permissions: Vec<Permission>,
+}
+
+impl Validate for RolePermissions {
+    fn validate(&self) -> Result<(), Error> {
+        Validator::new().id("role_id", self.role_id.0).finish()
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct AssignRole {
+    pub user_id: UserId,
+    pub role_id: RoleId,
+}
+
+impl Validate for AssignRole {
+    fn validate(&self) -> Result<(), Error> {
+        let mut validator = Validator::new();
+        validator
+            .id("user_id", self.user_id.0)
+            .id("role_id", self.role_id.0)
+            .finish()
+    }
+}
@@ -1,41 +1,57 @@
 use std::fs;
 
 use async_trait::async_trait;
+use chrono::Utc;
 use sqlx::{
-    postgres::{PgPool, PgPoolOptions, PgRow},
-    Row,
+    postgres::{PgPool, PgPoolOptions, PgRow, Postgres},
+    types::Json,
+    Row, Transaction,
 };
 use tracing::{event, Level};
 
 use crate::errors::Error;
+use crate::models::application::{
+    ApplicationStatus, ApplicationStatusChange, ApplicationStatusChangeId,
+    NewApplicationStatusChange,
+};
+use crate::models::audit::{
+    AuditAction, AuditEntity, AuditEntry, AuditEntryId, AuditFilter, NewAuditEntry,
+};
 use crate::models::company::{Company, CompanyId, NewCompany};
-use crate::models::job::{Job, JobId, NewJob};
+use crate::models::email_token::{EmailToken, EmailTokenId, EmailTokenPurpose, NewEmailToken};
+use crate::models::health::PoolStatus;
+use crate::models::job::{Job, JobId, JobStatus, NewJob};
+use crate::models::job_search::{JobSearch, JobSearchResult, JobSortField, SortOrder};
+use crate::models::login_attempt::{LoginAttempt, LoginPenalty};
 use crate::models::map_resume_job::{MapResumeJob, MapResumeJobId, NewMapResumeJob};
-use crate::models::resume::{NewResume, Resume, ResumeId};
-use crate::models::role::{Role, RoleId, RoleInfo, USER_ROLE_ID};
+use crate::models::pagination::{Cursor, Page, Pagination};
+use crate::models::permission::Permission;
+use crate::models::refresh_token::{NewRefreshToken, RefreshToken, RefreshTokenId};
+use crate::models::resume::{
+    Education, Experience, NewResume, Resume, ResumeFile, ResumeId, ResumeProfile,
+};
+use crate::models::resume_search::{ResumeSearch, ResumeSearchResult, RELEVANCE_WEIGHT};
+use crate::models::role::{Role, RoleId, RoleInfo, RolePermissions, MAX_ROLE_DEPTH, USER_ROLE_ID};
 use crate::models::store_trait::StoreMethods;
 use crate::models::user::{AuthInfo, User, UserId, UserInfo};
 
+const MAX_CONNECTIONS: u32 = 100;
+
 #[derive(Debug, Clone)]
 pub struct DatabaseStore {
     pub connection: PgPool,
 }
 impl DatabaseStore {
-    pub async fn new(db_url: &str) -> Self {
-        let db_pool = match PgPoolOptions::new()
-            .max_connections(100)
+    // The url holds the password, it is left out of the error
+    pub async fn new(db_url: &str) -> Result<Self, Error> {
+        let db_pool = PgPoolOptions::new()
+            .max_connections(MAX_CONNECTIONS)
             .connect(db_url)
             .await
-        {
-            Ok(pool) => pool,
-            Err(e) => panic!(
-                "Couldn't establish DB connection: {} with url {}",
-                e, db_url
-            ),
-        };
-        DatabaseStore {
+            .map_err(Error::DatabaseConnection)?;
+        Ok(DatabaseStore {
             connection: db_pool,
-        }
+        })
     }
 
     pub async fn create_sample_data(&self, path: &str) -> Result<(), Error> {
@@ -57,26 +73,123 @@ impl DatabaseStore {
 
 #[async_trait]
 impl StoreMethods for DatabaseStore {
+    async fn ping(&self) -> Result<(), Error> {
+        match sqlx::query("SELECT 1").execute(&self.connection).await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                event!(Level::ERROR, "Ping database has error: {:?}", e);
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn pending_migrations(&self) -> Result<Vec<i64>, Error> {
+        match sqlx::query("SELECT version FROM _sqlx_migrations WHERE success = true")
+            .map(|row: PgRow| row.get::<i64, _>("version"))
+            .fetch_all(&self.connection)
+            .await
+        {
+            Ok(applied) => Ok(sqlx::migrate!()
+                .iter()
+                .filter(|migration| !migration.migration_type.is_down_migration())
+                .map(|migration| migration.version)
+                .filter(|version| !applied.contains(version))
+                .collect()),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Get applied migrations from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    fn pool_status(&self) -> Option<PoolStatus> {
+        Some(PoolStatus {
+            size: self.connection.size(),
+            idle: self.connection.num_idle() as u32,
+            max: MAX_CONNECTIONS,
+        })
+    }
+
+    async fn close(&self) {
+        self.connection.close().await
+    }
+
     async fn create_map_job_resume(
         &self,
         new_map_resume_job: NewMapResumeJob,
+        applied_by: UserId,
     ) -> Result<MapResumeJob, Error> {
-        match sqlx::query(
-            "INSERT INTO map_resume_job (resume_id, job_id) \
-                            VALUES ($1, $2)\
-                            RETURNING id, resume_id, job_id",
+        let mut tx = self
+            .connection
+            .begin()
+            .await
+            .map_err(Error::DatabaseQuery)?;
+        // The job row is locked so concurrent applications to the job are checked one by one,
+        // a second application of the user is rejected by the unique index of applied_by
+        let hired: Option<bool> = match sqlx::query(
+            "SELECT j.quantity <= (SELECT COUNT(*) FROM map_resume_job m \
+                WHERE m.job_id = j.id AND m.status = $2) AS filled \
+                FROM jobs j WHERE j.id = $1 FOR UPDATE",
+        )
+        .bind(new_map_resume_job.job_id.0)
+        .bind(ApplicationStatus::Hired.as_str())
+        .map(|row: PgRow| row.get("filled"))
+        .fetch_optional(&mut tx)
+        .await
+        {
+            Ok(filled) => filled,
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Lock job of map_job_resume from database has error: {:?}",
+                    e
+                );
+                return Err(Error::DatabaseQuery(e));
+            }
+        };
+        if hired == Some(true) {
+            return Err(Error::JobQuantityExceeded);
+        }
+        let map_resume_job = match sqlx::query(
+            "INSERT INTO map_resume_job (resume_id, job_id, status, applied_by) \
+                            VALUES ($1, $2, $3, $4)\
+                            RETURNING id, resume_id, job_id, status",
         )
         .bind(new_map_resume_job.resume_id.0)
         .bind(new_map_resume_job.job_id.0)
-        .map(|row: PgRow| MapResumeJob {
-            id: Some(MapResumeJobId(row.get("id"))),
-            resume_id: ResumeId(row.get("resume_id")),
-            job_id: JobId(row.get("job_id")),
-        })
-        .fetch_one(&self.connection)
+        .bind(ApplicationStatus::Applied.as_str())
+        .bind(applied_by.0)
+        .map(|row: PgRow| row_map_resume_job(&row))
+        .fetch_one(&mut tx)
         .await
         {
-            Ok(user) => Ok(user),
+            Ok(map_resume_job) => map_resume_job,
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Create map_job_resume from database has error: {:?}",
+                    e
+                );
+                return Err(Error::DatabaseQuery(e));
+            }
+        };
+        insert_status_change(
+            &mut tx,
+            NewApplicationStatusChange {
+                application_id: map_resume_job.id.clone().unwrap(),
+                from_status: None,
+                to_status: ApplicationStatus::Applied,
+                changed_by: applied_by,
+                note: None,
+            },
+        )
+        .await?;
+        match tx.commit().await {
+            Ok(_) => Ok(map_resume_job),
             Err(e) => {
                 event!(
                     Level::ERROR,
@@ -87,17 +200,195 @@ impl StoreMethods for DatabaseStore {
             }
         }
     }
+    async fn has_applied_job(&self, user_id: UserId, job_id: JobId) -> Result<bool, Error> {
+        match sqlx::query(
+            "SELECT EXISTS (SELECT 1 FROM map_resume_job m \
+                JOIN resumes r ON r.id = m.resume_id \
+                WHERE r.user_id = $1 AND m.job_id = $2) AS applied",
+        )
+        .bind(user_id.0)
+        .bind(job_id.0)
+        .map(|row: PgRow| row.get("applied"))
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(applied) => Ok(applied),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Check applied job from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+    async fn count_hired_applications(&self, job_id: JobId) -> Result<i64, Error> {
+        match sqlx::query(
+            "SELECT COUNT(*) AS total FROM map_resume_job WHERE job_id = $1 AND status = $2",
+        )
+        .bind(job_id.0)
+        .bind(ApplicationStatus::Hired.as_str())
+        .map(|row: PgRow| row.get("total"))
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(total) => Ok(total),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Count hired applications from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+    async fn get_map_resume_job_by_id(
+        &self,
+        map_resume_job_id: MapResumeJobId,
+    ) -> Result<MapResumeJob, Error> {
+        match sqlx::query("SELECT * FROM map_resume_job WHERE id = $1")
+            .bind(map_resume_job_id.0)
+            .map(|row: PgRow| row_map_resume_job(&row))
+            .fetch_one(&self.connection)
+            .await
+        {
+            Ok(map_resume_job) => Ok(map_resume_job),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Get map_job_resume from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+    async fn update_application_status(
+        &self,
+        change: NewApplicationStatusChange,
+    ) -> Result<MapResumeJob, Error> {
+        let from_status = change.from_status.unwrap_or_default();
+        let mut tx = self
+            .connection
+            .begin()
+            .await
+            .map_err(Error::DatabaseQuery)?;
+        // The job row is locked so concurrent hires are counted one by one
+        if change.to_status == ApplicationStatus::Hired {
+            let filled: Option<bool> = match sqlx::query(
+                "SELECT j.quantity <= (SELECT COUNT(*) FROM map_resume_job m \
+                    WHERE m.job_id = j.id AND m.status = $2) AS filled \
+                    FROM jobs j JOIN map_resume_job a ON a.job_id = j.id \
+                    WHERE a.id = $1 FOR UPDATE OF j",
+            )
+            .bind(change.application_id.0)
+            .bind(ApplicationStatus::Hired.as_str())
+            .map(|row: PgRow| row.get("filled"))
+            .fetch_optional(&mut tx)
+            .await
+            {
+                Ok(filled) => filled,
+                Err(e) => {
+                    event!(
+                        Level::ERROR,
+                        "Lock job of application from database has error: {:?}",
+                        e
+                    );
+                    return Err(Error::DatabaseQuery(e));
+                }
+            };
+            if filled == Some(true) {
+                return Err(Error::JobFilled);
+            }
+        }
+        // Only the expected status is updated, so concurrent changes can't skip a step
+        let map_resume_job = match sqlx::query(
+            "UPDATE map_resume_job SET status = $1, updated_on = NOW() \
+                            WHERE id = $2 AND status = $3 \
+                            RETURNING id, resume_id, job_id, status",
+        )
+        .bind(change.to_status.as_str())
+        .bind(change.application_id.0)
+        .bind(from_status.as_str())
+        .map(|row: PgRow| row_map_resume_job(&row))
+        .fetch_optional(&mut tx)
+        .await
+        {
+            Ok(Some(map_resume_job)) => map_resume_job,
+            Ok(None) => {
+                return Err(Error::InvalidStatusTransition(
+                    from_status.to_string(),
+                    change.to_status.to_string(),
+                ))
+            }
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Update application status from database has error: {:?}",
+                    e
+                );
+                return Err(Error::DatabaseQuery(e));
+            }
+        };
+        insert_status_change(&mut tx, change).await?;
+        match tx.commit().await {
+            Ok(_) => Ok(map_resume_job),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Update application status from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+    async fn get_application_timeline(
+        &self,
+        map_resume_job_id: MapResumeJobId,
+    ) -> Result<Vec<ApplicationStatusChange>, Error> {
+        match sqlx::query(
+            "SELECT * FROM application_status_history \
+                WHERE application_id = $1 ORDER BY changed_at, id",
+        )
+        .bind(map_resume_job_id.0)
+        .map(|row: PgRow| ApplicationStatusChange {
+            id: Some(ApplicationStatusChangeId(row.get("id"))),
+            application_id: MapResumeJobId(row.get("application_id")),
+            from_status: row
+                .get::<Option<String>, _>("from_status")
+                .and_then(|status| status.parse().ok()),
+            to_status: row
+                .get::<String, _>("to_status")
+                .parse()
+                .unwrap_or_default(),
+            changed_by: UserId(row.get("changed_by")),
+            note: row.get("note"),
+            changed_at: row.get("changed_at"),
+        })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(timeline) => Ok(timeline),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Get application timeline from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
     async fn get_list_job_by_resume(
         &self,
         resume_id: ResumeId,
     ) -> Result<Vec<MapResumeJob>, Error> {
         match sqlx::query("SELECT * FROM map_resume_job where resume_id = $1")
             .bind(resume_id.0)
-            .map(|row: PgRow| MapResumeJob {
-                id: Some(MapResumeJobId(row.get("id"))),
-                resume_id: ResumeId(row.get("resume_id")),
-                job_id: JobId(row.get("job_id")),
-            })
+            .map(|row: PgRow| row_map_resume_job(&row))
             .fetch_all(&self.connection)
             .await
         {
@@ -114,23 +405,30 @@ impl StoreMethods for DatabaseStore {
     }
     async fn get_list_resume_by_job_id(
         &self,
-        limit: Option<i32>,
-        offset: i32,
+        pagination: Pagination,
         job_id: JobId,
-    ) -> Result<Vec<MapResumeJob>, Error> {
-        match sqlx::query("SELECT * FROM map_resume_job where job_id = $1 LIMIT $2 OFFSET $3 ")
-            .bind(job_id.0)
-            .bind(limit)
-            .bind(offset)
-            .map(|row: PgRow| MapResumeJob {
-                id: Some(MapResumeJobId(row.get("id"))),
-                resume_id: ResumeId(row.get("resume_id")),
-                job_id: JobId(row.get("job_id")),
-            })
-            .fetch_all(&self.connection)
-            .await
+    ) -> Result<Page<MapResumeJob>, Error> {
+        match sqlx::query(&format!(
+            "SELECT *, (EXTRACT(EPOCH FROM created_on) * 1000000)::BIGINT AS cursor_key FROM map_resume_job \
+                WHERE job_id = $1 AND {} \
+                ORDER BY created_on, id LIMIT $4 OFFSET $5",
+            created_on_after("$2", "$3")
+        ))
+        .bind(job_id.0)
+        .bind(pagination.cursor.as_ref().map(|cursor| cursor.key))
+        .bind(pagination.cursor.as_ref().map(|cursor| cursor.id))
+        .bind(pagination.limit.map(|limit| limit.saturating_add(1)))
+        .bind(pagination.offset)
+        .map(|row: PgRow| {
+            (
+                row_map_resume_job(&row),
+                row_cursor(&row),
+            )
+        })
+        .fetch_all(&self.connection)
+        .await
         {
-            Ok(list_map) => Ok(list_map),
+            Ok(rows) => Ok(Page::from_rows(rows, pagination.limit)),
             Err(e) => {
                 event!(
                     Level::ERROR,
@@ -146,8 +444,8 @@ impl StoreMethods for DatabaseStore {
     async fn create_user(&self, new_user: AuthInfo) -> Result<User, Error> {
         match sqlx::query(
             "INSERT INTO users (email, hash_password, company_id, role_id, is_delete) \
-                            VALUES ($1, $2, $3, $4, $5) \
-                            RETURNING id, email, hash_password, company_id, role_id, is_delete",
+                            VALUES ($1, $2, NULLIF($3, 0), $4, $5) \
+                            RETURNING id, email, hash_password, company_id, role_id, is_delete, email_verified",
         )
         .bind(new_user.email)
         .bind(new_user.hash_password)
@@ -158,9 +456,10 @@ impl StoreMethods for DatabaseStore {
             id: Some(UserId(row.get("id"))),
             email: row.get("email"),
             hash_password: row.get("hash_password"),
-            company_id: CompanyId(row.get("company_id")),
+            company_id: row_company_id(&row),
             role_id: RoleId(row.get("role_id")),
             is_delete: row.get("is_delete"),
+            email_verified: row.get("email_verified"),
         })
         .fetch_one(&self.connection)
         .await
@@ -179,9 +478,10 @@ impl StoreMethods for DatabaseStore {
                 id: Some(UserId(row.get("id"))),
                 email: row.get("email"),
                 hash_password: row.get("hash_password"),
-                company_id: CompanyId(row.get("company_id")),
+                company_id: row_company_id(&row),
                 role_id: RoleId(row.get("role_id")),
                 is_delete: row.get("is_delete"),
+                email_verified: row.get("email_verified"),
             })
             .fetch_one(&self.connection)
             .await
@@ -205,9 +505,10 @@ impl StoreMethods for DatabaseStore {
                 id: Some(UserId(row.get("id"))),
                 email: row.get("email"),
                 hash_password: row.get("hash_password"),
-                company_id: CompanyId(row.get("company_id")),
+                company_id: row_company_id(&row),
                 role_id: RoleId(row.get("role_id")),
                 is_delete: row.get("is_delete"),
+                email_verified: row.get("email_verified"),
             })
             .fetch_one(&self.connection)
             .await
@@ -224,22 +525,35 @@ impl StoreMethods for DatabaseStore {
         }
     }
 
-    async fn get_list_user(&self, limit: Option<i32>, offset: i32) -> Result<Vec<User>, Error> {
-        match sqlx::query("SELECT * FROM USERS LIMIT $1 OFFSET $2")
-            .bind(limit)
-            .bind(offset)
-            .map(|row: PgRow| User {
-                id: Some(UserId(row.get("id"))),
-                email: row.get("email"),
-                hash_password: row.get("hash_password"),
-                company_id: CompanyId(row.get("company_id")),
-                role_id: RoleId(row.get("role_id")),
-                is_delete: row.get("is_delete"),
-            })
-            .fetch_all(&self.connection)
-            .await
+    async fn get_list_user(&self, pagination: Pagination) -> Result<Page<User>, Error> {
+        match sqlx::query(&format!(
+            "SELECT *, (EXTRACT(EPOCH FROM created_on) * 1000000)::BIGINT AS cursor_key FROM users \
+                WHERE {} \
+                ORDER BY created_on, id LIMIT $3 OFFSET $4",
+            created_on_after("$1", "$2")
+        ))
+        .bind(pagination.cursor.as_ref().map(|cursor| cursor.key))
+        .bind(pagination.cursor.as_ref().map(|cursor| cursor.id))
+        .bind(pagination.limit.map(|limit| limit.saturating_add(1)))
+        .bind(pagination.offset)
+        .map(|row: PgRow| {
+            (
+                User {
+                    id: Some(UserId(row.get("id"))),
+                    email: row.get("email"),
+                    hash_password: row.get("hash_password"),
+                    company_id: row_company_id(&row),
+                    role_id: RoleId(row.get("role_id")),
+                    is_delete: row.get("is_delete"),
+                    email_verified: row.get("email_verified"),
+                },
+                row_cursor(&row),
+            )
+        })
+        .fetch_all(&self.connection)
+        .await
         {
-            Ok(users) => Ok(users),
+            Ok(rows) => Ok(Page::from_rows(rows, pagination.limit)),
             Err(e) => {
                 event!(
                     Level::ERROR,
@@ -253,19 +567,20 @@ impl StoreMethods for DatabaseStore {
 
     async fn update_user(&self, user_info: UserInfo) -> Result<User, Error> {
         match sqlx::query(
-            "Update users SET company_id = $1 \
-                where email = $2 \
-                RETURNING id, email, hash_password, company_id, role_id, is_delete",
+            "Update users SET company_id = NULLIF($1, 0) \
+                where id = $2 \
+                RETURNING id, email, hash_password, company_id, role_id, is_delete, email_verified",
         )
         .bind(user_info.company_id.0)
-        .bind(user_info.email)
+        .bind(user_info.id.0)
         .map(|row: PgRow| User {
             id: Some(UserId(row.get("id"))),
             email: row.get("email"),
             hash_password: row.get("hash_password"),
-            company_id: CompanyId(row.get("company_id")),
+            company_id: row_company_id(&row),
             role_id: RoleId(row.get("role_id")),
             is_delete: row.get("is_delete"),
+            email_verified: row.get("email_verified"),
         })
         .fetch_one(&self.connection)
         .await
@@ -282,7 +597,7 @@ impl StoreMethods for DatabaseStore {
         match sqlx::query(
             "Update users SET hash_password = $1 \
                 where email = $2 \
-                RETURNING id, email, hash_password, company_id, role_id, is_delete",
+                RETURNING id, email, hash_password, company_id, role_id, is_delete, email_verified",
         )
         .bind(user.hash_password)
         .bind(user.email)
@@ -290,9 +605,10 @@ impl StoreMethods for DatabaseStore {
             id: Some(UserId(row.get("id"))),
             email: row.get("email"),
             hash_password: row.get("hash_password"),
-            company_id: CompanyId(row.get("company_id")),
+            company_id: row_company_id(&row),
             role_id: RoleId(row.get("role_id")),
             is_delete: row.get("is_delete"),
+            email_verified: row.get("email_verified"),
         })
         .fetch_one(&self.connection)
         .await
@@ -313,7 +629,7 @@ impl StoreMethods for DatabaseStore {
         match sqlx::query(
             "Update users SET role_id = $1 \
                 where id = $2 \
-                RETURNING id, email, hash_password, company_id, role_id, is_delete",
+                RETURNING id, email, hash_password, company_id, role_id, is_delete, email_verified",
         )
         .bind(role_id.0)
         .bind(user.id.0)
@@ -321,9 +637,10 @@ impl StoreMethods for DatabaseStore {
             id: Some(UserId(row.get("id"))),
             email: row.get("email"),
             hash_password: row.get("hash_password"),
-            company_id: CompanyId(row.get("company_id")),
+            company_id: row_company_id(&row),
             role_id: RoleId(row.get("role_id")),
             is_delete: row.get("is_delete"),
+            email_verified: row.get("email_verified"),
         })
         .fetch_one(&self.connection)
         .await
@@ -340,6 +657,38 @@ impl StoreMethods for DatabaseStore {
         }
     }
 
+    async fn set_email_verified(&self, user_id: UserId) -> Result<User, Error> {
+        match sqlx::query(
+            "Update users SET email_verified = $1 \
+                where id = $2 \
+                RETURNING id, email, hash_password, company_id, role_id, is_delete, email_verified",
+        )
+        .bind(true)
+        .bind(user_id.0)
+        .map(|row: PgRow| User {
+            id: Some(UserId(row.get("id"))),
+            email: row.get("email"),
+            hash_password: row.get("hash_password"),
+            company_id: row_company_id(&row),
+            role_id: RoleId(row.get("role_id")),
+            is_delete: row.get("is_delete"),
+            email_verified: row.get("email_verified"),
+        })
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(user) => Ok(user),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Set email verified for user from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
     async fn delete_user_by_id(&self, user_id: UserId) -> Result<bool, Error> {
         match sqlx::query("Update users set is_delete = $1 where id = $2")
             .bind(true)
@@ -347,11 +696,573 @@ impl StoreMethods for DatabaseStore {
             .execute(&self.connection)
             .await
         {
-            Ok(_) => Ok(true),
+            Ok(_) => Ok(true),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Delete user by id from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn create_refresh_token(
+        &self,
+        new_refresh_token: NewRefreshToken,
+    ) -> Result<RefreshToken, Error> {
+        match sqlx::query(
+            "INSERT INTO refresh_tokens (user_id, token_hash, family_id, expires_at, is_used, is_revoked) \
+                            VALUES ($1, $2, $3, $4, $5, $6) \
+                            RETURNING id, user_id, token_hash, family_id, expires_at, is_used, is_revoked",
+        )
+        .bind(new_refresh_token.user_id.0)
+        .bind(new_refresh_token.token_hash)
+        .bind(new_refresh_token.family_id)
+        .bind(new_refresh_token.expires_at)
+        .bind(false)
+        .bind(false)
+        .map(|row: PgRow| RefreshToken {
+            id: Some(RefreshTokenId(row.get("id"))),
+            user_id: UserId(row.get("user_id")),
+            token_hash: row.get("token_hash"),
+            family_id: row.get("family_id"),
+            expires_at: row.get("expires_at"),
+            is_used: row.get("is_used"),
+            is_revoked: row.get("is_revoked"),
+        })
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(refresh_token) => Ok(refresh_token),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Create refresh token for database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn get_refresh_token(&self, token_hash: &str) -> Result<RefreshToken, Error> {
+        match sqlx::query("SELECT * FROM refresh_tokens WHERE token_hash = $1")
+            .bind(token_hash)
+            .map(|row: PgRow| RefreshToken {
+                id: Some(RefreshTokenId(row.get("id"))),
+                user_id: UserId(row.get("user_id")),
+                token_hash: row.get("token_hash"),
+                family_id: row.get("family_id"),
+                expires_at: row.get("expires_at"),
+                is_used: row.get("is_used"),
+                is_revoked: row.get("is_revoked"),
+            })
+            .fetch_one(&self.connection)
+            .await
+        {
+            Ok(refresh_token) => Ok(refresh_token),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Get refresh token from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn mark_refresh_token_used(&self, token_id: RefreshTokenId) -> Result<bool, Error> {
+        match sqlx::query(
+            "Update refresh_tokens set is_used = $1, updated_on = NOW() \
+                where id = $2 and is_used = $3",
+        )
+        .bind(true)
+        .bind(token_id.0)
+        .bind(false)
+        .execute(&self.connection)
+        .await
+        {
+            // Another request already rotated this token when no row was updated
+            Ok(result) => Ok(result.rows_affected() == 1),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Mark refresh token used from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn revoke_refresh_token_family(&self, family_id: &str) -> Result<bool, Error> {
+        match sqlx::query(
+            "Update refresh_tokens set is_revoked = $1, updated_on = NOW() where family_id = $2",
+        )
+        .bind(true)
+        .bind(family_id)
+        .execute(&self.connection)
+        .await
+        {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Revoke refresh token family from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn create_email_token(
+        &self,
+        new_email_token: NewEmailToken,
+    ) -> Result<EmailToken, Error> {
+        let mut tx = self
+            .connection
+            .begin()
+            .await
+            .map_err(Error::DatabaseQuery)?;
+        // Only the last email sent for a purpose holds a usable token
+        if let Err(e) = sqlx::query(
+            "Update email_tokens set used_at = $1, updated_on = NOW() \
+                where user_id = $2 and purpose = $3 and used_at IS NULL",
+        )
+        .bind(Utc::now().timestamp())
+        .bind(new_email_token.user_id.0)
+        .bind(new_email_token.purpose.as_str())
+        .execute(&mut tx)
+        .await
+        {
+            event!(
+                Level::ERROR,
+                "Discard email tokens of user for database has error: {:?}",
+                e
+            );
+            return Err(Error::DatabaseQuery(e));
+        }
+        let purpose = new_email_token.purpose;
+        let email_token = match sqlx::query(
+            "INSERT INTO email_tokens (jti, user_id, purpose, expires_at) \
+                            VALUES ($1, $2, $3, $4) \
+                            RETURNING id, jti, user_id, expires_at, used_at",
+        )
+        .bind(new_email_token.jti)
+        .bind(new_email_token.user_id.0)
+        .bind(purpose.as_str())
+        .bind(new_email_token.expires_at)
+        .map(|row: PgRow| row_email_token(&row, purpose))
+        .fetch_one(&mut tx)
+        .await
+        {
+            Ok(email_token) => email_token,
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Create email token for database has error: {:?}",
+                    e
+                );
+                return Err(Error::DatabaseQuery(e));
+            }
+        };
+        match tx.commit().await {
+            Ok(_) => Ok(email_token),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Commit create email token for database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn use_email_token(
+        &self,
+        jti: &str,
+        purpose: EmailTokenPurpose,
+    ) -> Result<EmailToken, Error> {
+        // Checking and marking the token in one statement, so a token is used only once
+        match sqlx::query(
+            "Update email_tokens set used_at = $1, updated_on = NOW() \
+                where jti = $2 and purpose = $3 and used_at IS NULL and expires_at > $1 \
+                RETURNING id, jti, user_id, expires_at, used_at",
+        )
+        .bind(Utc::now().timestamp())
+        .bind(jti)
+        .bind(purpose.as_str())
+        .map(|row: PgRow| row_email_token(&row, purpose))
+        .fetch_optional(&self.connection)
+        .await
+        {
+            Ok(Some(email_token)) => Ok(email_token),
+            Ok(None) => Err(Error::InvalidEmailToken),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Use email token from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn get_login_attempt(&self, subject: &str) -> Result<Option<LoginAttempt>, Error> {
+        match sqlx::query("SELECT * FROM login_attempts WHERE subject = $1")
+            .bind(subject)
+            .map(|row: PgRow| row_login_attempt(&row))
+            .fetch_optional(&self.connection)
+            .await
+        {
+            Ok(login_attempt) => Ok(login_attempt),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Get login attempt from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn reserve_login(
+        &self,
+        subject: &str,
+        now: i64,
+        window_start: i64,
+        penalty: LoginPenalty<'_>,
+    ) -> Result<LoginAttempt, Error> {
+        let mut tx = self
+            .connection
+            .begin()
+            .await
+            .map_err(Error::DatabaseQuery)?;
+        // The upsert locks the row of the subject, so concurrent attempts are reserved one by one
+        let mut attempt = match sqlx::query(
+            "INSERT INTO login_attempts (subject, failed_count, first_failed_at, next_attempt_at) \
+                VALUES ($1, 0, $2, $2) \
+                ON CONFLICT (subject) DO UPDATE SET subject = EXCLUDED.subject \
+                RETURNING subject, failed_count, first_failed_at, next_attempt_at, locked_until",
+        )
+        .bind(subject)
+        .bind(now)
+        .map(|row: PgRow| row_login_attempt(&row))
+        .fetch_one(&mut tx)
+        .await
+        {
+            Ok(login_attempt) => login_attempt,
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Lock login attempt from database has error: {:?}",
+                    e
+                );
+                return Err(Error::DatabaseQuery(e));
+            }
+        };
+        attempt.reserve(now, window_start, penalty)?;
+        update_login_attempt(&mut tx, &attempt).await?;
+        match tx.commit().await {
+            Ok(_) => Ok(attempt),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Reserve login attempt for database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn release_login(
+        &self,
+        reserved: &LoginAttempt,
+        now: i64,
+    ) -> Result<Option<LoginAttempt>, Error> {
+        let mut tx = self
+            .connection
+            .begin()
+            .await
+            .map_err(Error::DatabaseQuery)?;
+        let attempt = match sqlx::query(
+            "SELECT subject, failed_count, first_failed_at, next_attempt_at, locked_until \
+                FROM login_attempts WHERE subject = $1 FOR UPDATE",
+        )
+        .bind(&reserved.subject)
+        .map(|row: PgRow| row_login_attempt(&row))
+        .fetch_optional(&mut tx)
+        .await
+        {
+            Ok(login_attempt) => login_attempt,
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Lock login attempt from database has error: {:?}",
+                    e
+                );
+                return Err(Error::DatabaseQuery(e));
+            }
+        };
+        // The attempts were unlocked meanwhile
+        let mut attempt = match attempt {
+            Some(attempt) => attempt,
+            None => return Ok(None),
+        };
+        attempt.release(reserved, now);
+        update_login_attempt(&mut tx, &attempt).await?;
+        match tx.commit().await {
+            Ok(_) => Ok(Some(attempt)),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Release login attempt for database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn clear_login_attempts(&self, subject: &str) -> Result<bool, Error> {
+        match sqlx::query("DELETE FROM login_attempts WHERE subject = $1")
+            .bind(subject)
+            .execute(&self.connection)
+            .await
+        {
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Clear login attempts from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn create_audit_entry(&self, new_entry: NewAuditEntry) -> Result<AuditEntry, Error> {
+        match sqlx::query(
+            "INSERT INTO audit_log \
+                (actor_id, action, entity_type, entity_id, before, after, created_at) \
+                VALUES ($1, $2, $3, $4, $5, $6, $7) \
+                RETURNING id, actor_id, action, entity_type, entity_id, before, after, created_at",
+        )
+        .bind(new_entry.actor_id.map(|actor_id| actor_id.0))
+        .bind(new_entry.action.as_str())
+        .bind(new_entry.entity_type.as_str())
+        .bind(new_entry.entity_id)
+        .bind(new_entry.before)
+        .bind(new_entry.after)
+        .bind(new_entry.created_at)
+        .map(|row: PgRow| row_audit_entry(&row))
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(entry) => Ok(entry),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Create audit entry from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn get_list_audit_entries(&self, filter: AuditFilter) -> Result<Page<AuditEntry>, Error> {
+        // Ids grow with time, so the id is the key of the cursor
+        let pagination = filter.pagination;
+        match sqlx::query(
+            "SELECT *, id::BIGINT AS cursor_key FROM audit_log \
+                WHERE ($1::INTEGER IS NULL OR actor_id = $1) \
+                AND ($2::TEXT IS NULL OR action = $2) \
+                AND ($3::TEXT IS NULL OR entity_type = $3) \
+                AND ($4::TEXT IS NULL OR entity_id = $4) \
+                AND ($5::BIGINT IS NULL OR created_at >= $5) \
+                AND ($6::BIGINT IS NULL OR created_at <= $6) \
+                AND ($7::INTEGER IS NULL OR id < $7) \
+                ORDER BY id DESC LIMIT $8 OFFSET $9",
+        )
+        .bind(filter.actor_id.map(|actor_id| actor_id.0))
+        .bind(filter.action.map(|action| action.as_str()))
+        .bind(filter.entity_type.map(|entity_type| entity_type.as_str()))
+        .bind(filter.entity_id)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(pagination.cursor.as_ref().map(|cursor| cursor.id))
+        .bind(pagination.limit.map(|limit| limit.saturating_add(1)))
+        .bind(pagination.offset)
+        .map(|row: PgRow| (row_audit_entry(&row), row_cursor(&row)))
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(rows) => Ok(Page::from_rows(rows, pagination.limit)),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Get list audit entries from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn revoke_access_token(
+        &self,
+        jti: &str,
+        user_id: UserId,
+        expires_at: i64,
+    ) -> Result<bool, Error> {
+        // Entries of expired tokens are not needed anymore
+        if let Err(e) = sqlx::query("DELETE FROM revoked_tokens WHERE expires_at < $1")
+            .bind(Utc::now().timestamp())
+            .execute(&self.connection)
+            .await
+        {
+            event!(
+                Level::ERROR,
+                "Delete expired revoked tokens from database has error: {:?}",
+                e
+            );
+        }
+        match sqlx::query(
+            "INSERT INTO revoked_tokens (jti, user_id, expires_at) \
+                            VALUES ($1, $2, $3) \
+                            ON CONFLICT (jti) DO NOTHING",
+        )
+        .bind(jti)
+        .bind(user_id.0)
+        .bind(expires_at)
+        .execute(&self.connection)
+        .await
+        {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Revoke access token for database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn revoke_user_tokens(&self, user_id: UserId) -> Result<i32, Error> {
+        let mut tx = self
+            .connection
+            .begin()
+            .await
+            .map_err(Error::DatabaseQuery)?;
+        let token_version = match sqlx::query(
+            "INSERT INTO user_token_versions (user_id, token_version) \
+                            VALUES ($1, $2) \
+                            ON CONFLICT (user_id) \
+                            DO UPDATE SET token_version = user_token_versions.token_version + 1, \
+                                        updated_on = NOW() \
+                            RETURNING token_version",
+        )
+        .bind(user_id.0)
+        .bind(1)
+        .map(|row: PgRow| row.get::<i32, _>("token_version"))
+        .fetch_one(&mut tx)
+        .await
+        {
+            Ok(token_version) => token_version,
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Revoke user tokens for database has error: {:?}",
+                    e
+                );
+                return Err(Error::DatabaseQuery(e));
+            }
+        };
+        if let Err(e) = sqlx::query(
+            "Update refresh_tokens set is_revoked = $1, updated_on = NOW() where user_id = $2",
+        )
+        .bind(true)
+        .bind(user_id.0)
+        .execute(&mut tx)
+        .await
+        {
+            event!(
+                Level::ERROR,
+                "Revoke refresh tokens of user for database has error: {:?}",
+                e
+            );
+            return Err(Error::DatabaseQuery(e));
+        }
+        match tx.commit().await {
+            Ok(_) => Ok(token_version),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Commit revoke user tokens for database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn get_user_token_version(&self, user_id: UserId) -> Result<i32, Error> {
+        match sqlx::query(
+            "SELECT COALESCE((SELECT token_version FROM user_token_versions \
+                                WHERE user_id = $1), 0) AS token_version",
+        )
+        .bind(user_id.0)
+        .map(|row: PgRow| row.get::<i32, _>("token_version"))
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(token_version) => Ok(token_version),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Get user token version from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn is_access_token_revoked(
+        &self,
+        jti: &str,
+        user_id: UserId,
+        token_version: i32,
+    ) -> Result<bool, Error> {
+        match sqlx::query(
+            "SELECT EXISTS (SELECT 1 FROM revoked_tokens WHERE jti = $1) \
+                OR EXISTS (SELECT 1 FROM user_token_versions \
+                            WHERE user_id = $2 AND token_version > $3) AS revoked",
+        )
+        .bind(jti)
+        .bind(user_id.0)
+        .bind(token_version)
+        .map(|row: PgRow| row.get::<bool, _>("revoked"))
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(revoked) => Ok(revoked),
             Err(e) => {
                 event!(
                     Level::ERROR,
-                    "Delete user by id from database has error: {:?}",
+                    "Check revoked access token from database has error: {:?}",
                     e
                 );
                 Err(Error::DatabaseQuery(e))
@@ -361,15 +1272,17 @@ impl StoreMethods for DatabaseStore {
 
     async fn create_role(&self, new_role: RoleInfo) -> Result<Role, Error> {
         match sqlx::query(
-            "INSERT INTO roles (role, is_delete) \
-                            VALUES ($1, $2)\
-                            RETURNING id, role, is_delete",
+            "INSERT INTO roles (role, inherits_role_id, is_delete) \
+                            VALUES ($1, $2, $3)\
+                            RETURNING id, role, inherits_role_id, is_delete",
         )
         .bind(new_role.role)
+        .bind(new_role.inherits_role_id.map(|role_id| role_id.0))
         .bind(false)
         .map(|row: PgRow| Role {
             id: Some(RoleId(row.get("id"))),
             role: row.get("role"),
+            inherits_role_id: row.get::<Option<i32>, _>("inherits_role_id").map(RoleId),
             is_delete: row.get("is_delete"),
         })
         .fetch_one(&self.connection)
@@ -389,6 +1302,7 @@ impl StoreMethods for DatabaseStore {
             .map(|row: PgRow| Role {
                 id: Some(RoleId(row.get("id"))),
                 role: row.get("role"),
+                inherits_role_id: row.get::<Option<i32>, _>("inherits_role_id").map(RoleId),
                 is_delete: row.get("is_delete"),
             })
             .fetch_one(&self.connection)
@@ -411,6 +1325,7 @@ impl StoreMethods for DatabaseStore {
             .map(|row: PgRow| Role {
                 id: Some(RoleId(row.get("id"))),
                 role: row.get("role"),
+                inherits_role_id: row.get::<Option<i32>, _>("inherits_role_id").map(RoleId),
                 is_delete: row.get("is_delete"),
             })
             .fetch_all(&self.connection)
@@ -430,15 +1345,17 @@ impl StoreMethods for DatabaseStore {
 
     async fn update_role(&self, role: Role) -> Result<Role, Error> {
         match sqlx::query(
-            "Update roles SET role = $1\
-                            WHERE id = $2\
-                            RETURNING id, role, is_delete",
+            "Update roles SET role = $1, inherits_role_id = $2 \
+                            WHERE id = $3\
+                            RETURNING id, role, inherits_role_id, is_delete",
         )
         .bind(role.role)
+        .bind(role.inherits_role_id.map(|role_id| role_id.0))
         .bind(role.id.unwrap().0)
         .map(|row: PgRow| Role {
             id: Some(RoleId(row.get("id"))),
             role: row.get("role"),
+            inherits_role_id: row.get::<Option<i32>, _>("inherits_role_id").map(RoleId),
             is_delete: row.get("is_delete"),
         })
         .fetch_one(&self.connection)
@@ -467,6 +1384,147 @@ impl StoreMethods for DatabaseStore {
         }
     }
 
+    async fn get_role_chain(&self, role_id: RoleId) -> Result<Vec<Role>, Error> {
+        match sqlx::query(
+            "WITH RECURSIVE chain AS ( \
+                SELECT id, role, inherits_role_id, is_delete, 1 AS depth \
+                FROM roles WHERE id = $1 AND is_delete = false \
+                UNION ALL \
+                SELECT r.id, r.role, r.inherits_role_id, r.is_delete, c.depth + 1 \
+                FROM roles r JOIN chain c ON r.id = c.inherits_role_id \
+                WHERE r.is_delete = false AND c.depth < $2 \
+            ) \
+            SELECT id, role, inherits_role_id, is_delete FROM chain ORDER BY depth",
+        )
+        .bind(role_id.0)
+        .bind(MAX_ROLE_DEPTH)
+        .map(|row: PgRow| Role {
+            id: Some(RoleId(row.get("id"))),
+            role: row.get("role"),
+            inherits_role_id: row.get::<Option<i32>, _>("inherits_role_id").map(RoleId),
+            is_delete: row.get("is_delete"),
+        })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(mut chain) => {
+                let mut seen: Vec<RoleId> = Vec::new();
+                chain.retain(|role| {
+                    let id = role.id.clone().unwrap();
+                    if seen.contains(&id) {
+                        return false;
+                    }
+                    seen.push(id);
+                    true
+                });
+                Ok(chain)
+            }
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Get role chain from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn get_role_permissions(&self, role_id: RoleId) -> Result<Vec<Permission>, Error> {
+        let role_ids: Vec<i32> = self
+            .get_role_chain(role_id)
+            .await?
+            .into_iter()
+            .map(|role| role.id.unwrap().0)
+            .collect();
+        match sqlx::query(
+            "SELECT DISTINCT permission FROM role_permissions \
+                WHERE role_id = ANY($1) ORDER BY permission",
+        )
+        .bind(role_ids)
+        .map(|row: PgRow| row.get::<String, _>("permission"))
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(permissions) => Ok(permissions
+                .into_iter()
+                .filter_map(|permission| match permission.parse::<Permission>() {
+                    Ok(permission) => Some(permission),
+                    Err(e) => {
+                        event!(Level::WARN, "Skip role permission: {:?}", e);
+                        None
+                    }
+                })
+                .collect()),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Get role permissions from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn set_role_permissions(
+        &self,
+        role_permissions: RolePermissions,
+    ) -> Result<RolePermissions, Error> {
+        let mut tx = self
+            .connection
+            .begin()
+            .await
+            .map_err(Error::DatabaseQuery)?;
+        if let Err(e) = sqlx::query("DELETE FROM role_permissions WHERE role_id = $1")
+            .bind(role_permissions.role_id.0)
+            .execute(&mut tx)
+            .await
+        {
+            event!(
+                Level::ERROR,
+                "Clear role permissions for database has error: {:?}",
+                e
+            );
+            return Err(Error::DatabaseQuery(e));
+        }
+        let mut permissions: Vec<Permission> = Vec::new();
+        for permission in role_permissions.permissions {
+            if permissions.contains(&permission) {
+                continue;
+            }
+            if let Err(e) =
+                sqlx::query("INSERT INTO role_permissions (role_id, permission) VALUES ($1, $2)")
+                    .bind(role_permissions.role_id.0)
+                    .bind(permission.as_str())
+                    .execute(&mut tx)
+                    .await
+            {
+                event!(
+                    Level::ERROR,
+                    "Set role permissions for database has error: {:?}",
+                    e
+                );
+                return Err(Error::DatabaseQuery(e));
+            }
+            permissions.push(permission);
+        }
+        match tx.commit().await {
+            Ok(_) => Ok(RolePermissions {
+                role_id: role_permissions.role_id,
+                permissions,
+            }),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Commit role permissions for database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
     async fn create_company(&self, new_company: NewCompany) -> Result<Company, Error> {
         match sqlx::query(
             "INSERT INTO companies (email, name, address, description, is_delete) \
@@ -553,26 +1611,34 @@ impl StoreMethods for DatabaseStore {
         }
     }
 
-    async fn get_list_company(
-        &self,
-        limit: Option<i32>,
-        offset: i32,
-    ) -> Result<Vec<Company>, Error> {
-        match sqlx::query("SELECT * FROM COMPANIES LIMIT $1 OFFSET $2")
-            .bind(limit)
-            .bind(offset)
-            .map(|row: PgRow| Company {
-                id: Some(CompanyId(row.get("id"))),
-                email: row.get("email"),
-                name: row.get("name"),
-                address: row.get("address"),
-                description: row.get("description"),
-                is_delete: row.get("is_delete"),
-            })
-            .fetch_all(&self.connection)
-            .await
+    async fn get_list_company(&self, pagination: Pagination) -> Result<Page<Company>, Error> {
+        match sqlx::query(&format!(
+            "SELECT *, (EXTRACT(EPOCH FROM created_on) * 1000000)::BIGINT AS cursor_key FROM companies \
+                WHERE {} \
+                ORDER BY created_on, id LIMIT $3 OFFSET $4",
+            created_on_after("$1", "$2")
+        ))
+        .bind(pagination.cursor.as_ref().map(|cursor| cursor.key))
+        .bind(pagination.cursor.as_ref().map(|cursor| cursor.id))
+        .bind(pagination.limit.map(|limit| limit.saturating_add(1)))
+        .bind(pagination.offset)
+        .map(|row: PgRow| {
+            (
+                Company {
+                    id: Some(CompanyId(row.get("id"))),
+                    email: row.get("email"),
+                    name: row.get("name"),
+                    address: row.get("address"),
+                    description: row.get("description"),
+                    is_delete: row.get("is_delete"),
+                },
+                row_cursor(&row),
+            )
+        })
+        .fetch_all(&self.connection)
+        .await
         {
-            Ok(companies) => Ok(companies),
+            Ok(rows) => Ok(Page::from_rows(rows, pagination.limit)),
             Err(e) => {
                 event!(
                     Level::ERROR,
@@ -641,10 +1707,11 @@ impl StoreMethods for DatabaseStore {
     async fn create_job(&self, new_job: NewJob) -> Result<Job, Error> {
         match sqlx::query(
             "INSERT INTO jobs (job_name, company_id, location, quantity, \
-                                                salary, job_level, description, is_delete) \
-                            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)\
-                            RETURNING id, job_name, company_id, location, quantity,\
-                                        salary, job_level, description, is_delete",
+                                                salary, job_level, description, is_delete, \
+                                                status, published_at, expires_at) \
+                            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, \
+                                CASE WHEN $9::TEXT = $10::TEXT THEN EXTRACT(EPOCH FROM NOW())::BIGINT END, $11)\
+                            RETURNING *",
         )
         .bind(new_job.job_name)
         .bind(new_job.company_id.0)
@@ -654,17 +1721,10 @@ impl StoreMethods for DatabaseStore {
         .bind(new_job.job_level)
         .bind(new_job.description)
         .bind(false)
-        .map(|row: PgRow| Job {
-            id: Some(JobId(row.get("id"))),
-            job_name: row.get("job_name"),
-            company_id: CompanyId(row.get("company_id")),
-            location: row.get("location"),
-            quantity: row.get("quantity"),
-            salary: row.get("salary"),
-            job_level: row.get("job_level"),
-            description: row.get("description"),
-            is_delete: row.get("is_delete"),
-        })
+        .bind(new_job.status.as_str())
+        .bind(JobStatus::Published.as_str())
+        .bind(new_job.expires_at)
+        .map(|row: PgRow| row_job(&row))
         .fetch_one(&self.connection)
         .await
         {
@@ -679,17 +1739,7 @@ impl StoreMethods for DatabaseStore {
     async fn get_job_by_id(&self, job_id: JobId) -> Result<Job, Error> {
         match sqlx::query("SELECT * FROM JOBS WHERE id = $1")
             .bind(job_id.0)
-            .map(|row: PgRow| Job {
-                id: Some(JobId(row.get("id"))),
-                job_name: row.get("job_name"),
-                company_id: CompanyId(row.get("company_id")),
-                location: row.get("location"),
-                quantity: row.get("quantity"),
-                salary: row.get("salary"),
-                job_level: row.get("job_level"),
-                description: row.get("description"),
-                is_delete: row.get("is_delete"),
-            })
+            .map(|row: PgRow| row_job(&row))
             .fetch_one(&self.connection)
             .await
         {
@@ -701,31 +1751,82 @@ impl StoreMethods for DatabaseStore {
         }
     }
 
-    async fn get_list_job(&self, limit: Option<i32>, offset: i32) -> Result<Vec<Job>, Error> {
-        match sqlx::query("SELECT * FROM JOBS LIMIT $1 OFFSET $2")
-            .bind(limit)
-            .bind(offset)
-            .map(|row: PgRow| Job {
-                id: Some(JobId(row.get("id"))),
-                job_name: row.get("job_name"),
-                company_id: CompanyId(row.get("company_id")),
-                location: row.get("location"),
-                quantity: row.get("quantity"),
-                salary: row.get("salary"),
-                job_level: row.get("job_level"),
-                description: row.get("description"),
-                is_delete: row.get("is_delete"),
-            })
-            .fetch_all(&self.connection)
+    async fn search_jobs(&self, search: JobSearch) -> Result<JobSearchResult, Error> {
+        // Only open jobs are listed, every filter is skipped when its parameter is null
+        let filter = "WHERE is_delete = false AND status = 'published' \
+            AND (expires_at IS NULL OR expires_at > EXTRACT(EPOCH FROM NOW())) \
+            AND ($1::TEXT IS NULL OR search_vector @@ websearch_to_tsquery('english', $1)) \
+            AND ($2::TEXT IS NULL OR location ILIKE '%' || $2 || '%') \
+            AND ($3::TEXT IS NULL OR LOWER(job_level) = LOWER($3)) \
+            AND ($4::INTEGER IS NULL OR company_id = $4) \
+            AND ($5::INTEGER IS NULL OR salary >= $5) \
+            AND ($6::INTEGER IS NULL OR salary <= $6)";
+        // The cursor key holds the value of the sort column
+        let (sort_column, cursor_key, keyset_value) = match search.sort {
+            JobSortField::Salary => ("salary", "salary::BIGINT", "$9"),
+            JobSortField::CreatedOn => (
+                "created_on",
+                "(EXTRACT(EPOCH FROM created_on) * 1000000)::BIGINT",
+                "TIMESTAMP 'epoch' + $9 * INTERVAL '1 microsecond'",
+            ),
+        };
+        let (sort_order, keyset_operator) = match search.order {
+            SortOrder::Asc => ("ASC", ">"),
+            SortOrder::Desc => ("DESC", "<"),
+        };
+        let pagination = search.pagination;
+        let location = search.location.as_deref().map(escape_like);
+        let company_id = search.company_id.as_ref().map(|company_id| company_id.0);
+
+        let total = match sqlx::query(&format!("SELECT COUNT(*) AS total FROM jobs {}", filter))
+            .bind(search.q.as_deref())
+            .bind(location.as_deref())
+            .bind(search.job_level.as_deref())
+            .bind(company_id)
+            .bind(search.min_salary)
+            .bind(search.max_salary)
+            .map(|row: PgRow| row.get::<i64, _>("total"))
+            .fetch_one(&self.connection)
             .await
         {
-            Ok(jobs) => Ok(jobs),
+            Ok(total) => total,
             Err(e) => {
                 event!(
                     Level::ERROR,
-                    "Get job by id from database has error: {:?}",
+                    "Count search jobs from database has error: {:?}",
                     e
                 );
+                return Err(Error::DatabaseQuery(e));
+            }
+        };
+
+        match sqlx::query(&format!(
+            "SELECT *, {cursor_key} AS cursor_key FROM jobs {filter} \
+                AND ($9::BIGINT IS NULL OR ({sort_column}, id) {keyset_operator} ({keyset_value}, $10)) \
+                ORDER BY {sort_column} {sort_order}, id {sort_order} LIMIT $7 OFFSET $8"
+        ))
+        .bind(search.q.as_deref())
+        .bind(location.as_deref())
+        .bind(search.job_level.as_deref())
+        .bind(company_id)
+        .bind(search.min_salary)
+        .bind(search.max_salary)
+        .bind(pagination.limit.map(|limit| limit.saturating_add(1)))
+        .bind(pagination.offset)
+        .bind(pagination.cursor.as_ref().map(|cursor| cursor.key))
+        .bind(pagination.cursor.as_ref().map(|cursor| cursor.id))
+        .map(|row: PgRow| {
+            (row_job(&row), row_cursor(&row))
+        })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(rows) => Ok(JobSearchResult {
+                jobs: Page::from_rows(rows, pagination.limit),
+                total,
+            }),
+            Err(e) => {
+                event!(Level::ERROR, "Search jobs from database has error: {:?}", e);
                 Err(Error::DatabaseQuery(e))
             }
         }
@@ -736,10 +1837,9 @@ impl StoreMethods for DatabaseStore {
             "Update jobs \
                             SET job_name = $1, location = $2, \
                             quantity = $3, salary = $4, job_level= $5, \
-                            description = $6 \
-                            where id = $7 \
-                            RETURNING id, job_name, company_id, location, quantity,\
-                                        salary, job_level, description, is_delete",
+                            description = $6, expires_at = $7, updated_on = NOW() \
+                            where id = $8 \
+                            RETURNING *",
         )
         .bind(job.job_name)
         .bind(job.location)
@@ -747,18 +1847,9 @@ impl StoreMethods for DatabaseStore {
         .bind(job.salary)
         .bind(job.job_level)
         .bind(job.description)
+        .bind(job.expires_at)
         .bind(job.id.unwrap().0)
-        .map(|row: PgRow| Job {
-            id: Some(JobId(row.get("id"))),
-            job_name: row.get("job_name"),
-            company_id: CompanyId(row.get("company_id")),
-            location: row.get("location"),
-            quantity: row.get("quantity"),
-            salary: row.get("salary"),
-            job_level: row.get("job_level"),
-            description: row.get("description"),
-            is_delete: row.get("is_delete"),
-        })
+        .map(|row: PgRow| row_job(&row))
         .fetch_one(&self.connection)
         .await
         {
@@ -771,7 +1862,7 @@ impl StoreMethods for DatabaseStore {
     }
 
     async fn delete_job(&self, job_id: JobId) -> Result<bool, Error> {
-        match sqlx::query("Update resumes set is_delete = $1 where id = $2")
+        match sqlx::query("Update jobs set is_delete = $1 where id = $2")
             .bind(true)
             .bind(job_id.0)
             .execute(&self.connection)
@@ -785,23 +1876,145 @@ impl StoreMethods for DatabaseStore {
         }
     }
 
+    async fn update_job_status(
+        &self,
+        job_id: JobId,
+        from_status: JobStatus,
+        to_status: JobStatus,
+        now: i64,
+    ) -> Result<Job, Error> {
+        // Only the expected status is updated, so a job expired meanwhile isn't reopened
+        match sqlx::query(
+            "UPDATE jobs SET status = $1, updated_on = NOW(), \
+                            published_at = CASE WHEN $1::TEXT = $2::TEXT THEN COALESCE(published_at, $3) \
+                                ELSE published_at END \
+                            WHERE id = $4 AND status = $5 \
+                            RETURNING *",
+        )
+        .bind(to_status.as_str())
+        .bind(JobStatus::Published.as_str())
+        .bind(now)
+        .bind(job_id.0)
+        .bind(from_status.as_str())
+        .map(|row: PgRow| row_job(&row))
+        .fetch_optional(&self.connection)
+        .await
+        {
+            Ok(Some(job)) => Ok(job),
+            Ok(None) => Err(Error::InvalidStatusTransition(
+                from_status.to_string(),
+                to_status.to_string(),
+            )),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Update job status from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn expire_jobs(&self, now: i64) -> Result<Vec<Job>, Error> {
+        match sqlx::query(
+            "UPDATE jobs SET status = $1, updated_on = NOW() \
+                            WHERE status IN ($2, $3) AND expires_at <= $4 \
+                            RETURNING *",
+        )
+        .bind(JobStatus::Expired.as_str())
+        .bind(JobStatus::Published.as_str())
+        .bind(JobStatus::Paused.as_str())
+        .bind(now)
+        .map(|row: PgRow| row_job(&row))
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(jobs) => Ok(jobs),
+            Err(e) => {
+                event!(Level::ERROR, "Expire jobs from database has error: {:?}", e);
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn get_recommendation_jobs(
+        &self,
+        user_id: UserId,
+        skill_words: Vec<String>,
+        limit: i64,
+    ) -> Result<Vec<Job>, Error> {
+        // Same open jobs as search_jobs, matching any of the skill words through the
+        // search vector, or the newest ones when there is no skill word
+        match sqlx::query(
+            "SELECT * FROM jobs \
+                WHERE is_delete = false AND status = 'published' \
+                AND (expires_at IS NULL OR expires_at > EXTRACT(EPOCH FROM NOW())) \
+                AND (cardinality($2::TEXT[]) = 0 \
+                    OR search_vector @@ websearch_to_tsquery('english', array_to_string($2, ' or '))) \
+                AND id NOT IN (SELECT job_id FROM map_resume_job WHERE applied_by = $1) \
+                ORDER BY ts_rank(search_vector, websearch_to_tsquery('english', array_to_string($2, ' or '))) DESC, \
+                id DESC LIMIT $3",
+        )
+        .bind(user_id.0)
+        .bind(&skill_words)
+        .bind(limit)
+        .map(|row: PgRow| row_job(&row))
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(jobs) => Ok(jobs),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Get recommendation jobs from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
     async fn create_resume(&self, new_resume: NewResume) -> Result<Resume, Error> {
         match sqlx::query(
-            "INSERT INTO resumes (user_id, email, url, is_delete) \
-                            VALUES ($1, $2, $3, $4)\
-                            RETURNING id, user_id, email, url, is_delete",
+            "INSERT INTO resumes (user_id, email, url, is_delete, \
+                            file_key, file_name, file_content_type, file_size, file_text, \
+                            headline, location, skills, experiences, educations, languages, \
+                            years_of_experience, expected_salary, is_searchable) \
+                            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, \
+                            $15, $16, $17, $18) \
+                            RETURNING *",
         )
         .bind(new_resume.user_id.0)
         .bind(new_resume.email)
         .bind(new_resume.url)
         .bind(false)
-        .map(|row: PgRow| Resume {
-            id: Some(ResumeId(row.get("id"))),
-            user_id: UserId(row.get("user_id")),
-            email: row.get("email"),
-            url: row.get("url"),
-            is_delete: row.get("is_delete"),
-        })
+        .bind(new_resume.file.as_ref().map(|file| file.key.clone()))
+        .bind(new_resume.file.as_ref().map(|file| file.name.clone()))
+        .bind(
+            new_resume
+                .file
+                .as_ref()
+                .map(|file| file.content_type.clone()),
+        )
+        .bind(new_resume.file.as_ref().map(|file| file.size))
+        .bind(
+            new_resume
+                .file
+                .as_ref()
+                .map(|file| file.text.clone())
+                .unwrap_or_default(),
+        )
+        .bind(new_resume.profile.headline)
+        .bind(new_resume.profile.location)
+        .bind(new_resume.profile.skills)
+        .bind(Json(new_resume.profile.experiences))
+        .bind(Json(new_resume.profile.educations))
+        .bind(new_resume.profile.languages)
+        .bind(new_resume.profile.years_of_experience)
+        .bind(new_resume.profile.expected_salary)
+        .bind(new_resume.is_searchable)
+        .map(|row: PgRow| row_resume(&row))
         .fetch_one(&self.connection)
         .await
         {
@@ -820,13 +2033,7 @@ impl StoreMethods for DatabaseStore {
     async fn get_resume_by_id(&self, resume_id: ResumeId) -> Result<Resume, Error> {
         match sqlx::query("SELECT * FROM RESUMES WHERE id = $1")
             .bind(resume_id.0)
-            .map(|row: PgRow| Resume {
-                id: Some(ResumeId(row.get("id"))),
-                user_id: UserId(row.get("user_id")),
-                email: row.get("email"),
-                url: row.get("url"),
-                is_delete: row.get("is_delete"),
-            })
+            .map(|row: PgRow| row_resume(&row))
             .fetch_one(&self.connection)
             .await
         {
@@ -840,24 +2047,130 @@ impl StoreMethods for DatabaseStore {
 
     async fn get_list_resume_by_user_id(
         &self,
-        limit: Option<i32>,
-        offset: i32,
+        pagination: Pagination,
         user_id: UserId,
+    ) -> Result<Page<Resume>, Error> {
+        match sqlx::query(&format!(
+            "SELECT *, (EXTRACT(EPOCH FROM created_on) * 1000000)::BIGINT AS cursor_key FROM resumes \
+                WHERE user_id = $1 AND {} \
+                ORDER BY created_on, id LIMIT $4 OFFSET $5",
+            created_on_after("$2", "$3")
+        ))
+        .bind(user_id.0)
+        .bind(pagination.cursor.as_ref().map(|cursor| cursor.key))
+        .bind(pagination.cursor.as_ref().map(|cursor| cursor.id))
+        .bind(pagination.limit.map(|limit| limit.saturating_add(1)))
+        .bind(pagination.offset)
+        .map(|row: PgRow| {
+            (row_resume(&row), row_cursor(&row))
+        })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(rows) => Ok(Page::from_rows(rows, pagination.limit)),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Get list resume by user id from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn search_resumes(&self, search: ResumeSearch) -> Result<ResumeSearchResult, Error> {
+        // Deleted and hidden resumes are never listed, every filter is skipped when its
+        // parameter is null and the skills filter when the array is empty
+        let filter = "WHERE is_delete = false AND is_searchable = true \
+            AND ($1::TEXT IS NULL OR search_vector @@ websearch_to_tsquery('english', $1)) \
+            AND ($2::TEXT IS NULL OR location ILIKE '%' || $2 || '%') \
+            AND $3::TEXT[] <@ ARRAY(SELECT LOWER(skill) FROM unnest(skills) skill) \
+            AND ($4::INTEGER IS NULL OR years_of_experience >= $4) \
+            AND ($5::INTEGER IS NULL OR years_of_experience <= $5)";
+        // Same rank as resume_search::rank, with ts_rank scaled to an integer relevance
+        let rank = format!(
+            "(CASE WHEN $1::TEXT IS NULL THEN 0 \
+                ELSE (ts_rank(search_vector, websearch_to_tsquery('english', $1)) * 1000)::BIGINT \
+                END) * {} + years_of_experience",
+            RELEVANCE_WEIGHT
+        );
+        let pagination = search.pagination;
+        let location = search.location.as_deref().map(escape_like);
+
+        let total = match sqlx::query(&format!("SELECT COUNT(*) AS total FROM resumes {}", filter))
+            .bind(search.q.as_deref())
+            .bind(location.as_deref())
+            .bind(&search.skills)
+            .bind(search.min_experience)
+            .bind(search.max_experience)
+            .map(|row: PgRow| row.get::<i64, _>("total"))
+            .fetch_one(&self.connection)
+            .await
+        {
+            Ok(total) => total,
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Count search resumes from database has error: {:?}",
+                    e
+                );
+                return Err(Error::DatabaseQuery(e));
+            }
+        };
+
+        match sqlx::query(&format!(
+            "SELECT * FROM (SELECT *, {rank} AS cursor_key FROM resumes {filter}) ranked \
+                WHERE ($8::BIGINT IS NULL OR (cursor_key, id) < ($8, $9)) \
+                ORDER BY cursor_key DESC, id DESC LIMIT $6 OFFSET $7"
+        ))
+        .bind(search.q.as_deref())
+        .bind(location.as_deref())
+        .bind(&search.skills)
+        .bind(search.min_experience)
+        .bind(search.max_experience)
+        .bind(pagination.limit.map(|limit| limit.saturating_add(1)))
+        .bind(pagination.offset)
+        .bind(pagination.cursor.as_ref().map(|cursor| cursor.key))
+        .bind(pagination.cursor.as_ref().map(|cursor| cursor.id))
+        .map(|row: PgRow| (row_resume(&row), row_cursor(&row)))
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(rows) => Ok(ResumeSearchResult {
+                resumes: Page::from_rows(rows, pagination.limit),
+                total,
+            }),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Search resumes from database has error: {:?}",
+                    e
+                );
+                Err(Error::DatabaseQuery(e))
+            }
+        }
+    }
+
+    async fn get_recommendation_resumes(
+        &self,
+        job_words: Vec<String>,
+        limit: i64,
     ) -> Result<Vec<Resume>, Error> {
+        // Found through the index on the skill words, the text of the file is left out
+        // as only the profile is scored
         match sqlx::query(
-            "SELECT * FROM RESUMES WHERE user_id = $1 \
-                                LIMIT $2 OFFSET $3 ",
+            "SELECT id, user_id, email, url, is_delete, file_key, file_name, file_content_type, \
+                file_size, '' AS file_text, headline, location, skills, experiences, educations, \
+                languages, years_of_experience, expected_salary, is_searchable \
+                FROM resumes \
+                WHERE is_delete = false AND is_searchable = true AND skill_words(skills) && $1 \
+                ORDER BY cardinality(ARRAY(SELECT unnest(skill_words(skills)) INTERSECT SELECT unnest($1::TEXT[]))) DESC, \
+                id DESC LIMIT $2",
         )
-        .bind(user_id.0)
+        .bind(&job_words)
         .bind(limit)
-        .bind(offset)
-        .map(|row: PgRow| Resume {
-            id: Some(ResumeId(row.get("id"))),
-            user_id: UserId(row.get("user_id")),
-            email: row.get("email"),
-            url: row.get("url"),
-            is_delete: row.get("is_delete"),
-        })
+        .map(|row: PgRow| row_resume(&row))
         .fetch_all(&self.connection)
         .await
         {
@@ -865,7 +2178,7 @@ impl StoreMethods for DatabaseStore {
             Err(e) => {
                 event!(
                     Level::ERROR,
-                    "Get list resume by user id from database has error: {:?}",
+                    "Get recommendation resumes from database has error: {:?}",
                     e
                 );
                 Err(Error::DatabaseQuery(e))
@@ -875,19 +2188,37 @@ impl StoreMethods for DatabaseStore {
 
     async fn update_resume(&self, resume: Resume) -> Result<Resume, Error> {
         match sqlx::query(
-            "Update resumes SET url = $1 \
-                            WHERE id = $2 \
-                            RETURNING id, user_id, email, url, is_delete",
+            "Update resumes SET url = $1, file_key = $2, file_name = $3, \
+                            file_content_type = $4, file_size = $5, file_text = $6, \
+                            headline = $7, location = $8, skills = $9, experiences = $10, \
+                            educations = $11, languages = $12, years_of_experience = $13, \
+                            expected_salary = $14, is_searchable = $15 \
+                            WHERE id = $16 \
+                            RETURNING *",
         )
         .bind(resume.url)
+        .bind(resume.file.as_ref().map(|file| file.key.clone()))
+        .bind(resume.file.as_ref().map(|file| file.name.clone()))
+        .bind(resume.file.as_ref().map(|file| file.content_type.clone()))
+        .bind(resume.file.as_ref().map(|file| file.size))
+        .bind(
+            resume
+                .file
+                .as_ref()
+                .map(|file| file.text.clone())
+                .unwrap_or_default(),
+        )
+        .bind(resume.profile.headline)
+        .bind(resume.profile.location)
+        .bind(resume.profile.skills)
+        .bind(Json(resume.profile.experiences))
+        .bind(Json(resume.profile.educations))
+        .bind(resume.profile.languages)
+        .bind(resume.profile.years_of_experience)
+        .bind(resume.profile.expected_salary)
+        .bind(resume.is_searchable)
         .bind(resume.id.unwrap().0)
-        .map(|row: PgRow| Resume {
-            id: Some(ResumeId(row.get("id"))),
-            user_id: UserId(row.get("user_id")),
-            email: row.get("email"),
-            url: row.get("url"),
-            is_delete: row.get("is_delete"),
-        })
+        .map(|row: PgRow| row_resume(&row))
         .fetch_one(&self.connection)
         .await
         {
@@ -922,3 +2253,188 @@ impl StoreMethods for DatabaseStore {
         }
     }
 }
+
+// Escape wildcard characters of a LIKE pattern, backslash is the default escape character
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+// Keyset condition of lists ordered by (created_on, id),
+// the cursor key holds created_on in microseconds since epoch
+fn created_on_after(key: &str, id: &str) -> String {
+    format!(
+        "({key}::BIGINT IS NULL OR (created_on, id) > \
+            (TIMESTAMP 'epoch' + {key} * INTERVAL '1 microsecond', {id}))"
+    )
+}
+
+// Cursor of a row selected with a cursor_key column
+fn row_cursor(row: &PgRow) -> Cursor {
+    Cursor {
+        key: row.get("cursor_key"),
+        id: row.get("id"),
+    }
+}
+
+fn row_resume(row: &PgRow) -> Resume {
+    let file_key: Option<String> = row.get("file_key");
+    Resume {
+        id: Some(ResumeId(row.get("id"))),
+        user_id: UserId(row.get("user_id")),
+        email: row.get("email"),
+        url: row.get::<Option<String>, _>("url").unwrap_or_default(),
+        is_delete: row.get("is_delete"),
+        file: file_key.map(|key| ResumeFile {
+            key,
+            name: row.get("file_name"),
+            content_type: row.get("file_content_type"),
+            size: row.get("file_size"),
+            text: row.get("file_text"),
+        }),
+        profile: ResumeProfile {
+            headline: row.get("headline"),
+            location: row.get("location"),
+            skills: row.get("skills"),
+            experiences: row.get::<Json<Vec<Experience>>, _>("experiences").0,
+            educations: row.get::<Json<Vec<Education>>, _>("educations").0,
+            languages: row.get("languages"),
+            years_of_experience: row.get("years_of_experience"),
+            expected_salary: row.get("expected_salary"),
+        },
+        is_searchable: row.get("is_searchable"),
+    }
+}
+
+fn row_job(row: &PgRow) -> Job {
+    Job {
+        id: Some(JobId(row.get("id"))),
+        job_name: row.get("job_name"),
+        company_id: CompanyId(row.get("company_id")),
+        location: row.get("location"),
+        quantity: row.get("quantity"),
+        salary: row.get("salary"),
+        job_level: row.get("job_level"),
+        description: row.get("description"),
+        is_delete: row.get("is_delete"),
+        status: row.get::<String, _>("status").parse().unwrap_or_default(),
+        published_at: row.get("published_at"),
+        expires_at: row.get("expires_at"),
+    }
+}
+
+fn row_map_resume_job(row: &PgRow) -> MapResumeJob {
+    MapResumeJob {
+        id: Some(MapResumeJobId(row.get("id"))),
+        resume_id: ResumeId(row.get("resume_id")),
+        job_id: JobId(row.get("job_id")),
+        status: row.get::<String, _>("status").parse().unwrap_or_default(),
+    }
+}
+
+fn row_login_attempt(row: &PgRow) -> LoginAttempt {
+    LoginAttempt {
+        subject: row.get("subject"),
+        failed_count: row.get("failed_count"),
+        first_failed_at: row.get("first_failed_at"),
+        next_attempt_at: row.get("next_attempt_at"),
+        locked_until: row.get("locked_until"),
+    }
+}
+
+fn row_audit_entry(row: &PgRow) -> AuditEntry {
+    AuditEntry {
+        id: Some(AuditEntryId(row.get("id"))),
+        actor_id: row.get::<Option<i32>, _>("actor_id").map(UserId),
+        action: row
+            .get::<String, _>("action")
+            .parse()
+            .unwrap_or(AuditAction::Update),
+        entity_type: row
+            .get::<String, _>("entity_type")
+            .parse()
+            .unwrap_or(AuditEntity::Login),
+        entity_id: row.get("entity_id"),
+        before: row.get("before"),
+        after: row.get("after"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn row_email_token(row: &PgRow, purpose: EmailTokenPurpose) -> EmailToken {
+    EmailToken {
+        id: Some(EmailTokenId(row.get("id"))),
+        jti: row.get("jti"),
+        user_id: UserId(row.get("user_id")),
+        purpose,
+        expires_at: row.get("expires_at"),
+        used_at: row.get("used_at"),
+    }
+}
+
+// Record a change of application status inside the transaction of the change
+async fn insert_status_change(
+    tx: &mut Transaction<'_, Postgres>,
+    change: NewApplicationStatusChange,
+) -> Result<(), Error> {
+    match sqlx::query(
+        "INSERT INTO application_status_history \
+            (application_id, from_status, to_status, changed_by, note, changed_at) \
+            VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(change.application_id.0)
+    .bind(change.from_status.map(|status| status.as_str()))
+    .bind(change.to_status.as_str())
+    .bind(change.changed_by.0)
+    .bind(change.note)
+    .bind(Utc::now().timestamp())
+    .execute(tx)
+    .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            event!(
+                Level::ERROR,
+                "Create application status history from database has error: {:?}",
+                e
+            );
+            Err(Error::DatabaseQuery(e))
+        }
+    }
+}
+
+async fn update_login_attempt(
+    tx: &mut Transaction<'_, Postgres>,
+    attempt: &LoginAttempt,
+) -> Result<(), Error> {
+    match sqlx::query(
+        "Update login_attempts set failed_count = $1, first_failed_at = $2, \
+            next_attempt_at = $3, locked_until = $4, updated_on = NOW() \
+            where subject = $5",
+    )
+    .bind(attempt.failed_count)
+    .bind(attempt.first_failed_at)
+    .bind(attempt.next_attempt_at)
+    .bind(attempt.locked_until)
+    .bind(&attempt.subject)
+    .execute(tx)
+    .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            event!(
+                Level::ERROR,
+                "Update login attempt for database has error: {:?}",
+                e
+            );
+            Err(Error::DatabaseQuery(e))
+        }
+    }
+}
+
+// Users without company are stored with NULL company_id
+fn row_company_id(row: &PgRow) -> CompanyId {
+    CompanyId(row.get::<Option<i32>, _>("company_id").unwrap_or(0))
+}
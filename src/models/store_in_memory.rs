@@ -1,16 +1,33 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use chrono::Utc;
 use tokio::sync::RwLock;
 use tracing::{event, Level};
 
 use crate::errors::Error;
+use crate::models::application::{
+    ApplicationStatus, ApplicationStatusChange, ApplicationStatusChangeId,
+    NewApplicationStatusChange,
+};
+use crate::models::audit::{AuditEntry, AuditEntryId, AuditFilter, NewAuditEntry};
 use crate::models::company::{Company, CompanyId, NewCompany};
-use crate::models::job::{Job, JobId, NewJob};
+use crate::models::email_token::{EmailToken, EmailTokenId, EmailTokenPurpose, NewEmailToken};
+use crate::models::health::PoolStatus;
+use crate::models::job::{Job, JobId, JobStatus, NewJob};
+use crate::models::job_search::{tokenize, JobSearch, JobSearchResult, JobSortField, SortOrder};
+use crate::models::login_attempt::{LoginAttempt, LoginPenalty};
 use crate::models::map_resume_job::{MapResumeJob, MapResumeJobId, NewMapResumeJob};
+use crate::models::pagination::{Cursor, Page, Pagination};
+use crate::models::permission::Permission;
+use crate::models::recommendation::skill_words;
+use crate::models::refresh_token::{NewRefreshToken, RefreshToken, RefreshTokenId};
 use crate::models::resume::{NewResume, Resume, ResumeId};
-use crate::models::role::{Role, RoleId, RoleInfo};
+use crate::models::resume_search::{rank, ResumeSearch, ResumeSearchResult};
+use crate::models::revoked_token::RevokedToken;
+use crate::models::role::{default_roles, Role, RoleId, RoleInfo, RolePermissions};
 use crate::models::store_trait::StoreMethods;
 use crate::models::user::{AuthInfo, User, UserId, UserInfo};
 
@@ -18,10 +35,22 @@ use crate::models::user::{AuthInfo, User, UserId, UserInfo};
 pub struct InMemoryStore {
     pub users: Arc<RwLock<HashMap<UserId, User>>>,
     pub roles: Arc<RwLock<HashMap<RoleId, Role>>>,
+    pub role_permissions: Arc<RwLock<HashMap<RoleId, Vec<Permission>>>>,
     pub companies: Arc<RwLock<HashMap<CompanyId, Company>>>,
     pub jobs: Arc<RwLock<HashMap<JobId, Job>>>,
+    // Token index over job name and description, used by job search. The tokens are
+    // lowercase words without stemming, unlike the english search vector of the database
+    pub job_tokens: Arc<RwLock<HashMap<String, HashSet<JobId>>>>,
     pub resumes: Arc<RwLock<HashMap<ResumeId, Resume>>>,
     pub map_resume_job: Arc<RwLock<HashMap<MapResumeJobId, MapResumeJob>>>,
+    pub application_status_history:
+        Arc<RwLock<HashMap<ApplicationStatusChangeId, ApplicationStatusChange>>>,
+    pub refresh_tokens: Arc<RwLock<HashMap<RefreshTokenId, RefreshToken>>>,
+    pub revoked_tokens: Arc<RwLock<HashMap<String, RevokedToken>>>,
+    pub user_token_versions: Arc<RwLock<HashMap<UserId, i32>>>,
+    pub email_tokens: Arc<RwLock<HashMap<EmailTokenId, EmailToken>>>,
+    pub login_attempts: Arc<RwLock<HashMap<String, LoginAttempt>>>,
+    pub audit_log: Arc<RwLock<HashMap<AuditEntryId, AuditEntry>>>,
 }
 
 impl Default for InMemoryStore {
@@ -32,36 +61,246 @@ impl Default for InMemoryStore {
 
 impl InMemoryStore {
     pub fn new() -> Self {
+        let mut roles = HashMap::new();
+        let mut role_permissions = HashMap::new();
+        for (role, permissions) in default_roles() {
+            let role_id = role.id.clone().unwrap();
+            roles.insert(role_id.clone(), role);
+            role_permissions.insert(role_id, permissions);
+        }
+
         InMemoryStore {
             users: Default::default(),
-            roles: Default::default(),
+            roles: Arc::new(RwLock::new(roles)),
+            role_permissions: Arc::new(RwLock::new(role_permissions)),
             companies: Default::default(),
             jobs: Default::default(),
+            job_tokens: Default::default(),
             resumes: Default::default(),
             map_resume_job: Default::default(),
+            application_status_history: Default::default(),
+            refresh_tokens: Default::default(),
+            revoked_tokens: Default::default(),
+            user_token_versions: Default::default(),
+            email_tokens: Default::default(),
+            login_attempts: Default::default(),
+            audit_log: Default::default(),
+        }
+    }
+
+    fn job_tokens(job: &Job) -> Vec<String> {
+        tokenize(&format!("{} {}", job.job_name, job.description))
+    }
+
+    async fn index_job(&self, job: &Job) {
+        let mut lock_token = self.job_tokens.write().await;
+        for token in Self::job_tokens(job) {
+            lock_token
+                .entry(token)
+                .or_default()
+                .insert(job.id.clone().unwrap());
+        }
+    }
+
+    // Company id 0 is used by users without company
+    async fn check_company(&self, company_id: &CompanyId) -> Result<(), Error> {
+        if company_id.0 == 0 {
+            return Ok(());
+        }
+        check_reference(&*self.companies.read().await, company_id, "Company")
+    }
+
+    async fn insert_status_change(&self, change: NewApplicationStatusChange) {
+        let mut lock_history = self.application_status_history.write().await;
+        let id = ApplicationStatusChangeId(lock_history.len() as i32 + 1);
+        lock_history.insert(
+            id.clone(),
+            ApplicationStatusChange {
+                id: Some(id),
+                application_id: change.application_id,
+                from_status: change.from_status,
+                to_status: change.to_status,
+                changed_by: change.changed_by,
+                note: change.note,
+                changed_at: Utc::now().timestamp(),
+            },
+        );
+    }
+
+    async fn unindex_job(&self, job: &Job) {
+        let mut lock_token = self.job_tokens.write().await;
+        for token in Self::job_tokens(job) {
+            if let Some(ids) = lock_token.get_mut(&token) {
+                ids.remove(job.id.as_ref().unwrap());
+                if ids.is_empty() {
+                    lock_token.remove(&token);
+                }
+            }
         }
     }
 }
 
 #[async_trait]
 impl StoreMethods for InMemoryStore {
+    async fn ping(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    // Nothing is migrated in memory
+    async fn pending_migrations(&self) -> Result<Vec<i64>, Error> {
+        Ok(Vec::new())
+    }
+
+    fn pool_status(&self) -> Option<PoolStatus> {
+        None
+    }
+
+    async fn close(&self) {}
+
     // methods for map resume job
     async fn create_map_job_resume(
         &self,
         new_map_resume_job: NewMapResumeJob,
+        applied_by: UserId,
     ) -> Result<MapResumeJob, Error> {
+        check_reference(
+            &*self.resumes.read().await,
+            &new_map_resume_job.resume_id,
+            "Resume",
+        )?;
+        check_reference(&*self.jobs.read().await, &new_map_resume_job.job_id, "Job")?;
+        check_reference(&*self.users.read().await, &applied_by, "User")?;
+        let lock_resume = self.resumes.read().await;
+        let quantity = self.jobs.read().await[&new_map_resume_job.job_id].quantity as usize;
+        // Checked again under the lock, as the database does in the transaction
         let mut lock_map_resume_job = self.map_resume_job.write().await;
+        let applications: Vec<&MapResumeJob> = lock_map_resume_job
+            .values()
+            .filter(|v| v.job_id == new_map_resume_job.job_id)
+            .collect();
+        if applications
+            .iter()
+            .any(|v| v.resume_id == new_map_resume_job.resume_id)
+        {
+            return Err(Error::AlreadyExists("Application".to_string()));
+        }
+        if applications.iter().any(|v| {
+            lock_resume
+                .get(&v.resume_id)
+                .is_some_and(|resume| resume.user_id == applied_by)
+        }) {
+            return Err(Error::DuplicateApplication);
+        }
+        if applications
+            .iter()
+            .filter(|v| v.status == ApplicationStatus::Hired)
+            .count()
+            >= quantity
+        {
+            return Err(Error::JobQuantityExceeded);
+        }
         let len = lock_map_resume_job.len() as i32;
         let id: i32 = if len == 0 { 1 } else { len + 1 };
         let map_resume_job = MapResumeJob {
             id: Some(MapResumeJobId(id)),
             resume_id: new_map_resume_job.resume_id,
             job_id: new_map_resume_job.job_id,
+            status: ApplicationStatus::Applied,
         };
         lock_map_resume_job.insert(map_resume_job.id.clone().unwrap(), map_resume_job.clone());
+        self.insert_status_change(NewApplicationStatusChange {
+            application_id: MapResumeJobId(id),
+            from_status: None,
+            to_status: ApplicationStatus::Applied,
+            changed_by: applied_by,
+            note: None,
+        })
+        .await;
         Ok(map_resume_job)
     }
 
+    async fn has_applied_job(&self, user_id: UserId, job_id: JobId) -> Result<bool, Error> {
+        let lock_resume = self.resumes.read().await;
+        Ok(self.map_resume_job.read().await.values().any(|v| {
+            v.job_id == job_id
+                && lock_resume
+                    .get(&v.resume_id)
+                    .is_some_and(|resume| resume.user_id == user_id)
+        }))
+    }
+
+    async fn count_hired_applications(&self, job_id: JobId) -> Result<i64, Error> {
+        Ok(self
+            .map_resume_job
+            .read()
+            .await
+            .values()
+            .filter(|v| v.job_id == job_id && v.status == ApplicationStatus::Hired)
+            .count() as i64)
+    }
+
+    async fn get_map_resume_job_by_id(
+        &self,
+        map_resume_job_id: MapResumeJobId,
+    ) -> Result<MapResumeJob, Error> {
+        match self.map_resume_job.read().await.get(&map_resume_job_id) {
+            Some(map_resume_job) => Ok(map_resume_job.clone()),
+            None => Err(Error::NotFound),
+        }
+    }
+
+    async fn update_application_status(
+        &self,
+        change: NewApplicationStatusChange,
+    ) -> Result<MapResumeJob, Error> {
+        let from_status = change.from_status.unwrap_or_default();
+        check_reference(&*self.users.read().await, &change.changed_by, "User")?;
+        let lock_job = self.jobs.read().await;
+        let mut lock_map_resume_job = self.map_resume_job.write().await;
+        let job_id = match lock_map_resume_job.get(&change.application_id) {
+            Some(map_resume_job) => map_resume_job.job_id.clone(),
+            None => return Err(Error::NotFound),
+        };
+        // Counted under the lock, as the database does in the transaction
+        if change.to_status == ApplicationStatus::Hired {
+            let quantity = lock_job.get(&job_id).map_or(0, |job| job.quantity as usize);
+            let hired = lock_map_resume_job
+                .values()
+                .filter(|v| v.job_id == job_id && v.status == ApplicationStatus::Hired)
+                .count();
+            if hired >= quantity {
+                return Err(Error::JobFilled);
+            }
+        }
+        let map_resume_job = lock_map_resume_job.get_mut(&change.application_id).unwrap();
+        if map_resume_job.status != from_status {
+            return Err(Error::InvalidStatusTransition(
+                from_status.to_string(),
+                change.to_status.to_string(),
+            ));
+        }
+        map_resume_job.status = change.to_status;
+        let res = map_resume_job.clone();
+        self.insert_status_change(change).await;
+        Ok(res)
+    }
+
+    async fn get_application_timeline(
+        &self,
+        map_resume_job_id: MapResumeJobId,
+    ) -> Result<Vec<ApplicationStatusChange>, Error> {
+        let mut timeline = self
+            .application_status_history
+            .read()
+            .await
+            .values()
+            .filter(|change| change.application_id == map_resume_job_id)
+            .cloned()
+            .collect::<Vec<_>>();
+        timeline.sort_by_key(|change| (change.changed_at, change.id.as_ref().map(|id| id.0)));
+        Ok(timeline)
+    }
+
     async fn get_list_job_by_resume(
         &self,
         resume_id: ResumeId,
@@ -83,25 +322,26 @@ impl StoreMethods for InMemoryStore {
     }
     async fn get_list_resume_by_job_id(
         &self,
-        limit: Option<i32>,
-        offset: i32,
+        pagination: Pagination,
         job_id: JobId,
-    ) -> Result<Vec<MapResumeJob>, Error> {
-        Ok(self
+    ) -> Result<Page<MapResumeJob>, Error> {
+        let rows = self
             .map_resume_job
             .read()
             .await
-            .iter()
-            .filter_map(|(_k, v)| if v.job_id == job_id { Some(v) } else { None })
-            .skip(offset as usize)
-            .take((limit.unwrap() - offset) as usize)
-            .cloned()
-            .collect::<Vec<_>>())
+            .values()
+            .filter(|v| v.job_id == job_id)
+            .map(|v| (v.clone(), created_cursor(v.id.as_ref().unwrap().0)))
+            .collect::<Vec<_>>();
+        Ok(Page::paginate(rows, &pagination, false))
     }
     //methods for users
 
     async fn create_user(&self, new_user: AuthInfo) -> Result<User, Error> {
         let mut lock_user = self.users.write().await;
+        if lock_user.values().any(|v| v.email == new_user.email) {
+            return Err(Error::AlreadyExists("User".to_string()));
+        }
         let len = lock_user.len() as i32;
         let id: i32 = if len == 0 { 1 } else { len + 1 };
 
@@ -112,6 +352,7 @@ impl StoreMethods for InMemoryStore {
             company_id: CompanyId(0),
             role_id: RoleId(2),
             is_delete: false,
+            email_verified: false,
         };
 
         lock_user.insert(user.id.clone().unwrap(), user.clone());
@@ -146,48 +387,38 @@ impl StoreMethods for InMemoryStore {
             }
         }
     }
-    async fn get_list_user(&self, limit: Option<i32>, offset: i32) -> Result<Vec<User>, Error> {
-        Ok(self
+    async fn get_list_user(&self, pagination: Pagination) -> Result<Page<User>, Error> {
+        let rows = self
             .users
             .read()
             .await
             .values()
-            .skip(offset as usize)
-            .take((limit.unwrap() - offset) as usize)
-            .cloned()
-            .collect::<Vec<_>>())
+            .map(|v| (v.clone(), created_cursor(v.id.as_ref().unwrap().0)))
+            .collect::<Vec<_>>();
+        Ok(Page::paginate(rows, &pagination, false))
     }
     async fn update_user(&self, user_info: UserInfo) -> Result<User, Error> {
-        // Get user from user_info
-        let user = self.get_user_by_id(user_info.id.clone()).await?;
-        let user_update = User {
-            id: Some(user_info.id.clone()),
-            email: user_info.email,
-            hash_password: user.hash_password,
-            company_id: user_info.company_id,
-            role_id: user_info.role_id,
-            is_delete: user_info.is_delete,
-        };
-        match self
-            .users
-            .write()
-            .await
-            .get_mut(&user_update.id.clone().unwrap())
-        {
-            Some(u) => {
-                *u = user_update.clone();
-                Ok(user_update)
+        self.check_company(&user_info.company_id).await?;
+        // Only the company is updated, as in the database, the role and the
+        // deletion have their own routes
+        match self.users.write().await.get_mut(&user_info.id) {
+            Some(user) => {
+                user.company_id = user_info.company_id;
+                Ok(user.clone())
             }
             None => {
                 event!(Level::ERROR, "User update in memory not found");
-                return Err(Error::NotFound);
+                Err(Error::NotFound)
             }
         }
     }
 
     async fn delete_user_by_id(&self, user_id: UserId) -> Result<bool, Error> {
-        match self.users.write().await.remove(&user_id) {
-            Some(_) => Ok(true),
+        match self.users.write().await.get_mut(&user_id) {
+            Some(user) => {
+                user.is_delete = true;
+                Ok(true)
+            }
             None => {
                 event!(Level::ERROR, "User delete in memory not found");
                 return Err(Error::NotFound);
@@ -204,6 +435,7 @@ impl StoreMethods for InMemoryStore {
             company_id: _user.company_id,
             role_id: _user.role_id,
             is_delete: _user.is_delete,
+            email_verified: _user.email_verified,
         };
         match self
             .users
@@ -223,6 +455,7 @@ impl StoreMethods for InMemoryStore {
     }
 
     async fn set_role(&self, user: UserInfo, id_role: RoleId) -> Result<User, Error> {
+        check_reference(&*self.roles.read().await, &id_role, "Role")?;
         let _user = self.get_user_by_email(user.email).await?;
         let user_update = User {
             id: _user.id,
@@ -231,6 +464,7 @@ impl StoreMethods for InMemoryStore {
             company_id: _user.company_id,
             role_id: id_role,
             is_delete: _user.is_delete,
+            email_verified: _user.email_verified,
         };
         match self
             .users
@@ -249,14 +483,280 @@ impl StoreMethods for InMemoryStore {
         }
     }
 
+    async fn set_email_verified(&self, user_id: UserId) -> Result<User, Error> {
+        match self.users.write().await.get_mut(&user_id) {
+            Some(user) => {
+                user.email_verified = true;
+                Ok(user.clone())
+            }
+            None => {
+                event!(Level::ERROR, "User set email verified in memory not found");
+                Err(Error::NotFound)
+            }
+        }
+    }
+
+    // methods for refresh tokens
+    async fn create_refresh_token(
+        &self,
+        new_refresh_token: NewRefreshToken,
+    ) -> Result<RefreshToken, Error> {
+        check_reference(
+            &*self.users.read().await,
+            &new_refresh_token.user_id,
+            "User",
+        )?;
+        let mut lock_refresh_token = self.refresh_tokens.write().await;
+        let len = lock_refresh_token.len() as i32;
+        let id: i32 = if len == 0 { 1 } else { len + 1 };
+        let refresh_token = RefreshToken {
+            id: Some(RefreshTokenId(id)),
+            user_id: new_refresh_token.user_id,
+            token_hash: new_refresh_token.token_hash,
+            family_id: new_refresh_token.family_id,
+            expires_at: new_refresh_token.expires_at,
+            is_used: false,
+            is_revoked: false,
+        };
+        lock_refresh_token.insert(refresh_token.id.clone().unwrap(), refresh_token.clone());
+        Ok(refresh_token)
+    }
+
+    async fn get_refresh_token(&self, token_hash: &str) -> Result<RefreshToken, Error> {
+        match self
+            .refresh_tokens
+            .read()
+            .await
+            .values()
+            .find(|v| v.token_hash == token_hash)
+            .cloned()
+        {
+            Some(refresh_token) => Ok(refresh_token),
+            None => {
+                event!(Level::ERROR, "Get refresh token in memory not found");
+                Err(Error::NotFound)
+            }
+        }
+    }
+
+    async fn mark_refresh_token_used(&self, token_id: RefreshTokenId) -> Result<bool, Error> {
+        match self.refresh_tokens.write().await.get_mut(&token_id) {
+            Some(value) => {
+                // Another request already rotated this token
+                if value.is_used {
+                    return Ok(false);
+                }
+                value.is_used = true;
+                Ok(true)
+            }
+            None => {
+                event!(Level::ERROR, "Refresh token mark used in memory not found");
+                Err(Error::NotFound)
+            }
+        }
+    }
+
+    async fn revoke_refresh_token_family(&self, family_id: &str) -> Result<bool, Error> {
+        self.refresh_tokens
+            .write()
+            .await
+            .values_mut()
+            .filter(|v| v.family_id == family_id)
+            .for_each(|v| v.is_revoked = true);
+        Ok(true)
+    }
+
+    // methods for email tokens
+    async fn create_email_token(
+        &self,
+        new_email_token: NewEmailToken,
+    ) -> Result<EmailToken, Error> {
+        check_reference(&*self.users.read().await, &new_email_token.user_id, "User")?;
+        let mut lock_email_token = self.email_tokens.write().await;
+        let now = Utc::now().timestamp();
+        // Only the last email sent for a purpose holds a usable token
+        lock_email_token
+            .values_mut()
+            .filter(|v| {
+                v.user_id == new_email_token.user_id
+                    && v.purpose == new_email_token.purpose
+                    && v.used_at.is_none()
+            })
+            .for_each(|v| v.used_at = Some(now));
+        let id = EmailTokenId(lock_email_token.len() as i32 + 1);
+        let email_token = EmailToken {
+            id: Some(id.clone()),
+            jti: new_email_token.jti,
+            user_id: new_email_token.user_id,
+            purpose: new_email_token.purpose,
+            expires_at: new_email_token.expires_at,
+            used_at: None,
+        };
+        lock_email_token.insert(id, email_token.clone());
+        Ok(email_token)
+    }
+
+    async fn use_email_token(
+        &self,
+        jti: &str,
+        purpose: EmailTokenPurpose,
+    ) -> Result<EmailToken, Error> {
+        let now = Utc::now().timestamp();
+        match self.email_tokens.write().await.values_mut().find(|v| {
+            v.jti == jti && v.purpose == purpose && v.used_at.is_none() && v.expires_at > now
+        }) {
+            Some(email_token) => {
+                email_token.used_at = Some(now);
+                Ok(email_token.clone())
+            }
+            None => Err(Error::InvalidEmailToken),
+        }
+    }
+
+    // methods for login attempts
+    async fn get_login_attempt(&self, subject: &str) -> Result<Option<LoginAttempt>, Error> {
+        Ok(self.login_attempts.read().await.get(subject).cloned())
+    }
+
+    async fn reserve_login(
+        &self,
+        subject: &str,
+        now: i64,
+        window_start: i64,
+        penalty: LoginPenalty<'_>,
+    ) -> Result<LoginAttempt, Error> {
+        let mut lock_login_attempt = self.login_attempts.write().await;
+        let attempt = lock_login_attempt
+            .entry(subject.to_string())
+            .or_insert(LoginAttempt::new(subject, now));
+        attempt.reserve(now, window_start, penalty)?;
+        Ok(attempt.clone())
+    }
+
+    async fn release_login(
+        &self,
+        reserved: &LoginAttempt,
+        now: i64,
+    ) -> Result<Option<LoginAttempt>, Error> {
+        Ok(self
+            .login_attempts
+            .write()
+            .await
+            .get_mut(&reserved.subject)
+            .map(|attempt| {
+                attempt.release(reserved, now);
+                attempt.clone()
+            }))
+    }
+
+    async fn clear_login_attempts(&self, subject: &str) -> Result<bool, Error> {
+        Ok(self.login_attempts.write().await.remove(subject).is_some())
+    }
+
+    async fn create_audit_entry(&self, new_entry: NewAuditEntry) -> Result<AuditEntry, Error> {
+        let mut lock_audit_log = self.audit_log.write().await;
+        let entry_id = AuditEntryId(lock_audit_log.len() as i32 + 1);
+        let entry = AuditEntry {
+            id: Some(entry_id.clone()),
+            actor_id: new_entry.actor_id,
+            action: new_entry.action,
+            entity_type: new_entry.entity_type,
+            entity_id: new_entry.entity_id,
+            before: new_entry.before,
+            after: new_entry.after,
+            created_at: new_entry.created_at,
+        };
+        lock_audit_log.insert(entry_id, entry.clone());
+        Ok(entry)
+    }
+
+    async fn get_list_audit_entries(&self, filter: AuditFilter) -> Result<Page<AuditEntry>, Error> {
+        // Ids grow with time, so the id is the key of the cursor
+        let entries = self
+            .audit_log
+            .read()
+            .await
+            .values()
+            .filter(|entry| filter.matches(entry))
+            .map(|entry| {
+                let id = entry.id.as_ref().unwrap().0;
+                (entry.clone(), Cursor { key: id as i64, id })
+            })
+            .collect::<Vec<_>>();
+        Ok(Page::paginate(entries, &filter.pagination, true))
+    }
+
+    // methods for access token revocation
+    async fn revoke_access_token(
+        &self,
+        jti: &str,
+        user_id: UserId,
+        expires_at: i64,
+    ) -> Result<bool, Error> {
+        check_reference(&*self.users.read().await, &user_id, "User")?;
+        let mut lock_revoked_token = self.revoked_tokens.write().await;
+        // Entries of expired tokens are not needed anymore
+        let now = Utc::now().timestamp();
+        lock_revoked_token.retain(|_k, v| v.expires_at >= now);
+        lock_revoked_token.insert(
+            jti.to_string(),
+            RevokedToken {
+                jti: jti.to_string(),
+                user_id,
+                expires_at,
+            },
+        );
+        Ok(true)
+    }
+
+    async fn revoke_user_tokens(&self, user_id: UserId) -> Result<i32, Error> {
+        check_reference(&*self.users.read().await, &user_id, "User")?;
+        self.refresh_tokens
+            .write()
+            .await
+            .values_mut()
+            .filter(|v| v.user_id == user_id)
+            .for_each(|v| v.is_revoked = true);
+        let mut lock_token_version = self.user_token_versions.write().await;
+        let token_version = lock_token_version.entry(user_id).or_insert(0);
+        *token_version += 1;
+        Ok(*token_version)
+    }
+
+    async fn get_user_token_version(&self, user_id: UserId) -> Result<i32, Error> {
+        Ok(self
+            .user_token_versions
+            .read()
+            .await
+            .get(&user_id)
+            .cloned()
+            .unwrap_or(0))
+    }
+
+    async fn is_access_token_revoked(
+        &self,
+        jti: &str,
+        user_id: UserId,
+        token_version: i32,
+    ) -> Result<bool, Error> {
+        if self.revoked_tokens.read().await.contains_key(jti) {
+            return Ok(true);
+        }
+        Ok(self.get_user_token_version(user_id).await? > token_version)
+    }
+
     // methods for role
     async fn create_role(&self, new_role: RoleInfo) -> Result<Role, Error> {
         let mut lock_role = self.roles.write().await;
+        if let Some(inherits_role_id) = &new_role.inherits_role_id {
+            check_reference(&*lock_role, inherits_role_id, "Role")?;
+        }
         let len = lock_role.len() as i32;
         let id: i32 = if len == 0 { 1 } else { len + 1 };
         let role = Role {
             id: Some(RoleId(id)),
             role: new_role.role,
+            inherits_role_id: new_role.inherits_role_id,
             is_delete: false,
         };
 
@@ -285,7 +785,11 @@ impl StoreMethods for InMemoryStore {
     }
 
     async fn update_role(&self, role: Role) -> Result<Role, Error> {
-        match self.roles.write().await.get_mut(&role.id.clone().unwrap()) {
+        let mut lock_role = self.roles.write().await;
+        if let Some(inherits_role_id) = &role.inherits_role_id {
+            check_reference(&*lock_role, inherits_role_id, "Role")?;
+        }
+        match lock_role.get_mut(&role.id.clone().unwrap()) {
             Some(value) => {
                 *value = role.clone();
                 Ok(role)
@@ -298,8 +802,11 @@ impl StoreMethods for InMemoryStore {
     }
 
     async fn delete_role(&self, role_id: RoleId) -> Result<bool, Error> {
-        match self.roles.write().await.remove(&role_id) {
-            Some(_) => Ok(true),
+        match self.roles.write().await.get_mut(&role_id) {
+            Some(role) => {
+                role.is_delete = true;
+                Ok(true)
+            }
             None => {
                 event!(Level::ERROR, "Role delete in memory not found");
                 return Err(Error::NotFound);
@@ -307,9 +814,75 @@ impl StoreMethods for InMemoryStore {
         }
     }
 
+    async fn get_role_chain(&self, role_id: RoleId) -> Result<Vec<Role>, Error> {
+        let lock_role = self.roles.read().await;
+        let mut chain: Vec<Role> = Vec::new();
+        let mut next = Some(role_id);
+        while let Some(id) = next {
+            // stop on a deleted role or a cycle in the hierarchy
+            let role = match lock_role.get(&id) {
+                Some(role) if !role.is_delete && !chain.iter().any(|r| r.id == role.id) => role,
+                _ => break,
+            };
+            next = role.inherits_role_id.clone();
+            chain.push(role.clone());
+        }
+        Ok(chain)
+    }
+
+    async fn get_role_permissions(&self, role_id: RoleId) -> Result<Vec<Permission>, Error> {
+        let chain = self.get_role_chain(role_id).await?;
+        let lock_permission = self.role_permissions.read().await;
+        let mut permissions: Vec<Permission> = Vec::new();
+        for role in chain {
+            for permission in lock_permission
+                .get(&role.id.unwrap())
+                .cloned()
+                .unwrap_or_default()
+            {
+                if !permissions.contains(&permission) {
+                    permissions.push(permission);
+                }
+            }
+        }
+        Ok(permissions)
+    }
+
+    async fn set_role_permissions(
+        &self,
+        role_permissions: RolePermissions,
+    ) -> Result<RolePermissions, Error> {
+        if !self
+            .roles
+            .read()
+            .await
+            .contains_key(&role_permissions.role_id)
+        {
+            event!(Level::ERROR, "Role set permissions in memory not found");
+            return Err(Error::NotFound);
+        }
+        let mut permissions: Vec<Permission> = Vec::new();
+        for permission in role_permissions.permissions {
+            if !permissions.contains(&permission) {
+                permissions.push(permission);
+            }
+        }
+        self.role_permissions
+            .write()
+            .await
+            .insert(role_permissions.role_id.clone(), permissions.clone());
+        Ok(RolePermissions {
+            role_id: role_permissions.role_id,
+            permissions,
+        })
+    }
+
     // methods for company
     async fn create_company(&self, new_company: NewCompany) -> Result<Company, Error> {
         let mut lock_company = self.companies.write().await;
+        if lock_company.values().any(|v| v.email == new_company.email) {
+            return Err(Error::AlreadyExists("Company".to_string()));
+        }
         let len = lock_company.len() as i32;
         let id: i32 = if len == 0 { 1 } else { len + 1 };
         let company = Company {
@@ -358,29 +931,26 @@ impl StoreMethods for InMemoryStore {
         }
     }
 
-    async fn get_list_company(
-        &self,
-        limit: Option<i32>,
-        offset: i32,
-    ) -> Result<Vec<Company>, Error> {
-        Ok(self
+    async fn get_list_company(&self, pagination: Pagination) -> Result<Page<Company>, Error> {
+        let rows = self
             .companies
             .read()
             .await
             .values()
-            .skip(offset as usize)
-            .take((limit.unwrap() - offset) as usize)
-            .cloned()
-            .collect::<Vec<_>>())
+            .map(|v| (v.clone(), created_cursor(v.id.as_ref().unwrap().0)))
+            .collect::<Vec<_>>();
+        Ok(Page::paginate(rows, &pagination, false))
     }
 
     async fn update_company(&self, company: Company) -> Result<Company, Error> {
-        match self
-            .companies
-            .write()
-            .await
-            .get_mut(&company.id.clone().unwrap())
+        let mut lock_company = self.companies.write().await;
+        if lock_company
+            .values()
+            .any(|v| v.email == company.email && v.id != company.id)
         {
+            return Err(Error::AlreadyExists("Company".to_string()));
+        }
+        match lock_company.get_mut(&company.id.clone().unwrap()) {
             Some(value) => {
                 *value = company.clone();
                 Ok(company)
@@ -393,8 +963,11 @@ impl StoreMethods for InMemoryStore {
     }
 
     async fn delete_company(&self, company_id: CompanyId) -> Result<bool, Error> {
-        match self.companies.write().await.remove(&company_id) {
-            Some(_) => Ok(true),
+        match self.companies.write().await.get_mut(&company_id) {
+            Some(company) => {
+                company.is_delete = true;
+                Ok(true)
+            }
             None => {
                 event!(Level::ERROR, "Company delete in memory not found");
                 return Err(Error::NotFound);
@@ -403,6 +976,11 @@ impl StoreMethods for InMemoryStore {
     }
     // methods for job
     async fn create_job(&self, new_job: NewJob) -> Result<Job, Error> {
+        check_reference(
+            &*self.companies.read().await,
+            &new_job.company_id,
+            "Company",
+        )?;
         let mut lock_job = self.jobs.write().await;
         let len = lock_job.len() as i32;
         let id: i32 = if len == 0 { 1 } else { len + 1 };
@@ -416,8 +994,15 @@ impl StoreMethods for InMemoryStore {
             job_level: new_job.job_level,
             description: new_job.description,
             is_delete: false,
+            status: new_job.status,
+            published_at: match new_job.status {
+                JobStatus::Published => Some(Utc::now().timestamp()),
+                _ => None,
+            },
+            expires_at: new_job.expires_at,
         };
         lock_job.insert(job.id.clone().unwrap(), job.clone());
+        self.index_job(&job).await;
         Ok(job)
     }
 
@@ -431,22 +1016,84 @@ impl StoreMethods for InMemoryStore {
         }
     }
 
-    async fn get_list_job(&self, limit: Option<i32>, offset: i32) -> Result<Vec<Job>, Error> {
-        Ok(self
+    async fn search_jobs(&self, search: JobSearch) -> Result<JobSearchResult, Error> {
+        // Every token of the query has to be indexed for the job. Words are matched
+        // exactly, so "developers" doesn't find "developer" as websearch_to_tsquery
+        // does in the database, and its or and minus operators are not supported
+        let matched_ids: Option<HashSet<JobId>> = match &search.q {
+            Some(q) => {
+                let lock_token = self.job_tokens.read().await;
+                let mut matched: Option<HashSet<JobId>> = None;
+                for token in tokenize(q) {
+                    let ids = lock_token.get(&token).cloned().unwrap_or_default();
+                    matched = Some(match matched {
+                        Some(matched) => matched.intersection(&ids).cloned().collect(),
+                        None => ids,
+                    });
+                }
+                Some(matched.unwrap_or_default())
+            }
+            None => None,
+        };
+        let location = search.location.as_ref().map(|l| l.to_lowercase());
+        let now = Utc::now().timestamp();
+        let jobs = self
             .jobs
             .read()
             .await
             .values()
-            .skip(offset as usize)
-            .take((limit.unwrap() - offset) as usize)
-            .cloned()
-            .collect::<Vec<_>>())
+            .filter(|job| job.is_open(now))
+            .filter(|job| match &matched_ids {
+                Some(ids) => ids.contains(job.id.as_ref().unwrap()),
+                None => true,
+            })
+            .filter(|job| match &location {
+                Some(location) => job.location.to_lowercase().contains(location),
+                None => true,
+            })
+            .filter(|job| match &search.job_level {
+                Some(job_level) => job.job_level.eq_ignore_ascii_case(job_level),
+                None => true,
+            })
+            .filter(|job| match &search.company_id {
+                Some(company_id) => &job.company_id == company_id,
+                None => true,
+            })
+            .filter(|job| search.min_salary.is_none_or(|min| job.salary >= min))
+            .filter(|job| search.max_salary.is_none_or(|max| job.salary <= max))
+            .map(|job| {
+                let id = job.id.as_ref().unwrap().0;
+                let cursor = match search.sort {
+                    JobSortField::Salary => Cursor {
+                        key: job.salary as i64,
+                        id,
+                    },
+                    JobSortField::CreatedOn => created_cursor(id),
+                };
+                (job.clone(), cursor)
+            })
+            .collect::<Vec<_>>();
+        let total = jobs.len() as i64;
+        Ok(JobSearchResult {
+            jobs: Page::paginate(jobs, &search.pagination, search.order == SortOrder::Desc),
+            total,
+        })
     }
 
     async fn update_job(&self, job: Job) -> Result<Job, Error> {
         match self.jobs.write().await.get_mut(&job.id.clone().unwrap()) {
             Some(value) => {
+                self.unindex_job(value).await;
+                // The job stays with the company posting it, as in the database
+                let job = Job {
+                    company_id: value.company_id.clone(),
+                    is_delete: value.is_delete,
+                    status: value.status,
+                    published_at: value.published_at,
+                    ..job
+                };
                 *value = job.clone();
+                self.index_job(&job).await;
                 Ok(job)
             }
             None => {
@@ -457,16 +1104,111 @@ impl StoreMethods for InMemoryStore {
     }
 
     async fn delete_job(&self, job_id: JobId) -> Result<bool, Error> {
-        match self.jobs.write().await.remove(&job_id) {
-            Some(_) => Ok(true),
+        match self.jobs.write().await.get_mut(&job_id) {
+            Some(job) => {
+                job.is_delete = true;
+                Ok(true)
+            }
             None => {
                 event!(Level::ERROR, "Job delete in memory not found");
                 return Err(Error::NotFound);
             }
         }
     }
+
+    async fn update_job_status(
+        &self,
+        job_id: JobId,
+        from_status: JobStatus,
+        to_status: JobStatus,
+        now: i64,
+    ) -> Result<Job, Error> {
+        let mut lock_job = self.jobs.write().await;
+        let job = match lock_job.get_mut(&job_id) {
+            Some(job) => job,
+            None => return Err(Error::NotFound),
+        };
+        if job.status != from_status {
+            return Err(Error::InvalidStatusTransition(
+                from_status.to_string(),
+                to_status.to_string(),
+            ));
+        }
+        job.status = to_status;
+        if to_status == JobStatus::Published && job.published_at.is_none() {
+            job.published_at = Some(now);
+        }
+        Ok(job.clone())
+    }
+
+    async fn expire_jobs(&self, now: i64) -> Result<Vec<Job>, Error> {
+        let mut expired = Vec::new();
+        for job in self.jobs.write().await.values_mut() {
+            if matches!(job.status, JobStatus::Published | JobStatus::Paused)
+                && job.expires_at.is_some_and(|expires_at| expires_at <= now)
+            {
+                job.status = JobStatus::Expired;
+                expired.push(job.clone());
+            }
+        }
+        Ok(expired)
+    }
+
+    async fn get_recommendation_jobs(
+        &self,
+        user_id: UserId,
+        skill_words: Vec<String>,
+        limit: i64,
+    ) -> Result<Vec<Job>, Error> {
+        let lock_resume = self.resumes.read().await;
+        let applied: HashSet<JobId> = self
+            .map_resume_job
+            .read()
+            .await
+            .values()
+            .filter(|v| {
+                lock_resume
+                    .get(&v.resume_id)
+                    .is_some_and(|resume| resume.user_id == user_id)
+            })
+            .map(|v| v.job_id.clone())
+            .collect();
+        // Number of the skill words indexed for each job, the newest open jobs
+        // are taken when there is no skill word
+        let mut matched: HashMap<JobId, usize> = HashMap::new();
+        let lock_token = self.job_tokens.read().await;
+        for word in &skill_words {
+            for id in lock_token.get(word).into_iter().flatten() {
+                *matched.entry(id.clone()).or_default() += 1;
+            }
+        }
+        let now = Utc::now().timestamp();
+        let mut jobs: Vec<(usize, Job)> = self
+            .jobs
+            .read()
+            .await
+            .values()
+            .filter(|job| job.is_open(now) && !applied.contains(job.id.as_ref().unwrap()))
+            .filter_map(|job| match matched.get(job.id.as_ref().unwrap()) {
+                Some(count) => Some((*count, job.clone())),
+                None if skill_words.is_empty() => Some((0, job.clone())),
+                None => None,
+            })
+            .collect();
+        jobs.sort_by(|(a_count, a), (b_count, b)| {
+            b_count
+                .cmp(a_count)
+                .then_with(|| b.id.as_ref().unwrap().0.cmp(&a.id.as_ref().unwrap().0))
+        });
+        Ok(jobs
+            .into_iter()
+            .take(limit as usize)
+            .map(|(_, job)| job)
+            .collect())
+    }
     //methods for resume
     async fn create_resume(&self, new_resume: NewResume) -> Result<Resume, Error> {
+        check_reference(&*self.users.read().await, &new_resume.user_id, "User")?;
         let mut lock_resume = self.resumes.write().await;
         let len = lock_resume.len() as i32;
         let id: i32 = if len == 0 { 1 } else { len + 1 };
@@ -476,6 +1218,9 @@ impl StoreMethods for InMemoryStore {
             email: new_resume.email,
             url: new_resume.url,
             is_delete: false,
+            file: new_resume.file,
+            is_searchable: new_resume.is_searchable,
+            profile: new_resume.profile,
         };
         lock_resume.insert(resume.id.clone().unwrap(), resume.clone());
         Ok(resume)
@@ -493,23 +1238,116 @@ impl StoreMethods for InMemoryStore {
 
     async fn get_list_resume_by_user_id(
         &self,
-        limit: Option<i32>,
-        offset: i32,
+        pagination: Pagination,
         user_id: UserId,
+    ) -> Result<Page<Resume>, Error> {
+        let rows = self
+            .resumes
+            .read()
+            .await
+            .values()
+            .filter(|v| v.user_id == user_id)
+            .map(|v| (v.clone(), created_cursor(v.id.as_ref().unwrap().0)))
+            .collect::<Vec<_>>();
+        Ok(Page::paginate(rows, &pagination, false))
+    }
+
+    async fn search_resumes(&self, search: ResumeSearch) -> Result<ResumeSearchResult, Error> {
+        let query_tokens = search.q.as_deref().map(tokenize).unwrap_or_default();
+        let location = search.location.as_ref().map(|l| l.to_lowercase());
+        let resumes = self
+            .resumes
+            .read()
+            .await
+            .values()
+            .filter(|resume| !resume.is_delete && resume.is_searchable)
+            .filter(|resume| match &location {
+                Some(location) => resume.profile.location.to_lowercase().contains(location),
+                None => true,
+            })
+            .filter(|resume| {
+                search.skills.iter().all(|skill| {
+                    resume
+                        .profile
+                        .skills
+                        .iter()
+                        .any(|own| own.to_lowercase() == *skill)
+                })
+            })
+            .filter(|resume| {
+                let years = resume.profile.years_of_experience;
+                search.min_experience.is_none_or(|min| years >= min)
+                    && search.max_experience.is_none_or(|max| years <= max)
+            })
+            .filter_map(|resume| {
+                // Every token of the query has to be in the text, the relevance
+                // is the number of times they appear
+                let text = match &resume.file {
+                    Some(file) => format!("{} {}", resume.profile.headline, file.text),
+                    None => resume.profile.headline.clone(),
+                };
+                let tokens = tokenize(&text);
+                let mut relevance = 0;
+                for query_token in &query_tokens {
+                    let count = tokens.iter().filter(|token| *token == query_token).count();
+                    if count == 0 {
+                        return None;
+                    }
+                    relevance += count as i64;
+                }
+                let cursor = Cursor {
+                    key: rank(relevance, resume.profile.years_of_experience),
+                    id: resume.id.as_ref().unwrap().0,
+                };
+                Some((resume.clone(), cursor))
+            })
+            .collect::<Vec<_>>();
+        let total = resumes.len() as i64;
+        Ok(ResumeSearchResult {
+            resumes: Page::paginate(resumes, &search.pagination, true),
+            total,
+        })
+    }
+
+    async fn get_recommendation_resumes(
+        &self,
+        job_words: Vec<String>,
+        limit: i64,
     ) -> Result<Vec<Resume>, Error> {
-        Ok(self
+        let job_words: HashSet<String> = job_words.into_iter().collect();
+        let mut resumes: Vec<(usize, Resume)> = self
             .resumes
             .read()
             .await
-            .iter()
-            .filter_map(|(_k, v)| if v.user_id == user_id { Some(v) } else { None })
-            .skip(offset as usize)
-            .take((limit.unwrap() - offset) as usize)
-            .cloned()
-            .collect::<Vec<_>>())
+            .values()
+            .filter(|resume| !resume.is_delete && resume.is_searchable)
+            .filter_map(|resume| {
+                let shared = skill_words(&resume.profile.skills)
+                    .iter()
+                    .filter(|word| job_words.contains(*word))
+                    .count();
+                let mut resume = resume.clone();
+                // As the database, the text of the file isn't loaded
+                if let Some(file) = resume.file.as_mut() {
+                    file.text.clear();
+                }
+                (shared > 0).then_some((shared, resume))
+            })
+            .collect();
+        resumes.sort_by(|(a_shared, a), (b_shared, b)| {
+            b_shared
+                .cmp(a_shared)
+                .then_with(|| b.id.as_ref().unwrap().0.cmp(&a.id.as_ref().unwrap().0))
+        });
+        Ok(resumes
+            .into_iter()
+            .take(limit as usize)
+            .map(|(_, resume)| resume)
+            .collect())
     }
 
     async fn update_resume(&self, resume: Resume) -> Result<Resume, Error> {
+        check_reference(&*self.users.read().await, &resume.user_id, "User")?;
         match self
             .resumes
             .write()
@@ -528,8 +1366,11 @@ impl StoreMethods for InMemoryStore {
     }
 
     async fn delete_resume(&self, resume_id: ResumeId) -> Result<bool, Error> {
-        match self.resumes.write().await.remove(&resume_id) {
-            Some(_) => Ok(true),
+        match self.resumes.write().await.get_mut(&resume_id) {
+            Some(resume) => {
+                resume.is_delete = true;
+                Ok(true)
+            }
             None => {
                 event!(Level::ERROR, "Resume delete in memory not found");
                 return Err(Error::NotFound);
@@ -537,3 +1378,379 @@ impl StoreMethods for InMemoryStore {
         }
     }
 }
+
+// Ids grow with creation time, so they stand in for created_on in the cursor key
+fn created_cursor(id: i32) -> Cursor {
+    Cursor { key: id as i64, id }
+}
+
+// Same check as the foreign keys of the database schema
+fn check_reference<K: Eq + Hash, V>(
+    map: &HashMap<K, V>,
+    key: &K,
+    subject: &str,
+) -> Result<(), Error> {
+    match map.contains_key(key) {
+        true => Ok(()),
+        false => Err(Error::MissingReference(subject.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod store_in_memory_tests {
+    use super::InMemoryStore;
+    use crate::errors::Error;
+    use crate::models::application::{ApplicationStatus, NewApplicationStatusChange};
+    use crate::models::company::{CompanyId, NewCompany};
+    use crate::models::job::{new_job_fixture, JobId, JobStatus, NewJob};
+    use crate::models::job_search::JobSearch;
+    use crate::models::map_resume_job::{MapResumeJob, NewMapResumeJob};
+    use crate::models::resume::{NewResume, ResumeId, ResumeProfile};
+    use crate::models::resume_search::ResumeSearch;
+    use crate::models::role::{RoleId, ADMIN_ROLE_ID};
+    use crate::models::store_trait::StoreMethods;
+    use crate::models::user::{AuthInfo, User, UserId, UserInfo};
+
+    fn new_company(email: &str) -> NewCompany {
+        NewCompany {
+            name: "Company".to_string(),
+            email: email.to_string(),
+            address: "So 4 Nguyen Trai".to_string(),
+            description: "Web".to_string(),
+        }
+    }
+
+    fn new_job(company_id: i32) -> NewJob {
+        NewJob {
+            company_id: CompanyId(company_id),
+            ..new_job_fixture()
+        }
+    }
+
+    fn assert_error(result: Result<impl std::fmt::Debug, Error>, expect: Error) {
+        assert_eq!(result.unwrap_err().to_string(), expect.to_string());
+    }
+
+    #[tokio::test]
+    async fn unique_constraints() {
+        let store = InMemoryStore::new();
+        let user = AuthInfo {
+            email: "user1@gmail.com".to_string(),
+            hash_password: "123456".to_string(),
+        };
+        store.create_user(user.clone()).await.unwrap();
+        assert_error(
+            store.create_user(user).await,
+            Error::AlreadyExists("User".to_string()),
+        );
+
+        store
+            .create_company(new_company("12next@gmail.com"))
+            .await
+            .unwrap();
+        assert_error(
+            store.create_company(new_company("12next@gmail.com")).await,
+            Error::AlreadyExists("Company".to_string()),
+        );
+    }
+
+    #[tokio::test]
+    async fn update_user_changes_only_company() {
+        let store = InMemoryStore::new();
+        store
+            .create_company(new_company("12next@gmail.com"))
+            .await
+            .unwrap();
+        let user = store
+            .create_user(AuthInfo {
+                email: "user1@gmail.com".to_string(),
+                hash_password: "123456".to_string(),
+            })
+            .await
+            .unwrap();
+        let updated = store
+            .update_user(UserInfo {
+                id: user.id.clone().unwrap(),
+                email: "other@gmail.com".to_string(),
+                company_id: CompanyId(1),
+                role_id: RoleId(ADMIN_ROLE_ID),
+                is_delete: true,
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            updated,
+            User {
+                company_id: CompanyId(1),
+                ..user
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn foreign_key_constraints() {
+        let store = InMemoryStore::new();
+        assert_error(
+            store.create_job(new_job(1)).await,
+            Error::MissingReference("Company".to_string()),
+        );
+        assert_error(
+            store
+                .create_resume(NewResume {
+                    user_id: UserId(1),
+                    email: "user1@gmail.com".to_string(),
+                    url: "abcxyz".to_string(),
+                    file: None,
+                    is_searchable: false,
+                    profile: ResumeProfile::default(),
+                })
+                .await,
+            Error::MissingReference("User".to_string()),
+        );
+
+        let user = store
+            .create_user(AuthInfo {
+                email: "user1@gmail.com".to_string(),
+                hash_password: "123456".to_string(),
+            })
+            .await
+            .unwrap();
+        let user_id = user.id.unwrap();
+        store
+            .create_company(new_company("12next@gmail.com"))
+            .await
+            .unwrap();
+        store.create_job(new_job(1)).await.unwrap();
+        store
+            .create_resume(NewResume {
+                user_id: user_id.clone(),
+                email: "user1@gmail.com".to_string(),
+                url: "abcxyz".to_string(),
+                file: None,
+                is_searchable: false,
+                profile: ResumeProfile::default(),
+            })
+            .await
+            .unwrap();
+        let application = NewMapResumeJob {
+            resume_id: ResumeId(1),
+            job_id: JobId(1),
+        };
+        store
+            .create_map_job_resume(application.clone(), user_id.clone())
+            .await
+            .unwrap();
+        assert_error(
+            store
+                .create_map_job_resume(application, user_id.clone())
+                .await,
+            Error::AlreadyExists("Application".to_string()),
+        );
+        assert_error(
+            store
+                .create_map_job_resume(
+                    NewMapResumeJob {
+                        resume_id: ResumeId(1),
+                        job_id: JobId(2),
+                    },
+                    user_id,
+                )
+                .await,
+            Error::MissingReference("Job".to_string()),
+        );
+
+        // Deleted rows are still referenced, so they are only marked as deleted
+        store.delete_company(CompanyId(1)).await.unwrap();
+        assert!(
+            store
+                .get_company_by_id(CompanyId(1))
+                .await
+                .unwrap()
+                .is_delete
+        );
+        store.create_job(new_job(1)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn hire_is_refused_once_job_is_filled() {
+        let store = InMemoryStore::new();
+        store
+            .create_company(new_company("12next@gmail.com"))
+            .await
+            .unwrap();
+        store
+            .create_job(NewJob {
+                quantity: 1,
+                ..new_job(1)
+            })
+            .await
+            .unwrap();
+        let mut applications = Vec::new();
+        for email in ["user1@gmail.com", "user2@gmail.com"] {
+            let user_id = store
+                .create_user(AuthInfo {
+                    email: email.to_string(),
+                    hash_password: "123456".to_string(),
+                })
+                .await
+                .unwrap()
+                .id
+                .unwrap();
+            let resume = store
+                .create_resume(NewResume {
+                    user_id: user_id.clone(),
+                    email: email.to_string(),
+                    url: "abcxyz".to_string(),
+                    file: None,
+                    is_searchable: false,
+                    profile: ResumeProfile::default(),
+                })
+                .await
+                .unwrap();
+            let application = NewMapResumeJob {
+                resume_id: resume.id.unwrap(),
+                job_id: JobId(1),
+            };
+            applications.push(
+                store
+                    .create_map_job_resume(application, user_id)
+                    .await
+                    .unwrap(),
+            );
+        }
+        let hire = |application: &MapResumeJob| NewApplicationStatusChange {
+            application_id: application.id.clone().unwrap(),
+            from_status: Some(ApplicationStatus::Applied),
+            to_status: ApplicationStatus::Hired,
+            changed_by: UserId(1),
+            note: None,
+        };
+        store
+            .update_application_status(hire(&applications[0]))
+            .await
+            .unwrap();
+        assert_error(
+            store
+                .update_application_status(hire(&applications[1]))
+                .await,
+            Error::JobFilled,
+        );
+    }
+
+    #[tokio::test]
+    async fn resume_search_ranks_searchable_resumes() {
+        let store = InMemoryStore::new();
+        let user_id = store
+            .create_user(AuthInfo {
+                email: "user1@gmail.com".to_string(),
+                hash_password: "123456".to_string(),
+            })
+            .await
+            .unwrap()
+            .id
+            .unwrap();
+        let resume = |headline: &str, years_of_experience: i32, is_searchable: bool| NewResume {
+            user_id: user_id.clone(),
+            email: "user1@gmail.com".to_string(),
+            url: "https://cv.example.com/user1.pdf".to_string(),
+            file: None,
+            profile: ResumeProfile {
+                headline: headline.to_string(),
+                location: "Ha Noi".to_string(),
+                skills: vec!["Rust".to_string()],
+                years_of_experience,
+                ..ResumeProfile::default()
+            },
+            is_searchable,
+        };
+        for new_resume in [
+            resume("Rust developer", 8, true),
+            resume("Rust developer, rust mentor", 2, true),
+            resume("Rust developer", 10, false),
+            resume("Go developer", 5, true),
+        ] {
+            store.create_resume(new_resume).await.unwrap();
+        }
+
+        let search = ResumeSearch {
+            q: Some("rust".to_string()),
+            skills: vec!["rust".to_string()],
+            location: Some("ha noi".to_string()),
+            ..ResumeSearch::default()
+        };
+        let result = store.search_resumes(search).await.unwrap();
+        let ids: Vec<i32> = result
+            .resumes
+            .items
+            .iter()
+            .map(|resume| resume.id.clone().unwrap().0)
+            .collect();
+        // More mentions of the keyword rank first, the hidden resume is never listed
+        assert_eq!((ids, result.total), (vec![2, 1], 2));
+
+        let search = ResumeSearch {
+            min_experience: Some(3),
+            ..ResumeSearch::default()
+        };
+        let result = store.search_resumes(search).await.unwrap();
+        assert_eq!(result.total, 2);
+        assert_eq!(result.resumes.items[0].profile.years_of_experience, 8);
+    }
+
+    #[tokio::test]
+    async fn job_lifecycle() {
+        let store = InMemoryStore::new();
+        store
+            .create_company(new_company("12next@gmail.com"))
+            .await
+            .unwrap();
+        let draft = store
+            .create_job(NewJob {
+                status: JobStatus::Draft,
+                ..new_job(1)
+            })
+            .await
+            .unwrap();
+        let draft_id = draft.id.unwrap();
+        assert_eq!(draft.published_at, None);
+        store
+            .create_job(NewJob {
+                expires_at: Some(1_000),
+                ..new_job(1)
+            })
+            .await
+            .unwrap();
+        // The draft isn't listed and the other job is past its expiry date
+        let listed = store.search_jobs(JobSearch::default()).await.unwrap();
+        assert_eq!(listed.total, 0);
+
+        let job = store
+            .update_job_status(
+                draft_id.clone(),
+                JobStatus::Draft,
+                JobStatus::Published,
+                900,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            (job.status, job.published_at),
+            (JobStatus::Published, Some(900))
+        );
+        let listed = store.search_jobs(JobSearch::default()).await.unwrap();
+        assert_eq!(listed.jobs.items, vec![job]);
+        assert_error(
+            store
+                .update_job_status(draft_id, JobStatus::Draft, JobStatus::Closed, 900)
+                .await,
+            Error::InvalidStatusTransition("draft".to_string(), "closed".to_string()),
+        );
+
+        let expired = store.expire_jobs(1_500).await.unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(
+            (expired[0].id.clone(), expired[0].status),
+            (Some(JobId(2)), JobStatus::Expired)
+        );
+    }
+}
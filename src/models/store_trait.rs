@@ -3,39 +3,143 @@ use std::fmt;
 use async_trait::async_trait;
 
 use crate::errors::Error;
+use crate::models::application::{ApplicationStatusChange, NewApplicationStatusChange};
+use crate::models::audit::{AuditEntry, AuditFilter, NewAuditEntry};
 use crate::models::company::{Company, CompanyId, NewCompany};
-use crate::models::job::{Job, JobId, NewJob};
-use crate::models::map_resume_job::{MapResumeJob, NewMapResumeJob};
+use crate::models::email_token::{EmailToken, EmailTokenPurpose, NewEmailToken};
+use crate::models::health::PoolStatus;
+use crate::models::job::{Job, JobId, JobStatus, NewJob};
+use crate::models::job_search::{JobSearch, JobSearchResult};
+use crate::models::login_attempt::{LoginAttempt, LoginPenalty};
+use crate::models::map_resume_job::{MapResumeJob, MapResumeJobId, NewMapResumeJob};
+use crate::models::pagination::{Page, Pagination};
+use crate::models::permission::Permission;
+use crate::models::refresh_token::{NewRefreshToken, RefreshToken, RefreshTokenId};
 use crate::models::resume::{NewResume, Resume, ResumeId};
-use crate::models::role::{Role, RoleId, RoleInfo};
+use crate::models::resume_search::{ResumeSearch, ResumeSearchResult};
+use crate::models::role::{Role, RoleId, RoleInfo, RolePermissions};
 use crate::models::user::{AuthInfo, User, UserId, UserInfo};
 
 #[async_trait]
 pub trait StoreMethods: fmt::Debug + Send + Sync {
+    // methods for health
+    // Run a trivial query, fails when the store can't be reached
+    async fn ping(&self) -> Result<(), Error>;
+    // Versions of the migrations of the service which are not applied to the store
+    async fn pending_migrations(&self) -> Result<Vec<i64>, Error>;
+    // Connections of the pool, empty for stores without a pool
+    fn pool_status(&self) -> Option<PoolStatus>;
+    // Close the connections of the pool once the server stopped
+    async fn close(&self);
+
     // methods for map resume job
+    // Create the application with status applied and the first entry of its timeline,
+    // rejected when the user already applied to the job or the job hired its quantity
     async fn create_map_job_resume(
         &self,
         new_map_resume_job: NewMapResumeJob,
+        applied_by: UserId,
+    ) -> Result<MapResumeJob, Error>;
+    // Check whether any resume of the user was applied to the job
+    async fn has_applied_job(&self, user_id: UserId, job_id: JobId) -> Result<bool, Error>;
+    async fn count_hired_applications(&self, job_id: JobId) -> Result<i64, Error>;
+    async fn get_map_resume_job_by_id(
+        &self,
+        map_resume_job_id: MapResumeJobId,
     ) -> Result<MapResumeJob, Error>;
+    // Move the application from `from_status` to `to_status` and record the change,
+    // fails with InvalidStatusTransition when the application is not in `from_status` anymore
+    async fn update_application_status(
+        &self,
+        change: NewApplicationStatusChange,
+    ) -> Result<MapResumeJob, Error>;
+    async fn get_application_timeline(
+        &self,
+        map_resume_job_id: MapResumeJobId,
+    ) -> Result<Vec<ApplicationStatusChange>, Error>;
     async fn get_list_job_by_resume(&self, resume_id: ResumeId)
         -> Result<Vec<MapResumeJob>, Error>;
     async fn get_list_resume_by_job_id(
         &self,
-        limit: Option<i32>,
-        offset: i32,
+        pagination: Pagination,
         job_id: JobId,
-    ) -> Result<Vec<MapResumeJob>, Error>;
+    ) -> Result<Page<MapResumeJob>, Error>;
 
     //methods for users
     async fn create_user(&self, new_user: AuthInfo) -> Result<User, Error>;
     async fn get_user_by_email(&self, user_email: String) -> Result<User, Error>;
 
     async fn get_user_by_id(&self, user_id: UserId) -> Result<User, Error>;
-    async fn get_list_user(&self, limit: Option<i32>, offset: i32) -> Result<Vec<User>, Error>;
+    async fn get_list_user(&self, pagination: Pagination) -> Result<Page<User>, Error>;
     async fn update_user(&self, user_info: UserInfo) -> Result<User, Error>;
     async fn delete_user_by_id(&self, user_id: UserId) -> Result<bool, Error>;
     async fn update_password(&self, user: AuthInfo) -> Result<User, Error>;
     async fn set_role(&self, user: UserInfo, role_id: RoleId) -> Result<User, Error>;
+    async fn set_email_verified(&self, user_id: UserId) -> Result<User, Error>;
+
+    // methods for refresh tokens
+    async fn create_refresh_token(
+        &self,
+        new_refresh_token: NewRefreshToken,
+    ) -> Result<RefreshToken, Error>;
+    async fn get_refresh_token(&self, token_hash: &str) -> Result<RefreshToken, Error>;
+    async fn mark_refresh_token_used(&self, token_id: RefreshTokenId) -> Result<bool, Error>;
+    async fn revoke_refresh_token_family(&self, family_id: &str) -> Result<bool, Error>;
+
+    // methods for email tokens
+    // Store the token sent by email, unused tokens of the same purpose sent to the user before
+    // can't be used anymore
+    async fn create_email_token(&self, new_email_token: NewEmailToken)
+        -> Result<EmailToken, Error>;
+    // Mark the token used, fails with InvalidEmailToken when the token is unknown, used or expired
+    async fn use_email_token(
+        &self,
+        jti: &str,
+        purpose: EmailTokenPurpose,
+    ) -> Result<EmailToken, Error>;
+
+    // methods for login attempts
+    async fn get_login_attempt(&self, subject: &str) -> Result<Option<LoginAttempt>, Error>;
+    // Reserve a login attempt of the subject, it is counted as failed until it is released,
+    // see `LoginAttempt::reserve`. Refused with the error of the lockout or the delay.
+    async fn reserve_login(
+        &self,
+        subject: &str,
+        now: i64,
+        window_start: i64,
+        penalty: LoginPenalty<'_>,
+    ) -> Result<LoginAttempt, Error>;
+    // Give back an attempt returned by `reserve_login` which was a valid login
+    async fn release_login(
+        &self,
+        reserved: &LoginAttempt,
+        now: i64,
+    ) -> Result<Option<LoginAttempt>, Error>;
+    // Forget failed logins of the subject, returns false when there was none
+    async fn clear_login_attempts(&self, subject: &str) -> Result<bool, Error>;
+
+    // methods for audit log
+    async fn create_audit_entry(&self, new_entry: NewAuditEntry) -> Result<AuditEntry, Error>;
+    // Entries matching the filter, newest first
+    async fn get_list_audit_entries(&self, filter: AuditFilter) -> Result<Page<AuditEntry>, Error>;
+
+    // methods for access token revocation
+    async fn revoke_access_token(
+        &self,
+        jti: &str,
+        user_id: UserId,
+        expires_at: i64,
+    ) -> Result<bool, Error>;
+    // Bump the token version of the user, so every issued access token and
+    // all refresh tokens of the user are revoked
+    async fn revoke_user_tokens(&self, user_id: UserId) -> Result<i32, Error>;
+    async fn get_user_token_version(&self, user_id: UserId) -> Result<i32, Error>;
+    async fn is_access_token_revoked(
+        &self,
+        jti: &str,
+        user_id: UserId,
+        token_version: i32,
+    ) -> Result<bool, Error>;
 
     // methods for roles
     async fn create_role(&self, new_role: RoleInfo) -> Result<Role, Error>;
@@ -43,31 +147,64 @@ pub trait StoreMethods: fmt::Debug + Send + Sync {
     async fn get_list_roles(&self) -> Result<Vec<Role>, Error>;
     async fn update_role(&self, role: Role) -> Result<Role, Error>;
     async fn delete_role(&self, role_id: RoleId) -> Result<bool, Error>;
+    async fn get_role_chain(&self, role_id: RoleId) -> Result<Vec<Role>, Error>;
+    async fn get_role_permissions(&self, role_id: RoleId) -> Result<Vec<Permission>, Error>;
+    // Replace own permissions of the role
+    async fn set_role_permissions(
+        &self,
+        role_permissions: RolePermissions,
+    ) -> Result<RolePermissions, Error>;
     // methods for company
     async fn create_company(&self, new_company: NewCompany) -> Result<Company, Error>;
     async fn get_company_by_email(&self, company_email: &str) -> Result<Company, Error>;
     async fn get_company_by_id(&self, company_id: CompanyId) -> Result<Company, Error>;
-    async fn get_list_company(
-        &self,
-        limit: Option<i32>,
-        offset: i32,
-    ) -> Result<Vec<Company>, Error>;
+    async fn get_list_company(&self, pagination: Pagination) -> Result<Page<Company>, Error>;
     async fn update_company(&self, company: Company) -> Result<Company, Error>;
     async fn delete_company(&self, company_id: CompanyId) -> Result<bool, Error>;
 
     async fn create_job(&self, new_job: NewJob) -> Result<Job, Error>;
     async fn get_job_by_id(&self, job_id: JobId) -> Result<Job, Error>;
-    async fn get_list_job(&self, limit: Option<i32>, offset: i32) -> Result<Vec<Job>, Error>;
+    // Published jobs not past their expiry date matching every filter of the search
+    async fn search_jobs(&self, search: JobSearch) -> Result<JobSearchResult, Error>;
+    // Update the fields of the job, its status is kept
     async fn update_job(&self, job: Job) -> Result<Job, Error>;
     async fn delete_job(&self, job_id: JobId) -> Result<bool, Error>;
+    // Move the job from `from_status` to `to_status`, `now` is the publish time the first time
+    // the job is published, fails with InvalidStatusTransition when the job is not in
+    // `from_status` anymore
+    async fn update_job_status(
+        &self,
+        job_id: JobId,
+        from_status: JobStatus,
+        to_status: JobStatus,
+        now: i64,
+    ) -> Result<Job, Error>;
+    // Expire the published and paused jobs whose expiry date is not after `now`
+    async fn expire_jobs(&self, now: i64) -> Result<Vec<Job>, Error>;
+    // Open jobs mentioning one of the skill words and not applied to by the user,
+    // at most `limit` of them with the best matching first
+    async fn get_recommendation_jobs(
+        &self,
+        user_id: UserId,
+        skill_words: Vec<String>,
+        limit: i64,
+    ) -> Result<Vec<Job>, Error>;
     //methods for resume
     async fn create_resume(&self, new_resume: NewResume) -> Result<Resume, Error>;
     async fn get_resume_by_id(&self, resume_id: ResumeId) -> Result<Resume, Error>;
     async fn get_list_resume_by_user_id(
         &self,
-        limit: Option<i32>,
-        offset: i32,
+        pagination: Pagination,
         user_id: UserId,
+    ) -> Result<Page<Resume>, Error>;
+    // Searchable resumes matching every filter of the search, ordered by rank
+    async fn search_resumes(&self, search: ResumeSearch) -> Result<ResumeSearchResult, Error>;
+    // Searchable resumes with a skill word among the words of the job, at most `limit`
+    // of them with the most shared words first, the text of their file isn't loaded
+    async fn get_recommendation_resumes(
+        &self,
+        job_words: Vec<String>,
+        limit: i64,
     ) -> Result<Vec<Resume>, Error>;
     async fn update_resume(&self, resume: Resume) -> Result<Resume, Error>;
     async fn delete_resume(&self, resume_id: ResumeId) -> Result<bool, Error>;
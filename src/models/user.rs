@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
+use crate::errors::Error;
 use crate::models::company::CompanyId;
 use crate::models::role::RoleId;
+use crate::models::validation::{Validate, Validator};
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct User {
@@ -11,14 +14,16 @@ pub struct User {
     pub company_id: CompanyId,
     pub role_id: RoleId,
     pub is_delete: bool,
+    // Set once the user opened the link of the verification email
+    pub email_verified: bool,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, ToSchema)]
 pub struct UserId(pub i32);
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct UserEmail(pub String);
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
 pub struct UserInfo {
     pub id: UserId,
     pub email: String,
@@ -27,8 +32,31 @@ pub struct UserInfo {
     pub is_delete: bool,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+impl Validate for UserInfo {
+    fn validate(&self) -> Result<(), Error> {
+        let mut validator = Validator::new();
+        validator
+            .id("id", self.id.0)
+            .email("email", &self.email)
+            .range("company_id", self.company_id.0 as i64, 0, i32::MAX as i64)
+            .id("role_id", self.role_id.0)
+            .finish()
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, ToSchema)]
 pub struct AuthInfo {
     pub email: String,
     pub hash_password: String,
 }
+
+// Credentials of register and password update
+impl Validate for AuthInfo {
+    fn validate(&self) -> Result<(), Error> {
+        let mut validator = Validator::new();
+        validator
+            .email("email", &self.email)
+            .password("hash_password", &self.hash_password)
+            .finish()
+    }
+}
@@ -0,0 +1,244 @@
+use std::net::IpAddr;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::errors::Error;
+use crate::utils::convert_to_json::ProblemDetail;
+
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+pub const MAX_PASSWORD_LENGTH: usize = 128;
+pub const MAX_NAME_LENGTH: usize = 255;
+pub const MAX_TEXT_LENGTH: usize = 5000;
+pub const MAX_TOKEN_LENGTH: usize = 512;
+pub const MAX_JOB_QUANTITY: i64 = 10_000;
+
+// Rules an input model checks before it reaches the handler,
+// every broken rule is returned as an error of its field
+pub trait Validate {
+    fn validate(&self) -> Result<(), Error>;
+}
+
+fn email_regex() -> &'static Regex {
+    static EMAIL: OnceLock<Regex> = OnceLock::new();
+    EMAIL.get_or_init(|| {
+        Regex::new(r"^[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}$").unwrap()
+    })
+}
+
+fn url_regex() -> &'static Regex {
+    static URL: OnceLock<Regex> = OnceLock::new();
+    URL.get_or_init(|| {
+        Regex::new(r"^https?://[A-Za-z0-9-]+(\.[A-Za-z0-9-]+)*(:[0-9]{1,5})?(/[^\s]*)?$").unwrap()
+    })
+}
+
+// Collects the broken rules of a model, field by field
+#[derive(Debug, Default)]
+pub struct Validator {
+    errors: Vec<ProblemDetail>,
+}
+
+impl Validator {
+    pub fn new() -> Validator {
+        Validator::default()
+    }
+
+    fn fail(&mut self, field: &str, message: String) {
+        self.errors.push(ProblemDetail {
+            field: field.to_string(),
+            message,
+        });
+    }
+
+    pub fn email(&mut self, field: &str, value: &str) -> &mut Validator {
+        if value.len() > MAX_NAME_LENGTH || !email_regex().is_match(value) {
+            self.fail(field, "must be a valid email address".to_string());
+        }
+        self
+    }
+
+    // Password policy: 8 to 128 characters with at least a letter and a digit
+    pub fn password(&mut self, field: &str, value: &str) -> &mut Validator {
+        let length = value.chars().count();
+        if !(MIN_PASSWORD_LENGTH..=MAX_PASSWORD_LENGTH).contains(&length) {
+            self.fail(
+                field,
+                format!(
+                    "must be between {} and {} characters",
+                    MIN_PASSWORD_LENGTH, MAX_PASSWORD_LENGTH
+                ),
+            );
+        } else if !value.chars().any(|c| c.is_alphabetic())
+            || !value.chars().any(|c| c.is_ascii_digit())
+        {
+            self.fail(field, "must contain a letter and a digit".to_string());
+        }
+        self
+    }
+
+    // Length in characters, surrounding whitespace is not counted
+    pub fn length(&mut self, field: &str, value: &str, min: usize, max: usize) -> &mut Validator {
+        let length = value.trim().chars().count();
+        if length < min || length > max {
+            let message = match min {
+                0 => format!("must be at most {} characters", max),
+                _ => format!("must be between {} and {} characters", min, max),
+            };
+            self.fail(field, message);
+        }
+        self
+    }
+
+    pub fn range(&mut self, field: &str, value: i64, min: i64, max: i64) -> &mut Validator {
+        if value < min || value > max {
+            self.fail(field, format!("must be between {} and {}", min, max));
+        }
+        self
+    }
+
+    // Ids are generated by the database, starting from 1
+    pub fn id(&mut self, field: &str, value: i32) -> &mut Validator {
+        if value < 1 {
+            self.fail(field, "must be a positive id".to_string());
+        }
+        self
+    }
+
+    pub fn url(&mut self, field: &str, value: &str) -> &mut Validator {
+        if value.len() > MAX_TEXT_LENGTH || !url_regex().is_match(value) {
+            self.fail(field, "must be a valid http or https url".to_string());
+        }
+        self
+    }
+
+    pub fn ip(&mut self, field: &str, value: &str) -> &mut Validator {
+        if value.parse::<IpAddr>().is_err() {
+            self.fail(field, "must be a valid ip address".to_string());
+        }
+        self
+    }
+
+    pub fn one_of(&mut self, field: &str, value: &str, allowed: &[&str]) -> &mut Validator {
+        if !allowed.contains(&value) {
+            self.fail(field, format!("must be one of {}", allowed.join(", ")));
+        }
+        self
+    }
+
+    // Unix timestamp in seconds later than `now`
+    pub fn future(&mut self, field: &str, value: i64, now: i64) -> &mut Validator {
+        if value <= now {
+            self.fail(field, "must be in the future".to_string());
+        }
+        self
+    }
+
+    // Field which is optional in the model but needed by the request
+    pub fn required(&mut self, field: &str, present: bool) -> &mut Validator {
+        if !present {
+            self.fail(field, "is required".to_string());
+        }
+        self
+    }
+
+    pub fn finish(&mut self) -> Result<(), Error> {
+        match self.errors.is_empty() {
+            true => Ok(()),
+            false => Err(Error::Validation(std::mem::take(&mut self.errors))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod validation_tests {
+    use super::Validator;
+    use crate::errors::Error;
+
+    fn invalid_fields(validator: &mut Validator) -> Vec<String> {
+        match validator.finish() {
+            Err(Error::Validation(details)) => {
+                details.into_iter().map(|detail| detail.field).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn email_format() {
+        for email in ["user1@gmail.com", "first.last+tag@mail.example.vn"] {
+            assert!(Validator::new().email("email", email).finish().is_ok());
+        }
+        for email in [
+            "",
+            "user1",
+            "user1@",
+            "@gmail.com",
+            "user1@gmail",
+            "a b@gmail.com",
+        ] {
+            assert!(Validator::new().email("email", email).finish().is_err());
+        }
+    }
+
+    #[test]
+    fn password_policy() {
+        assert!(Validator::new()
+            .password("pw", "Secret123")
+            .finish()
+            .is_ok());
+        for password in ["", "Sec1", "12345678", "password", "a1".repeat(65).as_str()] {
+            assert!(Validator::new().password("pw", password).finish().is_err());
+        }
+    }
+
+    #[test]
+    fn url_format() {
+        for url in [
+            "https://cv.example.com/user1.pdf",
+            "http://localhost:8080/cv",
+        ] {
+            assert!(Validator::new().url("url", url).finish().is_ok());
+        }
+        for url in [
+            "",
+            "abcxyz",
+            "ftp://cv.example.com",
+            "https://",
+            "https://cv .com",
+        ] {
+            assert!(Validator::new().url("url", url).finish().is_err());
+        }
+    }
+
+    #[test]
+    fn every_broken_rule_is_reported() {
+        let mut validator = Validator::new();
+        validator
+            .email("email", "user1")
+            .length("name", "  ", 1, 255)
+            .range("salary", -1, 0, i32::MAX as i64)
+            .id("job_id", 0)
+            .length("address", "So 2 Pham Van Bach", 1, 255)
+            .ip("ip", "127.0.0.256")
+            .ip("ip_v6", "::1")
+            .required("email", false)
+            .future("expires_at", 100, 100)
+            .future("published_at", 101, 100)
+            .one_of("status", "paused", &["draft", "published"])
+            .one_of("order", "asc", &["asc", "desc"]);
+        assert_eq!(
+            invalid_fields(&mut validator),
+            vec![
+                "email",
+                "name",
+                "salary",
+                "job_id",
+                "ip",
+                "email",
+                "expires_at",
+                "status"
+            ]
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use warp::Filter;
+
+use crate::controllers::application::{
+    get_application_timeline, update_application_status, withdraw_application,
+};
+use crate::middleware::authen::{auth, Access};
+use crate::middleware::validation::validated_json;
+use crate::models::permission::Permission;
+use crate::models::store_trait::StoreMethods;
+use crate::services::jwt::Jwt;
+
+// Configures and returns the Warp filter for handling HTTP requests of application
+pub fn application_route(
+    base_path: &'static str,
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    jwt: Arc<Jwt>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    //Add base path into path
+    let application_path = warp::path(base_path)
+        .and(warp::path("v1"))
+        .and(warp::path("application"));
+    //Configures store filter
+    let auth_store = store.clone();
+    let store_filter = warp::any().map(move || store.clone());
+
+    //PUT api/v1/application/updateStatus
+    let update_status_api = application_path
+        .and(warp::path("update-status"))
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(store_filter.clone())
+        .and(auth(
+            Access::Permission(Permission::ManageJob),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
+        .and(validated_json())
+        .and_then(update_application_status);
+
+    //PUT api/v1/application/withdraw
+    let withdraw_api = application_path
+        .and(warp::path("withdraw"))
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(store_filter.clone())
+        .and(auth(
+            Access::Permission(Permission::ApplyJob),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
+        .and(validated_json())
+        .and_then(withdraw_application);
+
+    //GET api/v1/application/timeline/:id
+    let timeline_api = application_path
+        .and(warp::path("timeline"))
+        .and(warp::get())
+        .and(store_filter.clone())
+        .and(auth(
+            Access::Permission(Permission::ApplyJob),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and_then(get_application_timeline);
+
+    update_status_api.or(withdraw_api).or(timeline_api)
+}
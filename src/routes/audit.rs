@@ -0,0 +1,34 @@
+use std::sync::Arc;
+
+use warp::Filter;
+
+use crate::controllers::audit::get_list_audit;
+use crate::middleware::authen::{auth, Access};
+use crate::models::role::ADMIN_ROLE_ID;
+use crate::models::store_trait::StoreMethods;
+use crate::services::jwt::Jwt;
+
+// Configures and returns the Warp filter for handling HTTP requests of audit log
+pub fn audit_route(
+    base_path: &'static str,
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    jwt: Arc<Jwt>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    //Add base path into path
+    let admin_path = warp::path(base_path)
+        .and(warp::path("v1"))
+        .and(warp::path("admin"));
+    //Configures store filter
+    let auth_store = store.clone();
+    let store_filter = warp::any().map(move || store.clone());
+
+    //GET api/v1/admin/audit
+    admin_path
+        .and(warp::path("audit"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(store_filter)
+        .and(auth(Access::Roles(vec![ADMIN_ROLE_ID]), auth_store, jwt))
+        .and(warp::query())
+        .and_then(get_list_audit)
+}
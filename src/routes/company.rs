@@ -5,20 +5,24 @@ use warp::Filter;
 use crate::controllers::company::{
     create_company, delete_company, get_company, get_list_company, update_company,
 };
-use crate::middleware::authen::auth;
-use crate::models::role::ADMIN_ROLE_ID;
+use crate::middleware::authen::{auth, Access};
+use crate::middleware::validation::validated_json;
+use crate::models::permission::Permission;
 use crate::models::store_trait::StoreMethods;
+use crate::services::jwt::Jwt;
 
 // Configures and returns the Warp filter for handling HTTP requests of company
 pub fn company_route(
     base_path: &'static str,
     store: Arc<dyn StoreMethods + Send + Sync>,
+    jwt: Arc<Jwt>,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     //Add base path into path
     let company_path = warp::path(base_path)
         .and(warp::path("v1"))
         .and(warp::path("company"));
     //Configures store filter
+    let auth_store = store.clone();
     let store_filter = warp::any().map(move || store.clone());
 
     //POST api/v1/company/createCompany
@@ -27,11 +31,16 @@ pub fn company_route(
         .and(warp::path::end())
         .and(warp::post())
         .and(store_filter.clone())
-        .and(auth(ADMIN_ROLE_ID))
-        .and(warp::body::json())
+        .and(auth(
+            Access::Permission(Permission::ManageCompany),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
+        .and(validated_json())
         .and_then(create_company);
 
     //GET api/v1/company/listCompany?limit=x&offset=y
+    //GET api/v1/company/listCompany?limit=x&cursor=y
     let get_list_company_api = company_path
         .and(warp::get())
         .and(warp::path("list-company"))
@@ -55,8 +64,12 @@ pub fn company_route(
         .and(warp::path::end())
         .and(warp::put())
         .and(store_filter.clone())
-        .and(auth(ADMIN_ROLE_ID))
-        .and(warp::body::json())
+        .and(auth(
+            Access::Permission(Permission::ManageCompany),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
+        .and(validated_json())
         .and_then(update_company);
 
     //PUT api/v1/company/deleteCompany
@@ -65,8 +78,12 @@ pub fn company_route(
         .and(warp::path::end())
         .and(warp::put())
         .and(store_filter.clone())
-        .and(auth(ADMIN_ROLE_ID))
-        .and(warp::body::json())
+        .and(auth(
+            Access::Permission(Permission::ManageCompany),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
+        .and(validated_json())
         .and_then(delete_company);
 
     get_list_company_api
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use warp::Filter;
+
+use crate::controllers::health::{get_liveness, get_metrics, get_readiness};
+use crate::models::store_trait::StoreMethods;
+
+// Configures and returns the Warp filter for probes and metrics of the server,
+// they are not rate limited
+pub fn health_route(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    //Configures store filter
+    let store_filter = warp::any().map(move || store.clone());
+
+    //GET healthz
+    let liveness_api = warp::path("healthz")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(get_liveness);
+
+    //GET readyz
+    let readiness_api = warp::path("readyz")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(store_filter.clone())
+        .and_then(get_readiness);
+
+    //GET metrics
+    let metrics_api = warp::path("metrics")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(store_filter.clone())
+        .and_then(get_metrics);
+
+    liveness_api.or(readiness_api).or(metrics_api)
+}
@@ -3,22 +3,27 @@ use std::sync::Arc;
 use warp::Filter;
 
 use crate::controllers::job::{
-    apply_job, create_job, delete_job, get_job, get_list_job, update_job,
+    apply_job, create_job, delete_job, get_job, get_list_job, get_recommended_jobs,
+    get_suggested_candidates, update_job, update_job_status,
 };
-use crate::middleware::authen::auth;
-use crate::models::role::{HR_ROLE_ID, USER_ROLE_ID};
+use crate::middleware::authen::{auth, optional_authenticated, Access};
+use crate::middleware::validation::validated_json;
+use crate::models::permission::Permission;
 use crate::models::store_trait::StoreMethods;
+use crate::services::jwt::Jwt;
 
 // Configures and returns the Warp filter for handling HTTP requests of job
 pub fn job_route(
     base_path: &'static str,
     store: Arc<dyn StoreMethods + Send + Sync>,
+    jwt: Arc<Jwt>,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     //Add base path into path
     let job_path = warp::path(base_path)
         .and(warp::path("v1"))
         .and(warp::path("job"));
     //Configures store filter
+    let auth_store = store.clone();
     let store_filter = warp::any().map(move || store.clone());
 
     //POST api/v1/job/createJob
@@ -27,20 +32,26 @@ pub fn job_route(
         .and(warp::path::end())
         .and(warp::post())
         .and(store_filter.clone())
-        .and(auth(HR_ROLE_ID))
-        .and(warp::body::json())
+        .and(auth(
+            Access::Permission(Permission::ManageJob),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
+        .and(validated_json())
         .and_then(create_job);
 
     //GET api/v1/job/getJob/:id
     let get_job_api = job_path
         .and(warp::get())
         .and(warp::path("get-job"))
-        .and(store_filter.clone())
         .and(warp::path::param::<i32>())
         .and(warp::path::end())
-        .and_then(get_job);
+        .and(store_filter.clone())
+        .and(optional_authenticated(auth_store.clone(), jwt.clone()))
+        .and_then(|job_id, store, claims| get_job(store, claims, job_id));
 
-    //GET api/v1/job/listJob?limit=x&offset=y
+    //GET api/v1/job/listJob?q=x&jobLevel=x&minSalary=x&sort=salary&order=asc&limit=x&offset=y
+    //GET api/v1/job/listJob?q=x&jobLevel=x&minSalary=x&sort=salary&order=asc&limit=x&cursor=y
     let get_list_job_api = job_path
         .and(warp::get())
         .and(warp::path("list-job"))
@@ -55,18 +66,40 @@ pub fn job_route(
         .and(warp::path::end())
         .and(warp::put())
         .and(store_filter.clone())
-        .and(auth(HR_ROLE_ID))
-        .and(warp::body::json())
+        .and(auth(
+            Access::Permission(Permission::ManageJob),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
+        .and(validated_json())
         .and_then(update_job);
 
+    //PUT api/v1/job/updateJobStatus
+    let update_job_status_api = job_path
+        .and(warp::path("update-job-status"))
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(store_filter.clone())
+        .and(auth(
+            Access::Permission(Permission::ManageJob),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
+        .and(validated_json())
+        .and_then(update_job_status);
+
     //POST api/v1/job/applyJob
     let apply_job_api = job_path
         .and(warp::path("apply-job"))
         .and(warp::path::end())
         .and(warp::post())
         .and(store_filter.clone())
-        .and(auth(USER_ROLE_ID))
-        .and(warp::body::json())
+        .and(auth(
+            Access::Permission(Permission::ApplyJob),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
+        .and(validated_json())
         .and_then(apply_job);
 
     //PUT api/v1/job/deleteJob
@@ -75,14 +108,54 @@ pub fn job_route(
         .and(warp::path::end())
         .and(warp::put())
         .and(store_filter.clone())
-        .and(auth(HR_ROLE_ID))
-        .and(warp::body::json())
+        .and(auth(
+            Access::Permission(Permission::ManageJob),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
+        .and(validated_json())
         .and_then(delete_job);
 
+    //GET api/v1/job/recommended?limit=x
+    let recommended_job_api = job_path
+        .and(warp::get())
+        .and(warp::path("recommended"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(auth(
+            Access::Permission(Permission::ApplyJob),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
+        .and(warp::query())
+        .and_then(get_recommended_jobs);
+
+    //GET api/v1/job/:id/suggested-candidates?limit=x
+    // The path is matched before the token is checked, so requests to other
+    // job routes are not rejected as unauthorized by this one
+    let suggested_candidates_api = job_path
+        .and(warp::get())
+        .and(warp::path::param::<i32>())
+        .and(warp::path("suggested-candidates"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(auth(
+            Access::Permission(Permission::ManageJob),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
+        .and(warp::query())
+        .and_then(|job_id, store, claims, params| {
+            get_suggested_candidates(store, claims, job_id, params)
+        });
+
     create_api
         .or(get_job_api)
         .or(get_list_job_api)
         .or(update_job_api)
+        .or(update_job_status_api)
         .or(apply_job_api)
         .or(delete_job_api)
+        .or(recommended_job_api)
+        .or(suggested_candidates_api)
 }
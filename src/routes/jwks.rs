@@ -0,0 +1,22 @@
+use std::sync::Arc;
+
+use warp::Filter;
+
+use crate::controllers::jwks::get_jwks;
+use crate::services::jwt::Jwt;
+
+// Configures and returns the Warp filter for publishing public keys of jwt
+pub fn jwks_route(
+    jwt: Arc<Jwt>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    //Configures jwt filter
+    let jwt_filter = warp::any().map(move || jwt.clone());
+
+    //GET .well-known/jwks.json
+    warp::path(".well-known")
+        .and(warp::path("jwks.json"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(jwt_filter)
+        .and_then(get_jwks)
+}
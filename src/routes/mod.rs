@@ -1,4 +1,10 @@
+pub mod application;
+pub mod audit;
 pub mod company;
+pub mod health;
 pub mod job;
+pub mod jwks;
+pub mod openapi;
 pub mod resume;
+pub mod role;
 pub mod user;
@@ -0,0 +1,24 @@
+use warp::Filter;
+
+use crate::controllers::openapi::{get_docs_page, get_openapi};
+
+// Configures and returns the Warp filter for serving the OpenAPI document and its docs page
+pub fn openapi_route(
+    base_path: &'static str,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    //GET api/openapi.json
+    let openapi_api = warp::path(base_path)
+        .and(warp::path("openapi.json"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(get_openapi);
+
+    //GET api/docs
+    let docs_page_api = warp::path(base_path)
+        .and(warp::path("docs"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(get_docs_page);
+
+    openapi_api.or(docs_page_api)
+}
@@ -3,24 +3,34 @@ use std::sync::Arc;
 use warp::Filter;
 
 use crate::controllers::resume::{
-    create_resume, delete_resume, get_list_resume_by_job, get_list_resume_by_user_id, get_resume,
-    update_resume,
+    create_resume, delete_resume, download_resume, get_list_resume_by_job,
+    get_list_resume_by_user_id, get_resume, search_resume, update_resume,
 };
-use crate::middleware::authen::auth;
-use crate::models::role::USER_ROLE_ID;
+use crate::middleware::authen::{auth, authenticated, Access};
+use crate::middleware::upload::resume_body;
+use crate::middleware::validation::validated_json;
+use crate::models::permission::Permission;
+use crate::models::resume::{NewResume, Resume};
 use crate::models::store_trait::StoreMethods;
+use crate::services::jwt::Jwt;
+use crate::services::resume_file::ResumeFiles;
 
 // Configures and returns the Warp filter for handling HTTP requests of job
 pub fn resume_route(
     base_path: &'static str,
     store: Arc<dyn StoreMethods + Send + Sync>,
+    jwt: Arc<Jwt>,
+    files: Arc<ResumeFiles>,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     //Add base path into path
     let resume_path = warp::path(base_path)
         .and(warp::path("v1"))
         .and(warp::path("resume"));
     //Configures store filter
+    let auth_store = store.clone();
     let store_filter = warp::any().map(move || store.clone());
+    let max_file_bytes = files.max_file_bytes;
+    let files_filter = warp::any().map(move || files.clone());
 
     //POST api/v1/resume/createResume
     let create_api = resume_path
@@ -28,8 +38,13 @@ pub fn resume_route(
         .and(warp::path::end())
         .and(warp::post())
         .and(store_filter.clone())
-        .and(auth(USER_ROLE_ID))
-        .and(warp::body::json())
+        .and(files_filter.clone())
+        .and(auth(
+            Access::Permission(Permission::ManageResume),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
+        .and(resume_body::<NewResume>(max_file_bytes))
         .and_then(create_resume);
 
     //GET api/v1/resume/getResume/:id
@@ -37,38 +52,72 @@ pub fn resume_route(
         .and(warp::get())
         .and(warp::path("get-resume"))
         .and(store_filter.clone())
-        .and(auth(USER_ROLE_ID))
+        .and(auth(
+            Access::Permission(Permission::ManageResume),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
         .and(warp::path::param::<i32>())
         .and(warp::path::end())
         .and_then(get_resume);
 
     //GET api/v1/resume/listResumeByUser?limit=x&offset=y
+    //GET api/v1/resume/listResumeByUser?limit=x&cursor=y
     let get_list_resume_user_api = resume_path
         .and(warp::get())
         .and(warp::path("list-resume-by-user"))
         .and(warp::path::end())
         .and(store_filter.clone())
-        .and(auth(USER_ROLE_ID))
+        .and(auth(
+            Access::Permission(Permission::ManageResume),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
         .and(warp::query())
         .and_then(get_list_resume_by_user_id);
 
     //GET api/v1/resume/listResumeByJob?limit=x&offset=y&jobId=z
+    //GET api/v1/resume/listResumeByJob?limit=x&cursor=y&jobId=z
     let get_list_resume_job_api = resume_path
         .and(warp::get())
         .and(warp::path("list-resume-by-job"))
         .and(warp::path::end())
         .and(store_filter.clone())
+        .and(auth(
+            Access::Permission(Permission::ManageJob),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
         .and(warp::query())
         .and_then(get_list_resume_by_job);
 
+    //GET api/v1/resume/search?q=x&skills=y&location=z&minExperience=a&maxExperience=b&limit=c&offset=d
+    let search_resume_api = resume_path
+        .and(warp::get())
+        .and(warp::path("search"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(auth(
+            Access::Permission(Permission::ManageJob),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
+        .and(warp::query())
+        .and_then(search_resume);
+
     //PUT api/v1/resume/updateResume
     let update_resume_api = resume_path
         .and(warp::path("update-resume"))
         .and(warp::path::end())
         .and(warp::put())
         .and(store_filter.clone())
-        .and(auth(USER_ROLE_ID))
-        .and(warp::body::json())
+        .and(files_filter.clone())
+        .and(auth(
+            Access::Permission(Permission::ManageResume),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
+        .and(resume_body::<Resume>(max_file_bytes))
         .and_then(update_resume);
 
     //PUT api/v1/resume/deleteResume
@@ -77,14 +126,32 @@ pub fn resume_route(
         .and(warp::path::end())
         .and(warp::put())
         .and(store_filter.clone())
-        .and(auth(USER_ROLE_ID))
-        .and(warp::body::json())
+        .and(files_filter.clone())
+        .and(auth(
+            Access::Permission(Permission::ManageResume),
+            auth_store.clone(),
+            jwt.clone(),
+        ))
+        .and(validated_json())
         .and_then(delete_resume);
 
+    //GET api/v1/resume/download/:id
+    let download_resume_api = resume_path
+        .and(warp::get())
+        .and(warp::path("download"))
+        .and(store_filter.clone())
+        .and(files_filter.clone())
+        .and(authenticated(auth_store.clone(), jwt.clone()))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and_then(download_resume);
+
     create_api
         .or(get_resume_api)
         .or(get_list_resume_user_api)
         .or(get_list_resume_job_api)
+        .or(search_resume_api)
         .or(update_resume_api)
         .or(delete_resume_api)
+        .or(download_resume_api)
 }
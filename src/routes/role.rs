@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use warp::Filter;
+
+use crate::controllers::role::{
+    create_role, delete_role, get_list_roles, get_role, get_role_permissions, set_role_permissions,
+    update_role,
+};
+use crate::controllers::user::assign_role;
+use crate::middleware::authen::{auth, Access};
+use crate::middleware::validation::validated_json;
+use crate::models::role::ADMIN_ROLE_ID;
+use crate::models::store_trait::StoreMethods;
+use crate::services::jwt::Jwt;
+
+// Configures and returns the Warp filter for handling HTTP requests of role
+pub fn role_route(
+    base_path: &'static str,
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    jwt: Arc<Jwt>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    //Add base path into path
+    let role_path = warp::path(base_path)
+        .and(warp::path("v1"))
+        .and(warp::path("role"));
+    //Configures store filter
+    let auth_store = store.clone();
+    let store_filter = warp::any().map(move || store.clone());
+    //Every role route is admin only
+    let admin_filter = auth(Access::Roles(vec![ADMIN_ROLE_ID]), auth_store, jwt);
+
+    //POST api/v1/role/createRole
+    let create_api = role_path
+        .and(warp::path("create-role"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(store_filter.clone())
+        .and(admin_filter.clone())
+        .and(validated_json())
+        .and_then(create_role);
+
+    //GET api/v1/role/getRole/:id
+    let get_role_api = role_path
+        .and(warp::get())
+        .and(warp::path("get-role"))
+        .and(store_filter.clone())
+        .and(admin_filter.clone())
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and_then(get_role);
+
+    //GET api/v1/role/listRole
+    let get_list_role_api = role_path
+        .and(warp::get())
+        .and(warp::path("list-role"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(admin_filter.clone())
+        .and_then(get_list_roles);
+
+    //PUT api/v1/role/updateRole
+    let update_role_api = role_path
+        .and(warp::path("update-role"))
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(store_filter.clone())
+        .and(admin_filter.clone())
+        .and(validated_json())
+        .and_then(update_role);
+
+    //PUT api/v1/role/deleteRole
+    let delete_role_api = role_path
+        .and(warp::path("delete-role"))
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(store_filter.clone())
+        .and(admin_filter.clone())
+        .and(validated_json())
+        .and_then(delete_role);
+
+    //GET api/v1/role/getPermissions/:id
+    let get_permissions_api = role_path
+        .and(warp::get())
+        .and(warp::path("get-permissions"))
+        .and(store_filter.clone())
+        .and(admin_filter.clone())
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and_then(get_role_permissions);
+
+    //PUT api/v1/role/setPermissions
+    let set_permissions_api = role_path
+        .and(warp::path("set-permissions"))
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(store_filter.clone())
+        .and(admin_filter.clone())
+        .and(validated_json())
+        .and_then(set_role_permissions);
+
+    //PUT api/v1/role/assignRole
+    let assign_role_api = role_path
+        .and(warp::path("assign-role"))
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(store_filter.clone())
+        .and(admin_filter.clone())
+        .and(validated_json())
+        .and_then(assign_role);
+
+    get_list_role_api
+        .or(get_role_api)
+        .or(create_api)
+        .or(update_role_api)
+        .or(delete_role_api)
+        .or(get_permissions_api)
+        .or(set_permissions_api)
+        .or(assign_role_api)
+}
@@ -3,22 +3,40 @@ use std::sync::Arc;
 use warp::Filter;
 
 use crate::controllers::user::{
-    delete, get_list_users, get_user_by_id, login, register, set_admin_role, set_hr_role,
-    update_password, update_user,
+    delete, forgot_password, get_list_users, get_user_by_id, login, logout, logout_all,
+    refresh_token, register, resend_verification, reset_password, set_admin_role, set_hr_role,
+    unlock_login, update_password, update_user, verify_email,
 };
-use crate::middleware::authen::auth;
-use crate::models::role::{ADMIN_ROLE_ID, HR_ROLE_ID, USER_ROLE_ID};
+use crate::middleware::authen::{auth, authenticated, Access};
+use crate::middleware::client_ip::client_ip;
+use crate::middleware::validation::validated_json;
+use crate::models::permission::Permission;
+use crate::models::role::ADMIN_ROLE_ID;
 use crate::models::store_trait::StoreMethods;
+use crate::services::jwt::Jwt;
+use crate::services::login_guard::LoginGuard;
+use crate::services::mailer::AccountMailer;
 
 // Configures and returns the Warp filter for handling HTTP requests of user.
 pub fn user_route(
     base_path: &'static str,
     store: Arc<dyn StoreMethods + Send + Sync>,
+    jwt: Arc<Jwt>,
+    account_mailer: Arc<AccountMailer>,
+    login_guard: Arc<LoginGuard>,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     //Add base path into path
     let user_path = warp::path(base_path).and(warp::path("v1"));
     //Configures store filter
+    let auth_store = store.clone();
+    let auth_jwt = jwt.clone();
     let store_filter = warp::any().map(move || store.clone());
+    //Configures jwt filter
+    let jwt_filter = warp::any().map(move || jwt.clone());
+    //Configures mailer filter
+    let mailer_filter = warp::any().map(move || account_mailer.clone());
+    //Configures login guard filter
+    let login_guard_filter = warp::any().map(move || login_guard.clone());
 
     //POST api/v1/register
     let register_api = user_path
@@ -26,7 +44,9 @@ pub fn user_route(
         .and(warp::path::end())
         .and(warp::post())
         .and(store_filter.clone())
-        .and(warp::body::json())
+        .and(jwt_filter.clone())
+        .and(mailer_filter.clone())
+        .and(validated_json())
         .and_then(register);
 
     //GET api/v1/login
@@ -35,9 +55,88 @@ pub fn user_route(
         .and(warp::path::end())
         .and(warp::post())
         .and(store_filter.clone())
+        .and(jwt_filter.clone())
+        .and(mailer_filter.clone())
+        .and(login_guard_filter)
+        .and(client_ip())
+        // Credentials are only compared with the stored hash, the password policy
+        // applies when the password is set
         .and(warp::body::json())
         .and_then(login);
 
+    //POST api/v1/verify-email
+    let verify_email_api = user_path
+        .and(warp::path("verify-email"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(store_filter.clone())
+        .and(jwt_filter.clone())
+        .and(validated_json())
+        .and_then(verify_email);
+
+    //POST api/v1/verify-email/resend
+    let resend_verification_api = user_path
+        .and(warp::path("verify-email"))
+        .and(warp::path("resend"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(store_filter.clone())
+        .and(jwt_filter.clone())
+        .and(mailer_filter.clone())
+        .and(validated_json())
+        .and_then(resend_verification);
+
+    //POST api/v1/forgot-password
+    let forgot_password_api = user_path
+        .and(warp::path("forgot-password"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(store_filter.clone())
+        .and(jwt_filter.clone())
+        .and(mailer_filter.clone())
+        .and(validated_json())
+        .and_then(forgot_password);
+
+    //POST api/v1/reset-password
+    let reset_password_api = user_path
+        .and(warp::path("reset-password"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(store_filter.clone())
+        .and(jwt_filter.clone())
+        .and(validated_json())
+        .and_then(reset_password);
+
+    //POST api/v1/token/refresh
+    let refresh_token_api = user_path
+        .and(warp::path("token"))
+        .and(warp::path("refresh"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(store_filter.clone())
+        .and(jwt_filter.clone())
+        .and(validated_json())
+        .and_then(refresh_token);
+
+    //POST api/v1/logout
+    let logout_api = user_path
+        .and(warp::path("logout"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(store_filter.clone())
+        .and(authenticated(auth_store.clone(), auth_jwt.clone()))
+        .and(validated_json())
+        .and_then(logout);
+
+    //POST api/v1/logout-all
+    let logout_all_api = user_path
+        .and(warp::path("logout-all"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(store_filter.clone())
+        .and(authenticated(auth_store.clone(), auth_jwt.clone()))
+        .and_then(logout_all);
+
     //GET api/v1/user/getUser/:id
     let get_user_api = user_path
         .and(warp::get())
@@ -49,6 +148,7 @@ pub fn user_route(
         .and_then(get_user_by_id);
 
     //GET api/v1/user/listUser?limit=x&offset=y
+    //GET api/v1/user/listUser?limit=x&cursor=y
     let get_list_user_api = user_path
         .and(warp::get())
         .and(warp::path("user"))
@@ -65,19 +165,12 @@ pub fn user_route(
         .and(warp::path::end())
         .and(warp::put())
         .and(store_filter.clone())
-        .and(auth(USER_ROLE_ID))
-        .and(warp::body::json())
-        .and_then(update_user);
-
-    //PUT api/v1/user/updateUser
-    let update_hr_api = user_path
-        .and(warp::path("user"))
-        .and(warp::path("update-user"))
-        .and(warp::path::end())
-        .and(warp::put())
-        .and(store_filter.clone())
-        .and(auth(HR_ROLE_ID))
-        .and(warp::body::json())
+        .and(auth(
+            Access::Permission(Permission::ManageAccount),
+            auth_store.clone(),
+            auth_jwt.clone(),
+        ))
+        .and(validated_json())
         .and_then(update_user);
 
     //PUT api/v1/admin/updateAdmin
@@ -87,8 +180,12 @@ pub fn user_route(
         .and(warp::path::end())
         .and(warp::put())
         .and(store_filter.clone())
-        .and(auth(ADMIN_ROLE_ID))
-        .and(warp::body::json())
+        .and(auth(
+            Access::Roles(vec![ADMIN_ROLE_ID]),
+            auth_store.clone(),
+            auth_jwt.clone(),
+        ))
+        .and(validated_json())
         .and_then(update_user);
 
     //PUT api/v1/user/updatePassword
@@ -98,19 +195,12 @@ pub fn user_route(
         .and(warp::path::end())
         .and(warp::put())
         .and(store_filter.clone())
-        .and(auth(USER_ROLE_ID))
-        .and(warp::body::json())
-        .and_then(update_password);
-
-    //PUT api/v1/user/updatePassword
-    let update_hr_password_api = user_path
-        .and(warp::path("user"))
-        .and(warp::path("update-password"))
-        .and(warp::path::end())
-        .and(warp::put())
-        .and(store_filter.clone())
-        .and(auth(HR_ROLE_ID))
-        .and(warp::body::json())
+        .and(auth(
+            Access::Permission(Permission::ManageAccount),
+            auth_store.clone(),
+            auth_jwt.clone(),
+        ))
+        .and(validated_json())
         .and_then(update_password);
 
     //PUT api/v1/admin/updatePassword
@@ -120,8 +210,12 @@ pub fn user_route(
         .and(warp::path::end())
         .and(warp::put())
         .and(store_filter.clone())
-        .and(auth(ADMIN_ROLE_ID))
-        .and(warp::body::json())
+        .and(auth(
+            Access::Roles(vec![ADMIN_ROLE_ID]),
+            auth_store.clone(),
+            auth_jwt.clone(),
+        ))
+        .and(validated_json())
         .and_then(update_password);
 
     //PUT api/v1/user/deleteUser
@@ -131,19 +225,12 @@ pub fn user_route(
         .and(warp::path::end())
         .and(warp::put())
         .and(store_filter.clone())
-        .and(auth(USER_ROLE_ID))
-        .and(warp::body::json())
-        .and_then(delete);
-
-    //PUT api/v1/user/deleteUser
-    let delete_hr_api = user_path
-        .and(warp::path("user"))
-        .and(warp::path("delete-user"))
-        .and(warp::path::end())
-        .and(warp::put())
-        .and(store_filter.clone())
-        .and(auth(HR_ROLE_ID))
-        .and(warp::body::json())
+        .and(auth(
+            Access::Permission(Permission::ManageAccount),
+            auth_store.clone(),
+            auth_jwt.clone(),
+        ))
+        .and(validated_json())
         .and_then(delete);
 
     //PUT api/v1/admin/deleteAdmin
@@ -153,8 +240,12 @@ pub fn user_route(
         .and(warp::path::end())
         .and(warp::put())
         .and(store_filter.clone())
-        .and(auth(ADMIN_ROLE_ID))
-        .and(warp::body::json())
+        .and(auth(
+            Access::Roles(vec![ADMIN_ROLE_ID]),
+            auth_store.clone(),
+            auth_jwt.clone(),
+        ))
+        .and(validated_json())
         .and_then(delete);
 
     //PUT api/v1/admin/setHr
@@ -164,8 +255,12 @@ pub fn user_route(
         .and(warp::path::end())
         .and(warp::put())
         .and(store_filter.clone())
-        .and(auth(ADMIN_ROLE_ID))
-        .and(warp::body::json())
+        .and(auth(
+            Access::Permission(Permission::AssignRole),
+            auth_store.clone(),
+            auth_jwt.clone(),
+        ))
+        .and(validated_json())
         .and_then(set_hr_role);
 
     //PUT api/v1/admin/setAdmin
@@ -175,23 +270,47 @@ pub fn user_route(
         .and(warp::path::end())
         .and(warp::put())
         .and(store_filter.clone())
-        .and(auth(ADMIN_ROLE_ID))
-        .and(warp::body::json())
+        .and(auth(
+            Access::Permission(Permission::AssignRole),
+            auth_store.clone(),
+            auth_jwt.clone(),
+        ))
+        .and(validated_json())
         .and_then(set_admin_role);
 
+    //PUT api/v1/admin/unlock-login
+    let unlock_login_api = user_path
+        .and(warp::path("admin"))
+        .and(warp::path("unlock-login"))
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(store_filter.clone())
+        .and(auth(
+            Access::Roles(vec![ADMIN_ROLE_ID]),
+            auth_store.clone(),
+            auth_jwt.clone(),
+        ))
+        .and(validated_json())
+        .and_then(unlock_login);
+
     register_api
         .or(login_api)
+        .or(verify_email_api)
+        .or(resend_verification_api)
+        .or(forgot_password_api)
+        .or(reset_password_api)
+        .or(refresh_token_api)
+        .or(logout_api)
+        .or(logout_all_api)
         .or(get_list_user_api)
         .or(get_user_api)
         .or(update_user_api)
         .or(update_user_password_api)
         .or(delete_user_api)
-        .or(update_hr_api)
-        .or(update_hr_password_api)
-        .or(delete_hr_api)
         .or(set_hr_api)
         .or(update_admin_api)
         .or(update_admin_password_api)
         .or(delete_admin_api)
         .or(set_admin_api)
+        .or(unlock_login_api)
 }
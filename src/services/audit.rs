@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use serde::Serialize;
+use tracing::{event, Level};
+
+use crate::models::audit::{AuditAction, AuditEntity, NewAuditEntry};
+use crate::models::store_trait::StoreMethods;
+use crate::models::user::UserId;
+
+// State of an entity as stored in the audit entry
+pub fn snapshot<T: Serialize>(entity: &T) -> Option<serde_json::Value> {
+    serde_json::to_value(entity).ok()
+}
+
+// Store an audit entry of a mutating operation. The operation is already done
+// when it is recorded, so a failed write is logged instead of failing the request
+pub async fn record_audit(
+    store: &Arc<dyn StoreMethods + Send + Sync>,
+    actor_id: Option<UserId>,
+    action: AuditAction,
+    entity_type: AuditEntity,
+    entity_id: impl ToString,
+    before: Option<serde_json::Value>,
+    after: Option<serde_json::Value>,
+) {
+    let entity_id = entity_id.to_string();
+    event!(
+        target: "audit",
+        Level::INFO,
+        action = %action,
+        actor = actor_id.as_ref().map(|actor_id| actor_id.0),
+        entity_type = %entity_type,
+        entity_id = %entity_id,
+        "Audit entry"
+    );
+    let new_entry = NewAuditEntry {
+        actor_id,
+        action,
+        entity_type,
+        entity_id,
+        before,
+        after,
+        created_at: Utc::now().timestamp(),
+    };
+    if let Err(e) = store.create_audit_entry(new_entry).await {
+        event!(Level::ERROR, "Record audit entry has error: {:?}", e);
+    }
+}
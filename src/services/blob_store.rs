@@ -0,0 +1,451 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::{StatusCode, Url};
+use sha2::{Digest, Sha256};
+use tokio::sync::RwLock;
+use tracing::{event, Level};
+
+use crate::configs::config::{S3Config, StorageConfig, StorageKind};
+use crate::errors::Error;
+
+// Content of the uploaded files, addressed by a key like `resumes/7/1724-ab12.pdf`
+#[async_trait]
+pub trait BlobStore: fmt::Debug + Send + Sync {
+    async fn put(&self, key: &str, content_type: &str, content: Vec<u8>) -> Result<(), Error>;
+    // Not found when nothing is stored under the key
+    async fn get(&self, key: &str) -> Result<Vec<u8>, Error>;
+    async fn delete(&self, key: &str) -> Result<(), Error>;
+}
+
+// Keep the files in a directory of the local filesystem
+#[derive(Debug, Clone)]
+pub struct LocalBlobStore {
+    dir: PathBuf,
+}
+
+impl LocalBlobStore {
+    pub fn new(config: &StorageConfig) -> LocalBlobStore {
+        LocalBlobStore {
+            dir: PathBuf::from(&config.local_dir),
+        }
+    }
+
+    // Keys are made by the service, a key leaving the directory is refused anyway
+    fn path_of(&self, key: &str) -> Result<PathBuf, Error> {
+        let relative = Path::new(key);
+        match relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            true => Ok(self.dir.join(relative)),
+            false => Err(Error::BlobStore(format!("invalid key {}", key))),
+        }
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(&self, key: &str, _content_type: &str, content: Vec<u8>) -> Result<(), Error> {
+        let path = self.path_of(key)?;
+        let write = async {
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(&path, content).await
+        };
+        match write.await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Write file into local storage has error: {:?}",
+                    e
+                );
+                Err(Error::BlobStore(e.to_string()))
+            }
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, Error> {
+        match tokio::fs::read(self.path_of(key)?).await {
+            Ok(content) => Ok(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(Error::NotFound),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Read file from local storage has error: {:?}",
+                    e
+                );
+                Err(Error::BlobStore(e.to_string()))
+            }
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), Error> {
+        match tokio::fs::remove_file(self.path_of(key)?).await {
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => {
+                event!(
+                    Level::ERROR,
+                    "Delete file from local storage has error: {:?}",
+                    e
+                );
+                Err(Error::BlobStore(e.to_string()))
+            }
+        }
+    }
+}
+
+// Keep the files in a bucket of an S3-compatible service, requests are signed
+// with AWS signature version 4
+pub struct S3BlobStore {
+    config: S3Config,
+    endpoint: Url,
+    client: reqwest::Client,
+}
+
+impl fmt::Debug for S3BlobStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("S3BlobStore")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl S3BlobStore {
+    pub fn new(config: &StorageConfig) -> Result<S3BlobStore, Error> {
+        let s3 = config
+            .s3
+            .clone()
+            .ok_or_else(|| Error::StorageConfig("missing storage.s3".to_string()))?;
+        let endpoint = Url::parse(&s3.endpoint).map_err(|e| {
+            Error::StorageConfig(format!("invalid s3 endpoint {}: {}", s3.endpoint, e))
+        })?;
+        if endpoint.host_str().is_none() {
+            return Err(Error::StorageConfig(format!(
+                "invalid s3 endpoint {}: missing host",
+                s3.endpoint
+            )));
+        }
+        Ok(S3BlobStore {
+            config: s3,
+            endpoint,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    fn url_of(&self, key: &str) -> Url {
+        let mut url = self.endpoint.clone();
+        let key = uri_encode(key, false);
+        match self.config.path_style {
+            true => url.set_path(&format!("/{}/{}", self.config.bucket, key)),
+            false => {
+                let host = format!("{}.{}", self.config.bucket, url.host_str().unwrap());
+                // The host of the endpoint was checked by `new`
+                let _ = url.set_host(Some(&host));
+                url.set_path(&format!("/{}", key));
+            }
+        }
+        url
+    }
+
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        key: &str,
+        content_type: Option<&str>,
+        content: Vec<u8>,
+    ) -> Result<reqwest::Response, Error> {
+        let url = self.url_of(key);
+        let payload_hash = hex::encode(Sha256::digest(&content));
+        let headers = sign_v4(
+            &self.config,
+            method.as_str(),
+            &url,
+            &payload_hash,
+            Utc::now(),
+        );
+        let mut request = self.client.request(method, url).body(content);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        if let Some(content_type) = content_type {
+            request = request.header("content-type", content_type);
+        }
+        request.send().await.map_err(|e| {
+            event!(Level::ERROR, "Send request to s3 has error: {:?}", e);
+            Error::BlobStore(e.to_string())
+        })
+    }
+}
+
+// Error of a response which is not a success, the body holds the S3 error code
+async fn s3_error(action: &str, response: reqwest::Response) -> Error {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    event!(
+        Level::ERROR,
+        "{} s3 object has error: {} {}",
+        action,
+        status,
+        body
+    );
+    Error::BlobStore(format!("{} object answered {}", action, status))
+}
+
+#[async_trait]
+impl BlobStore for S3BlobStore {
+    async fn put(&self, key: &str, content_type: &str, content: Vec<u8>) -> Result<(), Error> {
+        let response = self
+            .send(reqwest::Method::PUT, key, Some(content_type), content)
+            .await?;
+        match response.status().is_success() {
+            true => Ok(()),
+            false => Err(s3_error("Put", response).await),
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, Error> {
+        let response = self
+            .send(reqwest::Method::GET, key, None, Vec::new())
+            .await?;
+        match response.status() {
+            status if status.is_success() => response
+                .bytes()
+                .await
+                .map(|bytes| bytes.to_vec())
+                .map_err(|e| Error::BlobStore(e.to_string())),
+            StatusCode::NOT_FOUND => Err(Error::NotFound),
+            _ => Err(s3_error("Get", response).await),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), Error> {
+        let response = self
+            .send(reqwest::Method::DELETE, key, None, Vec::new())
+            .await?;
+        match response.status().is_success() {
+            true => Ok(()),
+            false => Err(s3_error("Delete", response).await),
+        }
+    }
+}
+
+// Percent-encode everything but the unreserved characters, slashes are kept in keys
+fn uri_encode(value: &str, encode_slash: bool) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            b'/' if !encode_slash => "/".to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac_sha256(format!("AWS4{}", secret).as_bytes(), date);
+    let key = hmac_sha256(&key, region);
+    let key = hmac_sha256(&key, service);
+    hmac_sha256(&key, "aws4_request")
+}
+
+// Headers authorizing a request without query string, signed over the host,
+// the hash of the payload and the date
+fn sign_v4(
+    config: &S3Config,
+    method: &str,
+    url: &Url,
+    payload_hash: &str,
+    now: DateTime<Utc>,
+) -> Vec<(&'static str, String)> {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        method,
+        url.path(),
+        host,
+        payload_hash,
+        amz_date,
+        signed_headers,
+        payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, config.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let key = signing_key(&config.secret_access_key, &date, &config.region, "s3");
+    let signature = hex::encode(hmac_sha256(&key, &string_to_sign));
+    vec![
+        (
+            "authorization",
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                config.access_key_id, scope, signed_headers, signature
+            ),
+        ),
+        ("x-amz-content-sha256", payload_hash.to_string()),
+        ("x-amz-date", amz_date),
+    ]
+}
+
+// Keep the files in memory, used by tests
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryBlobStore {
+    pub blobs: Arc<RwLock<HashMap<String, Vec<u8>>>>,
+}
+
+impl InMemoryBlobStore {
+    pub fn new() -> InMemoryBlobStore {
+        InMemoryBlobStore::default()
+    }
+}
+
+#[async_trait]
+impl BlobStore for InMemoryBlobStore {
+    async fn put(&self, key: &str, _content_type: &str, content: Vec<u8>) -> Result<(), Error> {
+        self.blobs.write().await.insert(key.to_string(), content);
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, Error> {
+        self.blobs
+            .read()
+            .await
+            .get(key)
+            .cloned()
+            .ok_or(Error::NotFound)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), Error> {
+        self.blobs.write().await.remove(key);
+        Ok(())
+    }
+}
+
+pub fn build_blob_store(config: &StorageConfig) -> Result<Arc<dyn BlobStore>, Error> {
+    let blob_store: Arc<dyn BlobStore> = match config.backend {
+        StorageKind::Local => Arc::new(LocalBlobStore::new(config)),
+        StorageKind::S3 => Arc::new(S3BlobStore::new(config)?),
+        StorageKind::Memory => Arc::new(InMemoryBlobStore::new()),
+    };
+    Ok(blob_store)
+}
+
+#[cfg(test)]
+mod blob_store_tests {
+    use chrono::{TimeZone, Utc};
+    use reqwest::Url;
+
+    use super::{
+        sign_v4, signing_key, uri_encode, BlobStore, LocalBlobStore, S3BlobStore, S3Config,
+    };
+    use crate::configs::config::StorageConfig;
+    use crate::errors::Error;
+
+    fn s3_config(path_style: bool) -> StorageConfig {
+        StorageConfig {
+            s3: Some(S3Config {
+                endpoint: "http://localhost:9000".to_string(),
+                bucket: "resumes".to_string(),
+                region: "us-east-1".to_string(),
+                access_key_id: "AKIDEXAMPLE".to_string(),
+                secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+                path_style,
+            }),
+            ..StorageConfig::default()
+        }
+    }
+
+    #[test]
+    fn signing_key_matches_aws_example() {
+        // Example of the AWS documentation on deriving the signing key
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn request_is_signed_for_bucket_url() {
+        let store = S3BlobStore::new(&s3_config(true)).unwrap();
+        let url = store.url_of("resumes/7/cv 1.pdf");
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:9000/resumes/resumes/7/cv%201.pdf"
+        );
+        let virtual_host = S3BlobStore::new(&s3_config(false)).unwrap();
+        assert_eq!(
+            virtual_host.url_of("a.pdf").as_str(),
+            "http://resumes.localhost:9000/a.pdf"
+        );
+
+        let now = Utc.with_ymd_and_hms(2024, 8, 21, 9, 12, 30).unwrap();
+        let headers = sign_v4(
+            &store.config,
+            "GET",
+            &Url::parse("http://localhost:9000/resumes/a.pdf").unwrap(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            now,
+        );
+        assert_eq!(headers[0].0, "authorization");
+        assert!(headers[0].1.starts_with(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20240821/us-east-1/s3/aws4_request, \
+             SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature="
+        ));
+        assert_eq!(headers[2], ("x-amz-date", "20240821T091230Z".to_string()));
+        assert_eq!(uri_encode("a b/c", true), "a%20b%2Fc");
+    }
+
+    #[tokio::test]
+    async fn local_store_keeps_files_in_directory() {
+        let dir = std::env::temp_dir().join(format!("uploads-{}", rand::random::<u32>()));
+        let store = LocalBlobStore::new(&StorageConfig {
+            local_dir: dir.to_string_lossy().to_string(),
+            ..StorageConfig::default()
+        });
+        store
+            .put("resumes/7/cv.pdf", "application/pdf", b"%PDF-1.7".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(store.get("resumes/7/cv.pdf").await.unwrap(), b"%PDF-1.7");
+        assert!(store.get("../etc/passwd").await.is_err());
+
+        store.delete("resumes/7/cv.pdf").await.unwrap();
+        assert!(matches!(
+            store.get("resumes/7/cv.pdf").await,
+            Err(Error::NotFound)
+        ));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tracing::{event, Level};
+
+use crate::models::store_trait::StoreMethods;
+
+// Expire the published and paused jobs past their expiry date, returns the
+// number of expired jobs. Listings already hide them, so a failed run is
+// logged and retried by the next one
+pub async fn expire_jobs(store: &Arc<dyn StoreMethods + Send + Sync>, now: i64) -> usize {
+    match store.expire_jobs(now).await {
+        Ok(jobs) => {
+            if !jobs.is_empty() {
+                let ids: Vec<i32> = jobs.iter().map(|job| job.id.clone().unwrap().0).collect();
+                event!(Level::INFO, "Expired jobs {:?}", ids);
+            }
+            jobs.len()
+        }
+        Err(e) => {
+            event!(Level::ERROR, "Expire jobs has error: {:?}", e);
+            0
+        }
+    }
+}
+
+// Run the expiry every `interval`, the first run is right away. The task is
+// aborted when the server shuts down
+pub fn spawn_job_expiry(
+    store: Arc<dyn StoreMethods + Send + Sync>,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::task::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // A slow run delays the next ones instead of causing a burst of runs
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            expire_jobs(&store, Utc::now().timestamp()).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod job_expiry_tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::{expire_jobs, spawn_job_expiry};
+    use crate::models::company::NewCompany;
+    use crate::models::job::{new_job_fixture, JobId, JobStatus, NewJob};
+    use crate::models::store_in_memory::InMemoryStore;
+    use crate::models::store_trait::StoreMethods;
+
+    async fn store_with_jobs(expires_at: &[Option<i64>]) -> Arc<dyn StoreMethods + Send + Sync> {
+        let store = InMemoryStore::new();
+        store
+            .create_company(NewCompany {
+                name: "Company".to_string(),
+                email: "12next@gmail.com".to_string(),
+                address: "So 4 Nguyen Trai".to_string(),
+                description: "Web".to_string(),
+            })
+            .await
+            .unwrap();
+        for expires_at in expires_at {
+            store
+                .create_job(NewJob {
+                    expires_at: *expires_at,
+                    ..new_job_fixture()
+                })
+                .await
+                .unwrap();
+        }
+        Arc::new(store)
+    }
+
+    #[tokio::test]
+    async fn only_jobs_past_expiry_date_are_expired() {
+        let store = store_with_jobs(&[Some(1_000), Some(2_000), None]).await;
+        assert_eq!(expire_jobs(&store, 1_000).await, 1);
+        // Expired jobs are not expired again
+        assert_eq!(expire_jobs(&store, 1_500).await, 0);
+        let statuses = [
+            store.get_job_by_id(JobId(1)).await.unwrap().status,
+            store.get_job_by_id(JobId(2)).await.unwrap().status,
+            store.get_job_by_id(JobId(3)).await.unwrap().status,
+        ];
+        assert_eq!(
+            statuses,
+            [
+                JobStatus::Expired,
+                JobStatus::Published,
+                JobStatus::Published
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn task_expires_jobs_until_aborted() {
+        let store = store_with_jobs(&[Some(1_000)]).await;
+        let task = spawn_job_expiry(store.clone(), Duration::from_millis(10));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        task.abort();
+        assert!(task.await.unwrap_err().is_cancelled());
+        let job = store.get_job_by_id(JobId(1)).await.unwrap();
+        assert_eq!(job.status, JobStatus::Expired);
+    }
+}
@@ -0,0 +1,227 @@
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use chrono::Utc;
+
+use crate::configs::config::{LoginConfig, LoginLimit};
+use crate::errors::Error;
+use crate::models::audit::{AuditAction, AuditEntity};
+use crate::models::login_attempt::{account_subject, ip_subject, LoginAttempt};
+use crate::models::store_trait::StoreMethods;
+use crate::services::audit::{record_audit, snapshot};
+
+// Throttle failed logins per account and per client ip, so the password hash
+// is not verified for attempts which are refused anyway
+#[derive(Debug, Clone)]
+pub struct LoginGuard {
+    pub config: LoginConfig,
+}
+
+impl LoginGuard {
+    pub fn new(config: LoginConfig) -> LoginGuard {
+        LoginGuard { config }
+    }
+
+    fn subjects(&self, email: &str, ip: Option<IpAddr>) -> Vec<(String, LoginLimit)> {
+        let mut subjects = vec![(account_subject(email), self.config.account)];
+        if let Some(ip) = ip {
+            subjects.push((ip_subject(&ip), self.config.ip));
+        }
+        subjects
+    }
+
+    // Time of the next allowed attempt and end of the lockout after the failed logins
+    pub fn penalty(&self, limit: &LoginLimit, failed_count: i32, now: i64) -> (i64, Option<i64>) {
+        if failed_count >= limit.max_failures {
+            let locked_until = now + self.config.lockout_seconds;
+            return (locked_until, Some(locked_until));
+        }
+        if failed_count <= limit.free_attempts {
+            return (now, None);
+        }
+        let doublings = (failed_count - limit.free_attempts - 1).min(30) as u32;
+        let delay = self
+            .config
+            .base_delay_seconds
+            .saturating_mul(1i64 << doublings)
+            .min(self.config.max_delay_seconds);
+        (now + delay, None)
+    }
+
+    // Reserve an attempt of the account and of the ip before the password is verified, so
+    // concurrent attempts are counted one by one. Refused while either is locked or waiting.
+    pub async fn reserve(
+        &self,
+        store: &Arc<dyn StoreMethods + Send + Sync>,
+        email: &str,
+        ip: Option<IpAddr>,
+    ) -> Result<Vec<LoginAttempt>, Error> {
+        let now = Utc::now().timestamp();
+        let mut reserved = Vec::new();
+        for (subject, limit) in self.subjects(email, ip) {
+            let penalty = |failed_count: i32| self.penalty(&limit, failed_count, now);
+            match store
+                .reserve_login(&subject, now, now - self.config.window_seconds, &penalty)
+                .await
+            {
+                Ok(attempt) => reserved.push(attempt),
+                Err(e) => {
+                    self.release(store, &reserved).await?;
+                    return Err(e);
+                }
+            }
+        }
+        Ok(reserved)
+    }
+
+    // The reserved attempts stay counted as failed logins
+    pub async fn record_failure(
+        &self,
+        store: &Arc<dyn StoreMethods + Send + Sync>,
+        reserved: &[LoginAttempt],
+    ) {
+        for attempt in reserved
+            .iter()
+            .filter(|attempt| attempt.locked_until.is_some())
+        {
+            record_audit(
+                store,
+                None,
+                AuditAction::LoginLocked,
+                AuditEntity::Login,
+                &attempt.subject,
+                None,
+                snapshot(attempt),
+            )
+            .await;
+        }
+    }
+
+    // Failed logins of the account are forgotten, the ip only gets its reserved attempt back
+    // so one valid account doesn't reset the attempts of a guessing client
+    pub async fn record_success(
+        &self,
+        store: &Arc<dyn StoreMethods + Send + Sync>,
+        email: &str,
+        reserved: &[LoginAttempt],
+    ) -> Result<(), Error> {
+        let account = account_subject(email);
+        store.clear_login_attempts(&account).await?;
+        let others: Vec<LoginAttempt> = reserved
+            .iter()
+            .filter(|attempt| attempt.subject != account)
+            .cloned()
+            .collect();
+        self.release(store, &others).await
+    }
+
+    async fn release(
+        &self,
+        store: &Arc<dyn StoreMethods + Send + Sync>,
+        reserved: &[LoginAttempt],
+    ) -> Result<(), Error> {
+        let now = Utc::now().timestamp();
+        for attempt in reserved {
+            store.release_login(attempt, now).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod login_guard_tests {
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::sync::Arc;
+
+    use super::LoginGuard;
+    use crate::configs::config::{LoginConfig, LoginLimit};
+    use crate::errors::Error;
+    use crate::models::store_in_memory::InMemoryStore;
+    use crate::models::store_trait::StoreMethods;
+
+    const LIMIT: LoginLimit = LoginLimit {
+        free_attempts: 2,
+        max_failures: 6,
+    };
+
+    fn guard() -> LoginGuard {
+        LoginGuard::new(LoginConfig {
+            base_delay_seconds: 5,
+            max_delay_seconds: 15,
+            lockout_seconds: 900,
+            account: LIMIT,
+            ..LoginConfig::default()
+        })
+    }
+
+    #[test]
+    fn delay_doubles_up_to_max() {
+        let guard = guard();
+        let delays: Vec<i64> = (1..=5)
+            .map(|failed_count| guard.penalty(&LIMIT, failed_count, 100).0 - 100)
+            .collect();
+        assert_eq!(delays, vec![0, 0, 5, 10, 15]);
+        assert_eq!(guard.penalty(&LIMIT, 6, 100), (1000, Some(1000)));
+    }
+
+    async fn fail(
+        guard: &LoginGuard,
+        store: &Arc<dyn StoreMethods + Send + Sync>,
+        email: &str,
+        ip: Option<IpAddr>,
+    ) {
+        let reserved = guard.reserve(store, email, ip).await.unwrap();
+        guard.record_failure(store, &reserved).await;
+    }
+
+    #[tokio::test]
+    async fn account_is_locked_after_max_failures() {
+        let store: Arc<dyn StoreMethods + Send + Sync> = Arc::new(InMemoryStore::new());
+        let guard = LoginGuard {
+            config: LoginConfig {
+                base_delay_seconds: 0,
+                ..guard().config
+            },
+        };
+        for _ in 0..6 {
+            fail(&guard, &store, "user1@gmail.com", None).await;
+        }
+        assert!(matches!(
+            guard.reserve(&store, "user1@gmail.com", None).await,
+            Err(Error::LoginLocked(899..=900))
+        ));
+
+        guard
+            .record_success(&store, "user1@gmail.com", &[])
+            .await
+            .unwrap();
+        assert!(guard.reserve(&store, "user1@gmail.com", None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn concurrent_attempts_are_reserved_one_by_one() {
+        let store: Arc<dyn StoreMethods + Send + Sync> = Arc::new(InMemoryStore::new());
+        let guard = guard();
+        let ip = Some(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        for _ in 0..2 {
+            fail(&guard, &store, "user1@gmail.com", ip).await;
+        }
+        // The third attempt is delayed if it fails, so the fourth waits while it is verified
+        let reserved = guard.reserve(&store, "user1@gmail.com", ip).await.unwrap();
+        assert!(matches!(
+            guard.reserve(&store, "user1@gmail.com", ip).await,
+            Err(Error::LoginThrottled(4..=5))
+        ));
+        // Another account from the same ip is still answered
+        assert!(guard.reserve(&store, "user2@gmail.com", ip).await.is_ok());
+
+        guard
+            .record_success(&store, "user1@gmail.com", &reserved)
+            .await
+            .unwrap();
+        assert!(guard.reserve(&store, "user1@gmail.com", ip).await.is_ok());
+        // The ip keeps the failed logins but not the valid one
+        let ip_attempt = store.get_login_attempt("ip:127.0.0.1").await.unwrap();
+        assert_eq!(ip_attempt.unwrap().failed_count, 4);
+    }
+}
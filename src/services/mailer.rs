@@ -0,0 +1,261 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use rand::random;
+use tokio::sync::RwLock;
+use tracing::{event, Level};
+
+use crate::configs::config::{EmailConfig, MailerKind};
+use crate::errors::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait Mailer: fmt::Debug + Send + Sync {
+    async fn send(&self, mail: Mail) -> Result<(), Error>;
+}
+
+// Send emails through a smtp server
+pub struct SmtpMailer {
+    from: Mailbox,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl fmt::Debug for SmtpMailer {
+    // Credentials of the smtp server are never written into logs
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SmtpMailer")
+            .field("from", &self.from)
+            .finish()
+    }
+}
+
+impl SmtpMailer {
+    pub fn new(config: &EmailConfig) -> Result<SmtpMailer, Error> {
+        let smtp = config
+            .smtp
+            .as_ref()
+            .ok_or_else(|| Error::MailConfig("missing email.smtp".to_string()))?;
+        let builder = match smtp.starttls {
+            true => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp.host),
+            false => AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp.host),
+        }
+        .map_err(|e| Error::MailConfig(format!("invalid smtp host {}: {}", smtp.host, e)))?
+        .port(smtp.port);
+        let builder = match (&smtp.username, &smtp.password) {
+            (Some(username), Some(password)) => {
+                builder.credentials(Credentials::new(username.clone(), password.clone()))
+            }
+            _ => builder,
+        };
+        Ok(SmtpMailer {
+            from: parse_mailbox(&config.from)?,
+            transport: builder.build(),
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, mail: Mail) -> Result<(), Error> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(parse_mailbox(&mail.to)?)
+            .subject(mail.subject)
+            .body(mail.body)
+            .map_err(|e| Error::Mail(e.to_string()))?;
+        match self.transport.send(message).await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                event!(Level::ERROR, "Send email by smtp has error: {:?}", e);
+                Err(Error::Mail(e.to_string()))
+            }
+        }
+    }
+}
+
+// Write every email as a file, for local runs without smtp server
+#[derive(Debug, Clone)]
+pub struct FileMailer {
+    from: String,
+    dir: PathBuf,
+}
+
+impl FileMailer {
+    pub fn new(config: &EmailConfig) -> FileMailer {
+        FileMailer {
+            from: config.from.clone(),
+            dir: PathBuf::from(&config.file_dir),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, mail: Mail) -> Result<(), Error> {
+        let content = format!(
+            "From: {}\nTo: {}\nSubject: {}\n\n{}\n",
+            self.from, mail.to, mail.subject, mail.body
+        );
+        let name = format!(
+            "{}-{:08x}.eml",
+            Utc::now().timestamp_millis(),
+            random::<u32>()
+        );
+        let write = async {
+            tokio::fs::create_dir_all(&self.dir).await?;
+            tokio::fs::write(self.dir.join(name), content).await
+        };
+        match write.await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                event!(Level::ERROR, "Write email into file has error: {:?}", e);
+                Err(Error::Mail(e.to_string()))
+            }
+        }
+    }
+}
+
+// Keep every email in memory, tests read the tokens sent to users from it
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryMailer {
+    pub mails: Arc<RwLock<Vec<Mail>>>,
+}
+
+impl InMemoryMailer {
+    pub fn new() -> InMemoryMailer {
+        InMemoryMailer::default()
+    }
+
+    // Last email sent to the address
+    #[cfg(test)]
+    pub async fn last_mail_to(&self, to: &str) -> Option<Mail> {
+        self.mails
+            .read()
+            .await
+            .iter()
+            .rev()
+            .find(|mail| mail.to == to)
+            .cloned()
+    }
+}
+
+#[async_trait]
+impl Mailer for InMemoryMailer {
+    async fn send(&self, mail: Mail) -> Result<(), Error> {
+        self.mails.write().await.push(mail);
+        Ok(())
+    }
+}
+
+fn parse_mailbox(address: &str) -> Result<Mailbox, Error> {
+    address
+        .parse::<Mailbox>()
+        .map_err(|e| Error::Mail(format!("invalid address {}: {}", address, e)))
+}
+
+pub fn build_mailer(config: &EmailConfig) -> Result<Arc<dyn Mailer>, Error> {
+    let mailer: Arc<dyn Mailer> = match config.mailer {
+        MailerKind::Smtp => Arc::new(SmtpMailer::new(config)?),
+        MailerKind::File => Arc::new(FileMailer::new(config)),
+        MailerKind::Memory => Arc::new(InMemoryMailer::new()),
+    };
+    Ok(mailer)
+}
+
+// Emails of the account flows, the links open the pages of `email.app_url`
+#[derive(Debug, Clone)]
+pub struct AccountMailer {
+    pub config: EmailConfig,
+    mailer: Arc<dyn Mailer>,
+}
+
+impl AccountMailer {
+    pub fn new(config: EmailConfig, mailer: Arc<dyn Mailer>) -> AccountMailer {
+        AccountMailer { config, mailer }
+    }
+
+    pub async fn send_verification(&self, to: &str, token: &str) -> Result<(), Error> {
+        let link = format!("{}/verify-email?token={}", self.config.app_url, token);
+        self.mailer
+            .send(Mail {
+                to: to.to_string(),
+                subject: "Verify your email".to_string(),
+                body: format!(
+                    "Open the link below to verify your email, it expires in {} minutes.\n\n{}",
+                    self.config.verification_token_minutes, link
+                ),
+            })
+            .await
+    }
+
+    pub async fn send_password_reset(&self, to: &str, token: &str) -> Result<(), Error> {
+        let link = format!("{}/reset-password?token={}", self.config.app_url, token);
+        self.mailer
+            .send(Mail {
+                to: to.to_string(),
+                subject: "Reset your password".to_string(),
+                body: format!(
+                    "Open the link below to set a new password, it expires in {} minutes.\n\
+                     Ignore this email if you didn't ask for it.\n\n{}",
+                    self.config.reset_token_minutes, link
+                ),
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod mailer_tests {
+    use std::sync::Arc;
+
+    use super::{AccountMailer, FileMailer, InMemoryMailer, Mail, Mailer};
+    use crate::configs::config::EmailConfig;
+
+    #[tokio::test]
+    async fn verification_link_holds_token() {
+        let memory = InMemoryMailer::new();
+        let mailer = AccountMailer::new(EmailConfig::default(), Arc::new(memory.clone()));
+        mailer
+            .send_verification("user1@gmail.com", "abc.def.ghi")
+            .await
+            .unwrap();
+        let mail = memory.last_mail_to("user1@gmail.com").await.unwrap();
+        assert!(mail
+            .body
+            .contains("http://localhost:3030/verify-email?token=abc.def.ghi"));
+        assert!(memory.last_mail_to("user2@gmail.com").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn file_mailer_writes_email() {
+        let dir = std::env::temp_dir().join(format!("mails-{}", rand::random::<u32>()));
+        let config = EmailConfig {
+            file_dir: dir.to_string_lossy().to_string(),
+            ..EmailConfig::default()
+        };
+        FileMailer::new(&config)
+            .send(Mail {
+                to: "user1@gmail.com".to_string(),
+                subject: "Reset your password".to_string(),
+                body: "link".to_string(),
+            })
+            .await
+            .unwrap();
+        let file = std::fs::read_dir(&dir).unwrap().next().unwrap().unwrap();
+        let content = std::fs::read_to_string(file.path()).unwrap();
+        assert!(content.contains("To: user1@gmail.com\nSubject: Reset your password"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::HashSet;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+use utoipa::OpenApi;
+
+use crate::models::health::PoolStatus;
+use crate::utils::openapi::ApiDoc;
+
+// Label of requests which match no documented route, so unknown paths don't make a label each
+pub const UNMATCHED_ROUTE: &str = "unmatched";
+
+// Metrics of the process, shared by the routes and the password hashing
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+// Paths of the documented routes, `{id}` segments match any value
+static ROUTES: LazyLock<RouteTemplates> =
+    LazyLock::new(|| RouteTemplates::new(ApiDoc::openapi().paths.paths.keys().cloned().collect()));
+
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    db_pool_connections: IntGauge,
+    db_pool_idle_connections: IntGauge,
+    db_pool_max_connections: IntGauge,
+    password_hash_duration: HistogramVec,
+}
+
+impl Metrics {
+    fn new() -> Metrics {
+        let registry = Registry::new();
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "Handled requests"),
+            &["method", "route", "status"],
+        )
+        .unwrap();
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time to answer the request",
+            ),
+            &["method", "route"],
+        )
+        .unwrap();
+        let db_pool_connections = IntGauge::new(
+            "db_pool_connections",
+            "Open connections of the pool, idle ones included",
+        )
+        .unwrap();
+        let db_pool_idle_connections =
+            IntGauge::new("db_pool_idle_connections", "Idle connections of the pool").unwrap();
+        let db_pool_max_connections =
+            IntGauge::new("db_pool_max_connections", "Most connections of the pool").unwrap();
+        let password_hash_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "password_hash_duration_seconds",
+                "Time to hash or verify a password with argon2",
+            ),
+            &["operation"],
+        )
+        .unwrap();
+
+        registry.register(Box::new(http_requests.clone())).unwrap();
+        registry
+            .register(Box::new(http_request_duration.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(db_pool_connections.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(db_pool_idle_connections.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(db_pool_max_connections.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(password_hash_duration.clone()))
+            .unwrap();
+        Metrics {
+            registry,
+            http_requests,
+            http_request_duration,
+            db_pool_connections,
+            db_pool_idle_connections,
+            db_pool_max_connections,
+            password_hash_duration,
+        }
+    }
+
+    pub fn observe_request(&self, method: &str, path: &str, status: u16, elapsed: Duration) {
+        let route = ROUTES.route_of(path);
+        self.http_requests
+            .with_label_values(&[method, route, &status.to_string()])
+            .inc();
+        self.http_request_duration
+            .with_label_values(&[method, route])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    // Operation is hash or verify
+    pub fn observe_password_hash(&self, operation: &str, elapsed: Duration) {
+        self.password_hash_duration
+            .with_label_values(&[operation])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub fn set_pool_status(&self, status: Option<PoolStatus>) {
+        let status = status.unwrap_or(PoolStatus {
+            size: 0,
+            idle: 0,
+            max: 0,
+        });
+        self.db_pool_connections.set(status.size as i64);
+        self.db_pool_idle_connections.set(status.idle as i64);
+        self.db_pool_max_connections.set(status.max as i64);
+    }
+
+    // Every metric in the Prometheus text format
+    pub fn encode(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+}
+
+struct RouteTemplates {
+    exact: HashSet<String>,
+    // Templates with parameters, split into segments
+    templates: Vec<(String, Vec<String>)>,
+}
+
+impl RouteTemplates {
+    fn new(paths: Vec<String>) -> RouteTemplates {
+        let (templates, exact): (Vec<String>, Vec<String>) =
+            paths.into_iter().partition(|path| path.contains('{'));
+        RouteTemplates {
+            exact: exact.into_iter().collect(),
+            templates: templates
+                .into_iter()
+                .map(|path| {
+                    let segments = path.split('/').map(str::to_string).collect();
+                    (path, segments)
+                })
+                .collect(),
+        }
+    }
+
+    // Template of the route matching the path, a route without parameters wins
+    fn route_of(&self, path: &str) -> &str {
+        if let Some(route) = self.exact.get(path) {
+            return route;
+        }
+        let segments: Vec<&str> = path.split('/').collect();
+        self.templates
+            .iter()
+            .find(|(_, template)| {
+                template.len() == segments.len()
+                    && template.iter().zip(&segments).all(|(expected, segment)| {
+                        expected == segment || (expected.starts_with('{') && !segment.is_empty())
+                    })
+            })
+            .map_or(UNMATCHED_ROUTE, |(route, _)| route.as_str())
+    }
+}
+
+#[cfg(test)]
+mod metrics_tests {
+    use std::time::Duration;
+
+    use super::{RouteTemplates, METRICS, UNMATCHED_ROUTE};
+
+    #[test]
+    fn path_is_labeled_with_route_template() {
+        let routes = RouteTemplates::new(vec![
+            "/api/v1/job/get-job/{id}".to_string(),
+            "/api/v1/job/list-job".to_string(),
+            "/api/v1/user/{id}".to_string(),
+            "/api/v1/user/update-user".to_string(),
+        ]);
+        assert_eq!(
+            routes.route_of("/api/v1/job/get-job/12"),
+            "/api/v1/job/get-job/{id}"
+        );
+        assert_eq!(
+            routes.route_of("/api/v1/job/list-job"),
+            "/api/v1/job/list-job"
+        );
+        assert_eq!(
+            routes.route_of("/api/v1/user/update-user"),
+            "/api/v1/user/update-user"
+        );
+        assert_eq!(routes.route_of("/api/v1/job/get-job/"), UNMATCHED_ROUTE);
+        assert_eq!(routes.route_of("/wp-admin/login.php"), UNMATCHED_ROUTE);
+    }
+
+    #[test]
+    fn metrics_are_encoded() {
+        METRICS.observe_request("GET", "/healthz", 200, Duration::from_millis(3));
+        METRICS.observe_password_hash("verify", Duration::from_millis(40));
+        let text = METRICS.encode();
+        assert!(text.contains(r#"http_requests_total{method="GET",route="/healthz",status="200"}"#));
+        assert!(text.contains(r#"password_hash_duration_seconds_count{operation="verify"}"#));
+        assert!(text.contains("db_pool_connections"));
+    }
+}
@@ -1,2 +1,12 @@
+pub mod audit;
+pub mod blob_store;
+pub mod job_expiry;
 pub mod jwt;
+pub mod login_guard;
+pub mod mailer;
+pub mod metrics;
+pub mod rate_limiter;
+pub mod recommendation;
+pub mod resume_file;
+pub mod shutdown;
 pub mod telemetry;
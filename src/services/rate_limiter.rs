@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::configs::config::{RateLimitConfig, RateLimitQuota};
+
+// Buckets are swept once the limiter holds this many of them
+const MAX_BUCKETS: usize = 10_000;
+// Buckets dropped at least by a sweep, so the next one waits for as many new clients
+const EVICTED_BUCKETS: usize = MAX_BUCKETS / 10;
+
+// State of a quota after a request, sent to the client as RateLimit-* headers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    pub group: String,
+    pub limit: u32,
+    pub remaining: u32,
+    pub period_seconds: u64,
+    // Seconds until the bucket is full again
+    pub reset_seconds: u64,
+    // Seconds until the next request is allowed, set when the request is refused
+    pub retry_after: Option<u64>,
+}
+
+impl RateLimitStatus {
+    pub fn headers(&self) -> Vec<(&'static str, String)> {
+        let mut headers = vec![
+            ("ratelimit-limit", self.limit.to_string()),
+            ("ratelimit-remaining", self.remaining.to_string()),
+            ("ratelimit-reset", self.reset_seconds.to_string()),
+            (
+                "ratelimit-policy",
+                format!("{};w={}", self.limit, self.period_seconds),
+            ),
+        ];
+        if let Some(retry_after) = self.retry_after {
+            headers.push(("retry-after", retry_after.to_string()));
+        }
+        headers
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+    // Period of the quota, the bucket is full again once idle for that long
+    period: Duration,
+}
+
+// Token buckets per route group and client, kept in the memory of the instance
+#[derive(Debug)]
+pub struct RateLimiter {
+    pub config: RateLimitConfig,
+    buckets: Mutex<HashMap<(String, String), Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> RateLimiter {
+        RateLimiter {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Group of the path and its quota, the longest matching prefix wins
+    pub fn quota_of(&self, path: &str) -> (String, RateLimitQuota) {
+        let mut matched: Option<(usize, String, RateLimitQuota)> = None;
+        for group in &self.config.groups {
+            for prefix in &group.path_prefixes {
+                let prefix = prefix.trim_end_matches('/');
+                let matches = path == prefix
+                    || (path.starts_with(prefix) && path[prefix.len()..].starts_with('/'));
+                if matches && matched.as_ref().is_none_or(|(len, ..)| prefix.len() > *len) {
+                    let quota = RateLimitQuota {
+                        requests: group.requests,
+                        period_seconds: group.period_seconds,
+                    };
+                    matched = Some((prefix.len(), group.name.clone(), quota));
+                }
+            }
+        }
+        match matched {
+            Some((_, name, quota)) => (name, quota),
+            None => ("default".to_string(), self.config.default),
+        }
+    }
+
+    // Take a token of the bucket of the client for the group of the path
+    pub fn check(&self, path: &str, client: &str) -> Result<RateLimitStatus, RateLimitStatus> {
+        let (group, quota) = self.quota_of(path);
+        self.take(group, quota, client, Instant::now())
+    }
+
+    fn take(
+        &self,
+        group: String,
+        quota: RateLimitQuota,
+        client: &str,
+        now: Instant,
+    ) -> Result<RateLimitStatus, RateLimitStatus> {
+        let capacity = quota.requests.max(1) as f64;
+        let rate = capacity / quota.period_seconds.max(1) as f64;
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= MAX_BUCKETS {
+            evict(&mut buckets, now);
+        }
+        let bucket = buckets
+            .entry((group.clone(), client.to_string()))
+            .or_insert(Bucket {
+                tokens: capacity,
+                updated_at: now,
+                period: Duration::from_secs(quota.period_seconds.max(1)),
+            });
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.updated_at = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        let status = RateLimitStatus {
+            group,
+            limit: quota.requests,
+            remaining: bucket.tokens.floor() as u32,
+            period_seconds: quota.period_seconds,
+            reset_seconds: ((capacity - bucket.tokens) / rate).ceil() as u64,
+            retry_after: (!allowed).then(|| ((1.0 - bucket.tokens) / rate).ceil().max(1.0) as u64),
+        };
+        match allowed {
+            true => Ok(status),
+            false => Err(status),
+        }
+    }
+}
+
+// Drop the buckets idle for their whole period, they hold nothing more than a new
+// bucket would. When too few are, the least recently used ones are dropped too and
+// their clients start again with a full bucket
+fn evict(buckets: &mut HashMap<(String, String), Bucket>, now: Instant) {
+    buckets.retain(|_, bucket| now.duration_since(bucket.updated_at) < bucket.period);
+    let excess = (buckets.len() + EVICTED_BUCKETS).saturating_sub(MAX_BUCKETS);
+    if excess == 0 {
+        return;
+    }
+    let mut updated_at: Vec<Instant> = buckets.values().map(|bucket| bucket.updated_at).collect();
+    let (_, oldest_kept, _) = updated_at.select_nth_unstable(excess);
+    let oldest_kept = *oldest_kept;
+    buckets.retain(|_, bucket| bucket.updated_at >= oldest_kept);
+}
+
+#[cfg(test)]
+mod rate_limiter_tests {
+    use std::time::{Duration, Instant};
+
+    use super::{RateLimiter, EVICTED_BUCKETS, MAX_BUCKETS};
+    use crate::configs::config::{RateLimitConfig, RateLimitGroup, RateLimitQuota};
+
+    fn limiter() -> RateLimiter {
+        RateLimiter::new(RateLimitConfig {
+            enabled: true,
+            default: RateLimitQuota {
+                requests: 100,
+                period_seconds: 60,
+            },
+            groups: vec![
+                RateLimitGroup {
+                    name: "api".to_string(),
+                    path_prefixes: vec!["/api/v1".to_string()],
+                    requests: 50,
+                    period_seconds: 60,
+                },
+                RateLimitGroup {
+                    name: "auth".to_string(),
+                    path_prefixes: vec!["/api/v1/login".to_string()],
+                    requests: 3,
+                    period_seconds: 30,
+                },
+            ],
+        })
+    }
+
+    #[test]
+    fn longest_prefix_wins() {
+        let limiter = limiter();
+        assert_eq!(limiter.quota_of("/api/v1/login").0, "auth");
+        assert_eq!(limiter.quota_of("/api/v1/logout").0, "api");
+        assert_eq!(limiter.quota_of("/api/v1/login-all").0, "api");
+        assert_eq!(limiter.quota_of("/.well-known/jwks.json").0, "default");
+    }
+
+    #[test]
+    fn bucket_refills_over_period() {
+        let limiter = limiter();
+        let (group, quota) = limiter.quota_of("/api/v1/login");
+        let now = Instant::now();
+        let remaining: Vec<u32> = (0..3)
+            .map(|_| {
+                limiter
+                    .take(group.clone(), quota, "ip:127.0.0.1", now)
+                    .unwrap()
+                    .remaining
+            })
+            .collect();
+        assert_eq!(remaining, vec![2, 1, 0]);
+
+        let refused = limiter
+            .take(group.clone(), quota, "ip:127.0.0.1", now)
+            .unwrap_err();
+        assert_eq!(refused.retry_after, Some(10));
+        assert_eq!(refused.reset_seconds, 30);
+        // Other clients have their own bucket
+        assert!(limiter.take(group.clone(), quota, "user:1", now).is_ok());
+
+        let later = now + Duration::from_secs(10);
+        assert!(limiter
+            .take(group.clone(), quota, "ip:127.0.0.1", later)
+            .is_ok());
+        assert!(limiter.take(group, quota, "ip:127.0.0.1", later).is_err());
+    }
+
+    #[test]
+    fn least_recently_used_buckets_are_evicted() {
+        let limiter = limiter();
+        let (group, quota) = limiter.quota_of("/api/v1/jobs");
+        let now = Instant::now();
+        for i in 0..MAX_BUCKETS {
+            let at = now + Duration::from_millis(i as u64);
+            assert!(limiter
+                .take(group.clone(), quota, &format!("ip:{}", i), at)
+                .is_ok());
+        }
+        // No bucket is idle for the period, so the oldest make room
+        let at = now + Duration::from_secs(20);
+        assert!(limiter.take(group.clone(), quota, "user:1", at).is_ok());
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.len(), MAX_BUCKETS - EVICTED_BUCKETS + 1);
+        assert!(!buckets.contains_key(&(group.clone(), "ip:0".to_string())));
+        assert!(buckets.contains_key(&(group.clone(), format!("ip:{}", MAX_BUCKETS - 1))));
+        drop(buckets);
+
+        // Once the period has passed the buckets of the first clients are idle
+        let at = now + Duration::from_secs(100);
+        for i in 1..EVICTED_BUCKETS {
+            assert!(limiter
+                .take(group.clone(), quota, &format!("ip:new:{}", i), at)
+                .is_ok());
+        }
+        assert!(limiter.take(group, quota, "user:2", at).is_ok());
+        assert_eq!(limiter.buckets.lock().unwrap().len(), EVICTED_BUCKETS);
+    }
+}
@@ -0,0 +1,279 @@
+use std::collections::HashSet;
+
+use crate::models::job::{Job, JobId};
+use crate::models::job_search::tokenize;
+use crate::models::recommendation::{CandidateSuggestion, JobRecommendation, MatchScore};
+use crate::models::resume::{Resume, ResumeProfile};
+
+// Weights of the parts of the score, they add up to 100
+pub const SKILLS_WEIGHT: i32 = 50;
+pub const LOCATION_WEIGHT: i32 = 20;
+pub const LEVEL_WEIGHT: i32 = 15;
+pub const SALARY_WEIGHT: i32 = 15;
+// A resume matching this many skills gets the whole skills part, so a long
+// list of skills is not scored lower than a short one
+const FULL_SKILLS_MATCH: usize = 5;
+
+// Levels of jobs from the most junior, matched as words of the job level
+const LEVELS: [&[&str]; 5] = [
+    &["intern", "internship", "fresher", "trainee"],
+    &["junior"],
+    &["middle", "mid", "intermediate"],
+    &["senior"],
+    &["lead", "principal", "manager", "head", "architect"],
+];
+
+// Score how well a resume fits a job, every part is explained in the breakdown
+pub fn score_match(job: &Job, profile: &ResumeProfile) -> MatchScore {
+    let job_tokens: HashSet<String> = tokenize(&format!("{} {}", job.job_name, job.description))
+        .into_iter()
+        .collect();
+    // A skill of many words needs every word in the job
+    let matched_skills: Vec<String> = profile
+        .skills
+        .iter()
+        .filter(|skill| {
+            let tokens = tokenize(skill);
+            !tokens.is_empty() && tokens.iter().all(|token| job_tokens.contains(token))
+        })
+        .cloned()
+        .collect();
+    let skills = match profile.skills.len() {
+        0 => 0,
+        len => {
+            SKILLS_WEIGHT * matched_skills.len().min(FULL_SKILLS_MATCH) as i32
+                / len.min(FULL_SKILLS_MATCH) as i32
+        }
+    };
+
+    let job_location = job.location.trim().to_lowercase();
+    let location = profile.location.trim().to_lowercase();
+    let location = match location.is_empty() || job_location.is_empty() {
+        true => 0,
+        false if job_location.contains(&location) || location.contains(&job_location) => {
+            LOCATION_WEIGHT
+        }
+        false => 0,
+    };
+
+    let level = match job_level(&job.job_level) {
+        Some(job_level) => match (job_level - candidate_level(profile.years_of_experience)).abs() {
+            0 => LEVEL_WEIGHT,
+            1 => LEVEL_WEIGHT / 2,
+            _ => 0,
+        },
+        None => 0,
+    };
+
+    let salary = match profile.expected_salary {
+        // Unknown expectation is half way between a match and a miss
+        None => SALARY_WEIGHT / 2,
+        Some(expected) if job.salary >= expected => SALARY_WEIGHT,
+        // Close enough to be negotiated
+        Some(expected) if job.salary as i64 * 10 >= expected as i64 * 8 => SALARY_WEIGHT / 2,
+        Some(_) => 0,
+    };
+
+    MatchScore {
+        total: skills + location + level + salary,
+        skills,
+        location,
+        level,
+        salary,
+        matched_skills,
+    }
+}
+
+// Index in `LEVELS` of the job level, None when no level is recognized
+pub fn job_level(job_level: &str) -> Option<i32> {
+    let tokens = tokenize(job_level);
+    LEVELS
+        .iter()
+        .position(|names| tokens.iter().any(|token| names.contains(&token.as_str())))
+        .map(|level| level as i32)
+}
+
+// Index in `LEVELS` a candidate usually applies to
+pub fn candidate_level(years_of_experience: i32) -> i32 {
+    match years_of_experience {
+        i32::MIN..=0 => 0,
+        1..=2 => 1,
+        3..=4 => 2,
+        5..=7 => 3,
+        _ => 4,
+    }
+}
+
+// Best jobs for the resumes of a candidate, each job scored with its best resume.
+// Jobs without any matching part are left out
+pub fn recommend_jobs(
+    jobs: &[Job],
+    resumes: &[Resume],
+    excluded: &HashSet<JobId>,
+    limit: usize,
+) -> Vec<JobRecommendation> {
+    let mut recommendations: Vec<JobRecommendation> = jobs
+        .iter()
+        .filter(|job| !job.is_delete && !excluded.contains(job.id.as_ref().unwrap()))
+        .filter_map(|job| {
+            resumes
+                .iter()
+                .filter(|resume| !resume.is_delete)
+                .map(|resume| (resume, score_match(job, &resume.profile)))
+                // The first resume wins a tie
+                .reduce(|best, next| match next.1.total > best.1.total {
+                    true => next,
+                    false => best,
+                })
+                .map(|(resume, score)| JobRecommendation {
+                    job: job.clone(),
+                    resume_id: resume.id.clone().unwrap(),
+                    score,
+                })
+        })
+        .filter(|recommendation| has_match(&recommendation.score))
+        .collect();
+    // Newer jobs first among equal scores
+    recommendations.sort_by_key(|recommendation| {
+        (
+            -recommendation.score.total,
+            -recommendation.job.id.as_ref().unwrap().0,
+        )
+    });
+    recommendations.truncate(limit);
+    recommendations
+}
+
+// Best resumes for a job, among the resumes given by the caller
+pub fn suggest_candidates(job: &Job, resumes: &[Resume], limit: usize) -> Vec<CandidateSuggestion> {
+    let mut suggestions: Vec<CandidateSuggestion> = resumes
+        .iter()
+        .filter(|resume| !resume.is_delete)
+        .map(|resume| CandidateSuggestion {
+            resume: resume.clone(),
+            score: score_match(job, &resume.profile),
+        })
+        .filter(|suggestion| has_match(&suggestion.score))
+        .collect();
+    suggestions.sort_by_key(|suggestion| {
+        (
+            -suggestion.score.total,
+            -suggestion.resume.id.as_ref().unwrap().0,
+        )
+    });
+    suggestions.truncate(limit);
+    suggestions
+}
+
+// The neutral salary part alone is no reason to suggest anything
+fn has_match(score: &MatchScore) -> bool {
+    score.skills + score.location + score.level > 0
+}
+
+#[cfg(test)]
+mod recommendation_tests {
+    use std::collections::HashSet;
+
+    use super::{
+        candidate_level, job_level, recommend_jobs, score_match, LEVEL_WEIGHT, LOCATION_WEIGHT,
+        SALARY_WEIGHT, SKILLS_WEIGHT,
+    };
+    use crate::models::job::{job_fixture, Job, JobId};
+    use crate::models::resume::{Resume, ResumeId, ResumeProfile};
+    use crate::models::user::UserId;
+
+    fn job(id: i32, job_name: &str, job_level: &str, salary: i32) -> Job {
+        Job {
+            id: Some(JobId(id)),
+            job_name: job_name.to_string(),
+            location: "Ha Noi".to_string(),
+            salary,
+            job_level: job_level.to_string(),
+            description: "Services in Rust with PostgreSQL".to_string(),
+            ..job_fixture()
+        }
+    }
+
+    fn profile(skills: &[&str], years_of_experience: i32) -> ResumeProfile {
+        ResumeProfile {
+            location: "ha noi".to_string(),
+            skills: skills.iter().map(|skill| skill.to_string()).collect(),
+            years_of_experience,
+            expected_salary: Some(20_000_000),
+            ..ResumeProfile::default()
+        }
+    }
+
+    #[test]
+    fn score_is_explained_by_parts() {
+        let job = job(1, "Senior backend developer", "Senior", 25_000_000);
+        let score = score_match(&job, &profile(&["Rust", "PostgreSQL", "Kafka"], 6));
+        assert_eq!(score.matched_skills, vec!["Rust", "PostgreSQL"]);
+        assert_eq!(
+            (score.skills, score.location, score.level, score.salary),
+            (
+                SKILLS_WEIGHT * 2 / 3,
+                LOCATION_WEIGHT,
+                LEVEL_WEIGHT,
+                SALARY_WEIGHT
+            )
+        );
+        assert_eq!(
+            score.total,
+            score.skills + score.location + score.level + score.salary
+        );
+
+        // One level away and a salary a bit under the expectation
+        let job = Job {
+            salary: 17_000_000,
+            ..job
+        };
+        let score = score_match(&job, &profile(&["Rust"], 3));
+        assert_eq!(
+            (score.skills, score.level, score.salary),
+            (SKILLS_WEIGHT, LEVEL_WEIGHT / 2, SALARY_WEIGHT / 2)
+        );
+    }
+
+    #[test]
+    fn levels_of_jobs_and_candidates() {
+        assert_eq!(job_level("Intern"), Some(0));
+        assert_eq!(job_level("Mid-level"), Some(2));
+        assert_eq!(job_level("Tech Lead"), Some(4));
+        assert_eq!(job_level("Any"), None);
+        assert_eq!(candidate_level(0), 0);
+        assert_eq!(candidate_level(4), 2);
+        assert_eq!(candidate_level(12), 4);
+    }
+
+    #[test]
+    fn applied_and_unmatched_jobs_are_not_recommended() {
+        let jobs = vec![
+            job(1, "Junior Rust developer", "Junior", 10_000_000),
+            job(2, "Senior Rust developer", "Senior", 30_000_000),
+            Job {
+                location: "Da Nang".to_string(),
+                description: "Marketing".to_string(),
+                ..job(3, "Accountant", "Any", 10_000_000)
+            },
+        ];
+        let resumes = vec![Resume {
+            id: Some(ResumeId(1)),
+            user_id: UserId(7),
+            email: "user2@gmail.com".to_string(),
+            url: "https://cv.example.com/user2.pdf".to_string(),
+            is_delete: false,
+            file: None,
+            profile: profile(&["Rust"], 6),
+            is_searchable: false,
+        }];
+        let excluded = HashSet::from([JobId(1)]);
+        let recommendations = recommend_jobs(&jobs, &resumes, &excluded, 10);
+        let ids: Vec<i32> = recommendations
+            .iter()
+            .map(|recommendation| recommendation.job.id.clone().unwrap().0)
+            .collect();
+        assert_eq!(ids, vec![2]);
+        assert_eq!(recommendations[0].resume_id, ResumeId(1));
+    }
+}
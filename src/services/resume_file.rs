@@ -0,0 +1,201 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use rand::random;
+use tracing::{event, Level};
+
+use crate::errors::Error;
+use crate::models::resume::{FileUpload, ResumeFile};
+use crate::models::user::UserId;
+use crate::services::blob_store::BlobStore;
+
+// Longest text kept from a file, enough for any real resume
+const MAX_FILE_TEXT_CHARS: usize = 100_000;
+
+// Files of the resumes, the limits are checked before the content is stored
+#[derive(Debug, Clone)]
+pub struct ResumeFiles {
+    pub max_file_bytes: u64,
+    blobs: Arc<dyn BlobStore>,
+}
+
+impl ResumeFiles {
+    pub fn new(max_file_bytes: u64, blobs: Arc<dyn BlobStore>) -> ResumeFiles {
+        ResumeFiles {
+            max_file_bytes,
+            blobs,
+        }
+    }
+
+    pub async fn save(&self, user_id: &UserId, upload: FileUpload) -> Result<ResumeFile, Error> {
+        if upload.content.len() as u64 > self.max_file_bytes {
+            return Err(Error::FileTooLarge(self.max_file_bytes));
+        }
+        let (content_type, extension) = upload.resume_kind()?;
+        // Random part so keys of the same user never collide
+        let key = format!(
+            "resumes/{}/{}-{:016x}.{}",
+            user_id.0,
+            Utc::now().timestamp_millis(),
+            random::<u64>(),
+            extension
+        );
+        let text = match extension {
+            "pdf" => pdf_text(upload.content.clone()).await,
+            _ => String::new(),
+        };
+        let file = ResumeFile {
+            key,
+            name: upload.safe_file_name(extension),
+            content_type: content_type.to_string(),
+            size: upload.content.len() as i64,
+            text,
+        };
+        self.blobs
+            .put(&file.key, content_type, upload.content)
+            .await?;
+        Ok(file)
+    }
+
+    pub async fn load(&self, file: &ResumeFile) -> Result<Vec<u8>, Error> {
+        self.blobs.get(&file.key).await
+    }
+
+    // Called once the file is no longer referenced, a failure leaves an orphan
+    // file behind instead of failing the request
+    pub async fn remove(&self, file: &ResumeFile) {
+        if let Err(e) = self.blobs.delete(&file.key).await {
+            event!(Level::ERROR, "Remove resume file has error: {:?}", e);
+        }
+    }
+}
+
+// Plain text of the pdf with whitespace collapsed, empty when the pdf has no text
+// layer or can't be read, the upload is kept either way
+async fn pdf_text(content: Vec<u8>) -> String {
+    let extracted = tokio::task::spawn_blocking(move || {
+        // The parser panics on some malformed files
+        std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(&content))
+    })
+    .await;
+    match extracted {
+        Ok(Ok(Ok(text))) => text
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .take(MAX_FILE_TEXT_CHARS)
+            .collect(),
+        Ok(Ok(Err(e))) => {
+            event!(
+                Level::WARN,
+                "Extract text of resume file has error: {:?}",
+                e
+            );
+            String::new()
+        }
+        _ => {
+            event!(Level::WARN, "Extract text of resume file has panicked");
+            String::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod resume_file_tests {
+    use std::sync::Arc;
+
+    use super::ResumeFiles;
+    use crate::errors::Error;
+    use crate::models::resume::{FileUpload, PDF_CONTENT_TYPE};
+    use crate::models::user::UserId;
+    use crate::services::blob_store::InMemoryBlobStore;
+
+    fn upload(content: &[u8]) -> FileUpload {
+        FileUpload {
+            file_name: "cv.pdf".to_string(),
+            content_type: PDF_CONTENT_TYPE.to_string(),
+            content: content.to_vec(),
+        }
+    }
+
+    #[tokio::test]
+    async fn file_is_stored_under_user_key() {
+        let blobs = InMemoryBlobStore::new();
+        let files = ResumeFiles::new(16, Arc::new(blobs.clone()));
+        let file = files
+            .save(&UserId(7), upload(b"%PDF-1.7 cv"))
+            .await
+            .unwrap();
+        assert!(file.key.starts_with("resumes/7/") && file.key.ends_with(".pdf"));
+        assert_eq!((file.name.as_str(), file.size), ("cv.pdf", 11));
+        assert_eq!(files.load(&file).await.unwrap(), b"%PDF-1.7 cv");
+
+        files.remove(&file).await;
+        assert!(blobs.blobs.read().await.is_empty());
+    }
+
+    // Single page pdf showing the text with a standard font
+    fn pdf(text: &str) -> Vec<u8> {
+        let stream = format!("BT /F1 12 Tf 72 720 Td ({}) Tj ET", text);
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] \
+                /Resources << /Font << /F1 4 0 R >> >> /Contents 5 0 R >>"
+                .to_string(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+            format!(
+                "<< /Length {} >>\nstream\n{}\nendstream",
+                stream.len(),
+                stream
+            ),
+        ];
+        let mut content = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(content.len());
+            content.extend(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).bytes());
+        }
+        let xref = content.len();
+        content.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).bytes());
+        for offset in offsets {
+            content.extend(format!("{:010} 00000 n \n", offset).bytes());
+        }
+        content.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF",
+                objects.len() + 1,
+                xref
+            )
+            .bytes(),
+        );
+        content
+    }
+
+    #[tokio::test]
+    async fn text_of_pdf_is_extracted() {
+        let files = ResumeFiles::new(4096, Arc::new(InMemoryBlobStore::new()));
+        let file = files
+            .save(&UserId(7), upload(&pdf("Rust   developer since 2019")))
+            .await
+            .unwrap();
+        assert_eq!(file.text, "Rust developer since 2019");
+
+        // Unreadable content is stored without text
+        let file = files
+            .save(&UserId(7), upload(b"%PDF-1.7 cv"))
+            .await
+            .unwrap();
+        assert_eq!(file.text, "");
+    }
+
+    #[tokio::test]
+    async fn large_file_is_refused() {
+        let files = ResumeFiles::new(8, Arc::new(InMemoryBlobStore::new()));
+        assert!(matches!(
+            files.save(&UserId(7), upload(b"%PDF-1.7 cv")).await,
+            Err(Error::FileTooLarge(8))
+        ));
+    }
+}
@@ -0,0 +1,89 @@
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tracing::{event, Level};
+
+// Resolve on the first SIGINT, or SIGTERM on unix as sent by docker and kubernetes
+pub async fn shutdown_signal() {
+    let interrupt = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to listen to SIGINT");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to listen to SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => event!(Level::INFO, "Received SIGINT, shutting down"),
+        _ = terminate => event!(Level::INFO, "Received SIGTERM, shutting down"),
+    }
+}
+
+// Wait for the server to finish the requests in flight, false when the
+// timeout passed first and the task is aborted, dropping the remaining
+// connections and the pooled connections they hold
+pub async fn drain<T>(mut server: JoinHandle<T>, timeout: Duration) -> bool {
+    if tokio::time::timeout(timeout, &mut server).await.is_ok() {
+        return true;
+    }
+    server.abort();
+    // Resolves once the task is dropped
+    let _ = server.await;
+    false
+}
+
+// Run a cleanup step like closing the store, false when it is given up after the timeout
+pub async fn within<F: Future>(step: F, timeout: Duration) -> bool {
+    tokio::time::timeout(timeout, step).await.is_ok()
+}
+
+#[cfg(test)]
+mod shutdown_tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::{drain, within};
+
+    // Flags the task was dropped, as the connections it holds are
+    struct Dropped(Arc<AtomicBool>);
+
+    impl Drop for Dropped {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn drained_server_finishes_before_timeout() {
+        let server = tokio::spawn(tokio::time::sleep(Duration::from_millis(10)));
+        assert!(drain(server, Duration::from_secs(1)).await);
+    }
+
+    #[tokio::test]
+    async fn stuck_server_is_aborted_after_timeout() {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let guard = Dropped(dropped.clone());
+        let server = tokio::spawn(async move {
+            let _guard = guard;
+            std::future::pending::<()>().await
+        });
+        assert!(!drain(server, Duration::from_millis(10)).await);
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn stuck_step_is_given_up_after_timeout() {
+        assert!(within(async {}, Duration::from_millis(10)).await);
+        assert!(!within(std::future::pending::<()>(), Duration::from_millis(10)).await);
+    }
+}
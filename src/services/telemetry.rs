@@ -1,8 +1,9 @@
-use opentelemetry::trace::TracerProvider;
+use opentelemetry::trace::TracerProvider as _;
 use opentelemetry::{global, KeyValue};
 use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_sdk::propagation::TraceContextPropagator;
 use opentelemetry_sdk::{runtime, trace, Resource};
+use tracing::{event, Level};
 use tracing_bunyan_formatter::{BunyanFormattingLayer, JsonStorageLayer};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
@@ -18,6 +19,9 @@ use tracing_subscriber::{EnvFilter, Registry};
 // * `server_jaeger_port` - A string slice representing the port of jaeger listening.
 // * `log_level` - A string slice representing the level of log.
 //
+// The provider is also set as the global provider, it is returned so the
+// spans still buffered can be exported by `shutdown_telemetry`.
+//
 // # Panics
 //
 // This function will panic if it fails to initialize the tracer.
@@ -28,14 +32,14 @@ pub fn init_telemetry(
     server_host: &str,
     server_jaeger_port: &u16,
     log_level: &str,
-) {
+) -> trace::TracerProvider {
     // Create a gRPC exporter
     let exporter = opentelemetry_otlp::new_exporter()
         .tonic()
         .with_endpoint(format!("http://{}:{}", server_host, server_jaeger_port));
 
     // Define a tracer
-    let tracer = opentelemetry_otlp::new_pipeline()
+    let provider = opentelemetry_otlp::new_pipeline()
         .tracing()
         .with_exporter(exporter)
         .with_trace_config(trace::Config::default().with_resource(Resource::new(vec![
@@ -45,8 +49,9 @@ pub fn init_telemetry(
             ),
         ])))
         .install_batch(runtime::Tokio)
-        .expect("Error: Failed to initialize the tracer.")
-        .tracer(service_name.to_string());
+        .expect("Error: Failed to initialize the tracer.");
+    let tracer = provider.tracer(service_name.to_string());
+    global::set_tracer_provider(provider.clone());
 
     // Define a subscriber
     let subscriber = Registry::default();
@@ -64,5 +69,18 @@ pub fn init_telemetry(
         .with(tracing_layer)
         .with(JsonStorageLayer)
         .with(formatting_layer)
-        .init()
+        .init();
+    provider
+}
+
+// Export the spans still buffered and stop the exporter, the tracing layer
+// keeps its own handle on the provider so it is shut down explicitly
+pub async fn shutdown_telemetry(provider: trace::TracerProvider) {
+    global::shutdown_tracer_provider();
+    // The batch processor blocks until the exporter answered
+    match tokio::task::spawn_blocking(move || provider.shutdown()).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => event!(Level::ERROR, "Shut down tracer provider has error: {:?}", e),
+        Err(e) => event!(Level::ERROR, "Shut down tracer provider has error: {:?}", e),
+    }
 }
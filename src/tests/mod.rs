@@ -1,2 +1,6 @@
 #[cfg(test)]
+mod openapi_test;
+#[cfg(test)]
+mod recommendation_test;
+#[cfg(test)]
 mod route_test;
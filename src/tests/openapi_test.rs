@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use std::fs;
+
+use regex::Regex;
+use utoipa::OpenApi;
+
+use crate::utils::openapi::ApiDoc;
+
+// Read every route of `src/routes`, a route is a filter statement ending with a handler
+fn declared_routes() -> Vec<(String, String)> {
+    let segment = Regex::new(
+        r#"warp::path\(base_path\)|warp::path\("([^"]+)"\)|warp::path::param::<\w+>\(\)|\b(\w+_path)\b"#,
+    )
+    .unwrap();
+    let method = Regex::new(r"warp::(get|post|put|delete)\(\)").unwrap();
+    let prefix = Regex::new(r"let (\w+_path) = ([^;]*);").unwrap();
+
+    let resolve = |text: &str, prefixes: &HashMap<String, String>| -> String {
+        let mut path = String::new();
+        for caps in segment.captures_iter(text) {
+            let part = match (caps.get(1), caps.get(2)) {
+                (Some(literal), _) => literal.as_str().to_string(),
+                (None, Some(name)) => match prefixes.get(name.as_str()) {
+                    Some(prefix) => {
+                        path.push_str(prefix);
+                        continue;
+                    }
+                    None => continue,
+                },
+                (None, None) if caps[0].contains("param") => "{id}".to_string(),
+                (None, None) => "api".to_string(),
+            };
+            path.push('/');
+            path.push_str(&part);
+        }
+        path
+    };
+
+    let mut routes = Vec::new();
+    for entry in fs::read_dir("src/routes").unwrap() {
+        let source = fs::read_to_string(entry.unwrap().path()).unwrap();
+        let mut prefixes = HashMap::new();
+        for caps in prefix.captures_iter(&source) {
+            let path = resolve(&caps[2], &prefixes);
+            prefixes.insert(caps[1].to_string(), path);
+        }
+        for statement in source.split(';').filter(|s| s.contains(".and_then(")) {
+            let method = method
+                .captures(statement)
+                .map(|caps| caps[1].to_string())
+                .unwrap_or_default();
+            routes.push((method, resolve(statement, &prefixes)));
+        }
+    }
+    routes
+}
+
+#[test]
+fn every_route_is_documented() {
+    let openapi = ApiDoc::openapi();
+    let routes = declared_routes();
+    assert!(routes.len() > 40, "routes of src/routes weren't found");
+
+    let undocumented: Vec<String> = routes
+        .iter()
+        .filter(|(method, path)| {
+            let item = openapi.paths.paths.get(path);
+            let operation = item.and_then(|item| match method.as_str() {
+                "get" => item.get.as_ref(),
+                "post" => item.post.as_ref(),
+                "put" => item.put.as_ref(),
+                "delete" => item.delete.as_ref(),
+                _ => None,
+            });
+            operation.is_none()
+        })
+        .map(|(method, path)| format!("{} {}", method.to_uppercase(), path))
+        .collect();
+    assert!(
+        undocumented.is_empty(),
+        "routes without OpenAPI documentation: {:?}",
+        undocumented
+    );
+}
+
+#[test]
+fn document_is_openapi_3_1() {
+    let document = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    assert_eq!(document["openapi"], "3.1.0");
+    assert!(document["components"]["schemas"]["Problem"].is_object());
+    assert!(document["components"]["securitySchemes"]["bearer_auth"].is_object());
+    assert!(
+        document["paths"]["/api/v1/job/get-job/{id}"]["get"]["responses"]["default"].is_object()
+    );
+}
+
+#[test]
+fn created_resources_are_documented_as_201() {
+    let document = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    for path in [
+        "/api/v1/register",
+        "/api/v1/job/create-job",
+        "/api/v1/resume/create-resume",
+        "/api/v1/company/create-company",
+        "/api/v1/role/create-role",
+    ] {
+        let responses = &document["paths"][path]["post"]["responses"];
+        assert!(
+            responses["201"].is_object(),
+            "{} isn't documented as 201",
+            path
+        );
+        assert!(responses["200"].is_null(), "{} is documented as 200", path);
+    }
+}
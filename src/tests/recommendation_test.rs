@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+
+use serde::Deserialize;
+
+use crate::models::job::{job_fixture, Job, JobId};
+use crate::models::resume::{Resume, ResumeId, ResumeProfile};
+use crate::models::user::UserId;
+use crate::services::recommendation::{recommend_jobs, suggest_candidates};
+
+// Offline evaluation of the recommendations: every candidate of the fixture is
+// labeled with the jobs they are relevant for, the ranking of both routes is
+// measured against those labels
+const FIXTURE: &str = include_str!("fixtures/recommendation.json");
+// Enough to rank every job or candidate of the fixture
+const LIMIT: usize = 50;
+
+#[derive(Deserialize)]
+struct Fixture {
+    jobs: Vec<FixtureJob>,
+    candidates: Vec<FixtureCandidate>,
+}
+
+#[derive(Deserialize)]
+struct FixtureJob {
+    id: i32,
+    job_name: String,
+    location: String,
+    job_level: String,
+    salary: i32,
+    description: String,
+}
+
+#[derive(Deserialize)]
+struct FixtureCandidate {
+    id: i32,
+    profile: ResumeProfile,
+    relevant_jobs: Vec<i32>,
+}
+
+struct Metrics {
+    precision_at_1: f64,
+    mean_reciprocal_rank: f64,
+}
+
+fn load() -> (Vec<Job>, Vec<Resume>, Vec<FixtureCandidate>) {
+    let fixture: Fixture = serde_json::from_str(FIXTURE).unwrap();
+    let jobs = fixture
+        .jobs
+        .into_iter()
+        .map(|job| Job {
+            id: Some(JobId(job.id)),
+            job_name: job.job_name,
+            location: job.location,
+            salary: job.salary,
+            job_level: job.job_level,
+            description: job.description,
+            ..job_fixture()
+        })
+        .collect();
+    let resumes = fixture
+        .candidates
+        .iter()
+        .map(|candidate| Resume {
+            id: Some(ResumeId(candidate.id)),
+            user_id: UserId(candidate.id),
+            email: format!("candidate{}@gmail.com", candidate.id),
+            url: format!("https://cv.example.com/candidate{}.pdf", candidate.id),
+            is_delete: false,
+            file: None,
+            profile: candidate.profile.clone(),
+            is_searchable: true,
+        })
+        .collect();
+    (jobs, resumes, fixture.candidates)
+}
+
+// Precision of the first result and reciprocal rank of the first relevant
+// result, averaged over the rankings
+fn evaluate(rankings: &[(Vec<i32>, HashSet<i32>)]) -> Metrics {
+    let mut precision_at_1 = 0.0;
+    let mut mean_reciprocal_rank = 0.0;
+    for (ranking, relevant) in rankings {
+        if ranking.first().is_some_and(|id| relevant.contains(id)) {
+            precision_at_1 += 1.0;
+        }
+        if let Some(rank) = ranking.iter().position(|id| relevant.contains(id)) {
+            mean_reciprocal_rank += 1.0 / (rank + 1) as f64;
+        }
+    }
+    Metrics {
+        precision_at_1: precision_at_1 / rankings.len() as f64,
+        mean_reciprocal_rank: mean_reciprocal_rank / rankings.len() as f64,
+    }
+}
+
+#[test]
+fn recommended_jobs_rank_relevant_jobs_first() {
+    let (jobs, resumes, candidates) = load();
+    let rankings: Vec<(Vec<i32>, HashSet<i32>)> = candidates
+        .iter()
+        .zip(resumes.chunks(1))
+        .map(|(candidate, resume)| {
+            let ranking = recommend_jobs(&jobs, resume, &HashSet::new(), LIMIT)
+                .iter()
+                .map(|recommendation| recommendation.job.id.clone().unwrap().0)
+                .collect();
+            (ranking, candidate.relevant_jobs.iter().cloned().collect())
+        })
+        .collect();
+    let metrics = evaluate(&rankings);
+    assert!(
+        metrics.precision_at_1 >= 0.85,
+        "precision@1 {}",
+        metrics.precision_at_1
+    );
+    assert!(
+        metrics.mean_reciprocal_rank >= 0.9,
+        "mrr {}",
+        metrics.mean_reciprocal_rank
+    );
+}
+
+#[test]
+fn suggested_candidates_rank_relevant_candidates_first() {
+    let (jobs, resumes, candidates) = load();
+    let rankings: Vec<(Vec<i32>, HashSet<i32>)> = jobs
+        .iter()
+        .map(|job| {
+            let job_id = job.id.clone().unwrap().0;
+            let ranking = suggest_candidates(job, &resumes, LIMIT)
+                .iter()
+                .map(|suggestion| suggestion.resume.id.clone().unwrap().0)
+                .collect();
+            let relevant = candidates
+                .iter()
+                .filter(|candidate| candidate.relevant_jobs.contains(&job_id))
+                .map(|candidate| candidate.id)
+                .collect();
+            (ranking, relevant)
+        })
+        .collect();
+    let metrics = evaluate(&rankings);
+    assert!(
+        metrics.precision_at_1 >= 0.85,
+        "precision@1 {}",
+        metrics.precision_at_1
+    );
+    assert!(
+        metrics.mean_reciprocal_rank >= 0.9,
+        "mrr {}",
+        metrics.mean_reciprocal_rank
+    );
+}
@@ -1,41 +1,153 @@
+use rand::random;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use warp::http::{HeaderValue, StatusCode};
+use warp::Reply;
 
+use crate::models::application::ApplicationStatusChange;
+use crate::models::audit::AuditEntry;
 use crate::models::company::Company;
 use crate::models::job::Job;
 use crate::models::map_resume_job::MapResumeJob;
+use crate::models::pagination::Page;
+use crate::models::recommendation::{CandidateSuggestion, JobRecommendation};
 use crate::models::resume::Resume;
-use crate::models::role::Role;
+use crate::models::role::{Role, RolePermissions};
 use crate::models::user::UserInfo;
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
 pub enum Data {
     UserInfo(UserInfo),
     ListUserInfo(Vec<UserInfo>),
 
     Company(Company),
     Role(Role),
+    RolePermissions(RolePermissions),
     Job(Job),
-    Resume(Resume),
+    // Boxed since the profile makes it much larger than the other variants
+    Resume(Box<Resume>),
     MapJobResume(MapResumeJob),
+    ApplicationTimeline(Vec<ApplicationStatusChange>),
 
     ListCompany(Vec<Company>),
     ListRole(Vec<Role>),
     ListJob(Vec<Job>),
     ListResume(Vec<Resume>),
+    ListAudit(Vec<AuditEntry>),
+    ListJobRecommendation(Vec<JobRecommendation>),
+    ListCandidateSuggestion(Vec<CandidateSuggestion>),
 }
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
 pub struct PayloadWithData {
     pub message: String,
     pub data: Data,
+    // Pagination fields of list responses, flattened next to data
+    #[serde(flatten)]
+    pub page: Option<PageInfo>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct PageInfo {
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
+}
+
+impl PageInfo {
+    pub fn new<T>(page: &Page<T>, total: Option<i64>) -> PageInfo {
+        PageInfo {
+            next_cursor: page.next_cursor.as_ref().map(|cursor| cursor.encode()),
+            has_more: page.has_more,
+            total,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
 pub struct PayloadNoData {
     pub message: String,
 }
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
 pub struct PayloadForLogin {
     pub message: String,
     pub access_token: String,
+    pub refresh_token: String,
     pub data: Data,
 }
+
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+
+// Error response of every failed request, following RFC 7807
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    // Extension members, the code is stable for clients to match on
+    pub code: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<ProblemDetail>,
+    pub request_id: String,
+    // Headers sent with the response, like Retry-After
+    #[serde(skip)]
+    pub headers: Vec<(&'static str, String)>,
+}
+
+// Error of a single field of the request
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct ProblemDetail {
+    pub field: String,
+    pub message: String,
+}
+
+impl Problem {
+    pub fn new(status: StatusCode, code: &str, detail: impl Into<String>) -> Problem {
+        Problem {
+            problem_type: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or_default().to_string(),
+            status: status.as_u16(),
+            detail: detail.into(),
+            code: code.to_string(),
+            details: Vec::new(),
+            // Logged together with the error, so a response can be traced back
+            request_id: random::<[u8; 16]>()
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect(),
+            headers: Vec::new(),
+        }
+    }
+
+    pub fn with_details(mut self, details: Vec<ProblemDetail>) -> Problem {
+        self.details = details;
+        self
+    }
+
+    pub fn with_header(mut self, name: &'static str, value: impl Into<String>) -> Problem {
+        self.headers.push((name, value.into()));
+        self
+    }
+
+    pub fn with_retry_after(self, seconds: i64) -> Problem {
+        self.with_header("retry-after", seconds.max(1).to_string())
+    }
+
+    pub fn into_reply(self) -> warp::reply::Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let request_id = self.request_id.clone();
+        let headers = self.headers.clone();
+        let reply = warp::reply::with_status(warp::reply::json(&self), status);
+        let reply = warp::reply::with_header(reply, "content-type", PROBLEM_CONTENT_TYPE);
+        let mut response =
+            warp::reply::with_header(reply, "x-request-id", request_id).into_response();
+        for (name, value) in headers {
+            if let Ok(value) = HeaderValue::from_str(&value) {
+                response.headers_mut().insert(name, value);
+            }
+        }
+        response
+    }
+}
@@ -1 +1,2 @@
 pub mod convert_to_json;
+pub mod openapi;
@@ -0,0 +1,184 @@
+use utoipa::openapi::security::{Http, HttpAuthScheme, SecurityScheme};
+use utoipa::openapi::{ContentBuilder, Ref, ResponseBuilder};
+use utoipa::{Modify, OpenApi};
+
+use crate::controllers;
+use crate::models::application::{
+    ApplicationStatus, ApplicationStatusChange, ApplicationStatusChangeId, UpdateApplicationStatus,
+    WithdrawApplication,
+};
+use crate::models::audit::{AuditAction, AuditEntity, AuditEntry, AuditEntryId};
+use crate::models::company::{Company, CompanyId, NewCompany};
+use crate::models::email_token::{EmailRequest, ResetPasswordRequest, VerifyEmailRequest};
+use crate::models::health::{Health, HealthCheck};
+use crate::models::job::{Job, JobId, JobStatus, NewJob, UpdateJobStatus};
+use crate::models::login_attempt::UnlockLogin;
+use crate::models::map_resume_job::{MapResumeJob, MapResumeJobId, NewMapResumeJob};
+use crate::models::permission::Permission;
+use crate::models::recommendation::{CandidateSuggestion, JobRecommendation, MatchScore};
+use crate::models::refresh_token::RefreshTokenRequest;
+use crate::models::resume::{
+    Education, Experience, NewResume, Resume, ResumeFile, ResumeForm, ResumeId, ResumeProfile,
+};
+use crate::models::revoked_token::LogoutRequest;
+use crate::models::role::{AssignRole, Role, RoleId, RoleInfo, RolePermissions};
+use crate::models::user::{AuthInfo, UserId, UserInfo};
+use crate::utils::convert_to_json::{
+    Data, PageInfo, PayloadForLogin, PayloadNoData, PayloadWithData, Problem, ProblemDetail,
+    PROBLEM_CONTENT_TYPE,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Simple Web With Rust", description = "Job board API of companies, jobs, resumes and applications"),
+    paths(
+        controllers::user::register,
+        controllers::user::login,
+        controllers::user::verify_email,
+        controllers::user::resend_verification,
+        controllers::user::forgot_password,
+        controllers::user::reset_password,
+        controllers::user::refresh_token,
+        controllers::user::logout,
+        controllers::user::logout_all,
+        controllers::user::get_user_by_id,
+        controllers::user::get_list_users,
+        controllers::user::update_user,
+        controllers::user::update_password,
+        controllers::user::delete,
+        controllers::user::set_hr_role,
+        controllers::user::set_admin_role,
+        controllers::user::assign_role,
+        controllers::user::unlock_login,
+        controllers::company::create_company,
+        controllers::company::get_list_company,
+        controllers::company::get_company,
+        controllers::company::update_company,
+        controllers::company::delete_company,
+        controllers::job::create_job,
+        controllers::job::get_job,
+        controllers::job::get_list_job,
+        controllers::job::update_job,
+        controllers::job::update_job_status,
+        controllers::job::apply_job,
+        controllers::job::delete_job,
+        controllers::job::get_recommended_jobs,
+        controllers::job::get_suggested_candidates,
+        controllers::resume::create_resume,
+        controllers::resume::get_resume,
+        controllers::resume::get_list_resume_by_user_id,
+        controllers::resume::get_list_resume_by_job,
+        controllers::resume::search_resume,
+        controllers::resume::update_resume,
+        controllers::resume::delete_resume,
+        controllers::resume::download_resume,
+        controllers::role::create_role,
+        controllers::role::get_role,
+        controllers::role::get_list_roles,
+        controllers::role::update_role,
+        controllers::role::delete_role,
+        controllers::role::get_role_permissions,
+        controllers::role::set_role_permissions,
+        controllers::application::update_application_status,
+        controllers::application::withdraw_application,
+        controllers::application::get_application_timeline,
+        controllers::audit::get_list_audit,
+        controllers::health::get_liveness,
+        controllers::health::get_readiness,
+        controllers::health::get_metrics,
+        controllers::jwks::get_jwks,
+        controllers::openapi::get_openapi,
+        controllers::openapi::get_docs_page,
+    ),
+    components(schemas(
+        UserId, UserInfo, AuthInfo, Company, CompanyId, NewCompany, Job, JobId, NewJob,
+        JobStatus, UpdateJobStatus, Resume, ResumeId, NewResume, ResumeFile, ResumeForm,
+        ResumeProfile, Experience, Education, MapResumeJob, MapResumeJobId, NewMapResumeJob,
+        ApplicationStatus, ApplicationStatusChange, ApplicationStatusChangeId,
+        UpdateApplicationStatus, WithdrawApplication, Role, RoleId, RoleInfo, RolePermissions,
+        AssignRole, Permission, RefreshTokenRequest, LogoutRequest, VerifyEmailRequest,
+        EmailRequest, ResetPasswordRequest, UnlockLogin, AuditEntry, AuditEntryId, AuditAction,
+        AuditEntity, Health, HealthCheck, MatchScore, JobRecommendation, CandidateSuggestion,
+        Data, PageInfo,
+        PayloadWithData, PayloadNoData, PayloadForLogin, Problem, ProblemDetail,
+    )),
+    modifiers(&AdminPaths, &ProblemResponses, &BearerAuth),
+)]
+pub struct ApiDoc;
+
+// Admin routes sharing the handler of an user route, (admin path, user path)
+const ADMIN_PATHS: [(&str, &str); 3] = [
+    ("/api/v1/admin/update-admin", "/api/v1/user/update-user"),
+    (
+        "/api/v1/admin/update-password",
+        "/api/v1/user/update-password",
+    ),
+    ("/api/v1/admin/delete-admin", "/api/v1/user/delete-user"),
+];
+
+// Document the admin routes with the operation of the handler they share
+struct AdminPaths;
+
+impl Modify for AdminPaths {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        for (admin_path, user_path) in ADMIN_PATHS {
+            let Some(mut item) = openapi.paths.paths.get(user_path).cloned() else {
+                continue;
+            };
+            if let Some(operation) = item.put.as_mut() {
+                operation.tags = Some(vec!["admin".to_string()]);
+                // Operation ids have to be unique in the document
+                operation.operation_id = operation
+                    .operation_id
+                    .as_ref()
+                    .map(|id| format!("{}_of_admin", id));
+            }
+            openapi.paths.paths.insert(admin_path.to_string(), item);
+        }
+    }
+}
+
+// Every failed request returns a problem response, see `errors::return_error`
+struct ProblemResponses;
+
+impl Modify for ProblemResponses {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let response = ResponseBuilder::new()
+            .description("Problem details of the failed request, RFC 7807")
+            .content(
+                PROBLEM_CONTENT_TYPE,
+                ContentBuilder::new()
+                    .schema(Some(Ref::from_schema_name("Problem")))
+                    .build(),
+            )
+            .build();
+        for item in openapi.paths.paths.values_mut() {
+            let operations = [
+                &mut item.get,
+                &mut item.post,
+                &mut item.put,
+                &mut item.delete,
+            ];
+            for operation in operations.into_iter().flatten() {
+                operation
+                    .responses
+                    .responses
+                    .insert("default".to_string(), response.clone().into());
+            }
+        }
+    }
+}
+
+// Access token sent in the authorization header
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearer_auth",
+                SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+            );
+        }
+    }
+}